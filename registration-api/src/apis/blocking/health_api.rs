@@ -0,0 +1,16 @@
+//! Blocking variant of [`crate::apis::health_api`].
+
+use super::block_on;
+use super::configuration::Configuration;
+use crate::apis::health_api as api;
+use crate::apis::Error;
+
+pub use crate::apis::health_api::{GetHealthAliveError, GetHealthReadyError};
+
+pub fn get_health_alive(configuration: &Configuration) -> Result<(), Error<GetHealthAliveError>> {
+    block_on(api::get_health_alive(configuration))
+}
+
+pub fn get_health_ready(configuration: &Configuration) -> Result<(), Error<GetHealthReadyError>> {
+    block_on(api::get_health_ready(configuration))
+}
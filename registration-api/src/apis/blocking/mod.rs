@@ -0,0 +1,34 @@
+//! Blocking variant of the generated client `apis`.
+//!
+//! Each function runs the `async` function of the same name to completion on a runtime shared by
+//! the blocking client, and takes the same `Configuration`. These should not be called from an
+//! `async` context.
+
+use std::future::Future;
+use std::sync::OnceLock;
+
+use tokio::runtime::{Builder, Runtime};
+
+pub mod health_api;
+pub mod registration_api;
+pub mod subscriptions_api;
+pub mod xapp_api;
+
+pub use super::configuration;
+
+// The `reqwest::Client` of a `Configuration` keeps its connections on the runtime that opened
+// them, so all the calls use one runtime, with a worker thread to drive the idle connections.
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME
+        .get_or_init(|| {
+            Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("registration-api-blocking")
+                .enable_all()
+                .build()
+                .expect("Failed to build the blocking client runtime")
+        })
+        .block_on(future)
+}
@@ -0,0 +1,23 @@
+//! Blocking variant of [`crate::apis::registration_api`].
+
+use super::block_on;
+use super::configuration::Configuration;
+use crate::apis::registration_api as api;
+use crate::apis::Error;
+use crate::models::{DeregisterRequest, RegisterRequest};
+
+pub use crate::apis::registration_api::{DeregisterXappError, RegisterXappError};
+
+pub fn deregister_xapp(
+    configuration: &Configuration,
+    deregister_request: DeregisterRequest,
+) -> Result<(), Error<DeregisterXappError>> {
+    block_on(api::deregister_xapp(configuration, deregister_request))
+}
+
+pub fn register_xapp(
+    configuration: &Configuration,
+    register_request: RegisterRequest,
+) -> Result<(), Error<RegisterXappError>> {
+    block_on(api::register_xapp(configuration, register_request))
+}
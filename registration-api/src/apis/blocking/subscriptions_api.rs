@@ -0,0 +1,51 @@
+//! Blocking variant of [`crate::apis::subscriptions_api`].
+
+use super::block_on;
+use super::configuration::Configuration;
+use crate::apis::subscriptions_api as api;
+use crate::apis::Error;
+use crate::models::{Subscription, SubscriptionRequest, SubscriptionResponse};
+
+pub use crate::apis::subscriptions_api::{
+    AddSubscriptionError, DeleteSubscriptionError, GetSubscriptionByIdError, GetSubscriptionsError,
+    ModifySubscriptionError,
+};
+
+pub fn add_subscription(
+    configuration: &Configuration,
+    subscription_request: SubscriptionRequest,
+) -> Result<SubscriptionResponse, Error<AddSubscriptionError>> {
+    block_on(api::add_subscription(configuration, subscription_request))
+}
+
+pub fn delete_subscription(
+    configuration: &Configuration,
+    subscription_id: &str,
+) -> Result<(), Error<DeleteSubscriptionError>> {
+    block_on(api::delete_subscription(configuration, subscription_id))
+}
+
+pub fn get_subscription_by_id(
+    configuration: &Configuration,
+    subscription_id: &str,
+) -> Result<Subscription, Error<GetSubscriptionByIdError>> {
+    block_on(api::get_subscription_by_id(configuration, subscription_id))
+}
+
+pub fn get_subscriptions(
+    configuration: &Configuration,
+) -> Result<Vec<Subscription>, Error<GetSubscriptionsError>> {
+    block_on(api::get_subscriptions(configuration))
+}
+
+pub fn modify_subscription(
+    configuration: &Configuration,
+    subscription_id: &str,
+    subscription_request: SubscriptionRequest,
+) -> Result<SubscriptionResponse, Error<ModifySubscriptionError>> {
+    block_on(api::modify_subscription(
+        configuration,
+        subscription_id,
+        subscription_request,
+    ))
+}
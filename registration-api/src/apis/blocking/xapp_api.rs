@@ -0,0 +1,133 @@
+//! Blocking variant of [`crate::apis::xapp_api`].
+
+use super::block_on;
+use super::configuration::Configuration;
+use crate::apis::xapp_api as api;
+use crate::apis::Error;
+use crate::models::{
+    ConfigValidationError, DeregisterRequest, RegisterRequest, Subscription, SubscriptionRequest,
+    SubscriptionResponse, XAppConfig, Xapp, XappDescriptor, XappInstance,
+};
+
+pub use crate::apis::xapp_api::{
+    AddSubscriptionError, DeleteSubscriptionError, DeployXappError, DeregisterXappError,
+    GetAllXappConfigError, GetAllXappsError, GetConfigElementError, GetSubscriptionByIdError,
+    GetSubscriptionsError, GetXappByNameError, GetXappInstanceByNameError, ListAllXappsError,
+    ModifySubscriptionError, ModifyXappConfigError, RegisterXappError, UndeployXappError,
+};
+
+pub fn add_subscription(
+    configuration: &Configuration,
+    subscription_request: SubscriptionRequest,
+) -> Result<SubscriptionResponse, Error<AddSubscriptionError>> {
+    block_on(api::add_subscription(configuration, subscription_request))
+}
+
+pub fn delete_subscription(
+    configuration: &Configuration,
+    subscription_id: &str,
+) -> Result<(), Error<DeleteSubscriptionError>> {
+    block_on(api::delete_subscription(configuration, subscription_id))
+}
+
+pub fn deploy_xapp(
+    configuration: &Configuration,
+    xapp_descriptor: Option<XappDescriptor>,
+) -> Result<Xapp, Error<DeployXappError>> {
+    block_on(api::deploy_xapp(configuration, xapp_descriptor))
+}
+
+pub fn deregister_xapp(
+    configuration: &Configuration,
+    deregister_request: DeregisterRequest,
+) -> Result<(), Error<DeregisterXappError>> {
+    block_on(api::deregister_xapp(configuration, deregister_request))
+}
+
+pub fn get_all_xapp_config(
+    configuration: &Configuration,
+) -> Result<Vec<XAppConfig>, Error<GetAllXappConfigError>> {
+    block_on(api::get_all_xapp_config(configuration))
+}
+
+pub fn get_all_xapps(configuration: &Configuration) -> Result<Vec<Xapp>, Error<GetAllXappsError>> {
+    block_on(api::get_all_xapps(configuration))
+}
+
+pub fn get_config_element(
+    configuration: &Configuration,
+    element: &str,
+) -> Result<Vec<XAppConfig>, Error<GetConfigElementError>> {
+    block_on(api::get_config_element(configuration, element))
+}
+
+pub fn get_subscription_by_id(
+    configuration: &Configuration,
+    subscription_id: &str,
+) -> Result<Subscription, Error<GetSubscriptionByIdError>> {
+    block_on(api::get_subscription_by_id(configuration, subscription_id))
+}
+
+pub fn get_subscriptions(
+    configuration: &Configuration,
+) -> Result<Vec<Subscription>, Error<GetSubscriptionsError>> {
+    block_on(api::get_subscriptions(configuration))
+}
+
+pub fn get_xapp_by_name(
+    configuration: &Configuration,
+    x_app_name: &str,
+) -> Result<Xapp, Error<GetXappByNameError>> {
+    block_on(api::get_xapp_by_name(configuration, x_app_name))
+}
+
+pub fn get_xapp_instance_by_name(
+    configuration: &Configuration,
+    x_app_name: &str,
+    x_app_instance_name: &str,
+) -> Result<XappInstance, Error<GetXappInstanceByNameError>> {
+    block_on(api::get_xapp_instance_by_name(
+        configuration,
+        x_app_name,
+        x_app_instance_name,
+    ))
+}
+
+pub fn list_all_xapps(
+    configuration: &Configuration,
+) -> Result<Vec<String>, Error<ListAllXappsError>> {
+    block_on(api::list_all_xapps(configuration))
+}
+
+pub fn modify_subscription(
+    configuration: &Configuration,
+    subscription_id: &str,
+    subscription_request: SubscriptionRequest,
+) -> Result<SubscriptionResponse, Error<ModifySubscriptionError>> {
+    block_on(api::modify_subscription(
+        configuration,
+        subscription_id,
+        subscription_request,
+    ))
+}
+
+pub fn modify_xapp_config(
+    configuration: &Configuration,
+    x_app_config: Option<XAppConfig>,
+) -> Result<Vec<ConfigValidationError>, Error<ModifyXappConfigError>> {
+    block_on(api::modify_xapp_config(configuration, x_app_config))
+}
+
+pub fn register_xapp(
+    configuration: &Configuration,
+    register_request: RegisterRequest,
+) -> Result<(), Error<RegisterXappError>> {
+    block_on(api::register_xapp(configuration, register_request))
+}
+
+pub fn undeploy_xapp(
+    configuration: &Configuration,
+    x_app_name: &str,
+) -> Result<(), Error<UndeployXappError>> {
+    block_on(api::undeploy_xapp(configuration, x_app_name))
+}
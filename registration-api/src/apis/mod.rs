@@ -96,3 +96,6 @@ pub mod subscriptions_api;
 pub mod xapp_api;
 
 pub mod configuration;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
@@ -1,10 +1,7 @@
 #[macro_use]
 extern crate serde_derive;
 
-// DO NOT USE: `api` module
-// Right now we are only using the generated 'models'. Since the generated `api` are `async` and
-// yet not supported fully, simply a support for `models` is present.
-//
-// pub mod apis;
+#[cfg(feature = "client")]
+pub mod apis;
 
 pub mod models;
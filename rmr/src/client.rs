@@ -75,6 +75,14 @@ impl RMRClient {
     pub fn rts_msg(&self, msg: &RMRMessageBuffer) -> Result<(), RMRError> {
         rts_msg_internal(msg)
     }
+
+    /// Send the Message using the RMR Routing Table
+    ///
+    /// The Message Type and Payload of the `msg` should be set before sending. On return, `msg`
+    /// contains the buffer returned by RMR, which should be freed by the caller.
+    pub fn send_msg(&self, msg: &mut RMRMessageBuffer) -> Result<(), RMRError> {
+        send_msg_internal(msg)
+    }
 }
 
 impl Drop for RMRClient {
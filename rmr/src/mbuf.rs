@@ -75,6 +75,7 @@ impl RMRMessageBuffer {
         unsafe {
             (*self.buff).mtype = mtype;
         }
+        self.msgtype = mtype;
     }
 
     pub fn get_state(&self) -> i32 {
@@ -111,6 +112,32 @@ impl RMRMessageBuffer {
     pub fn get_msgtype(&self) -> i32 {
         self.msgtype
     }
+
+    /// Get the Subscription ID of the message (`-1` if not set).
+    pub fn get_sub_id(&self) -> i32 {
+        // Safety: self.buff is a valid pointer. This is because, the structure can only be created
+        // through internal function calls where we can guarantee as implementors that the pointers
+        // passed to the `new` is a valid one.
+        unsafe { (*self.buff).sub_id }
+    }
+
+    /// Get the MEID (Managed Entity ID) of the message, if set.
+    pub fn get_meid(&self) -> Option<String> {
+        let mut meid = [0_u8; rmr_int::RMR_MAX_MEID as usize + 1];
+        // Safety: self.buff is a valid pointer (see above) and `meid` is large enough for the
+        // MEID and the terminating NUL.
+        let result = unsafe { rmr_int::rmr_get_meid(self.buff, meid.as_mut_ptr()) };
+        if result.is_null() {
+            return None;
+        }
+
+        let len = meid.iter().position(|c| *c == 0).unwrap_or(meid.len());
+        if len == 0 {
+            None
+        } else {
+            Some(String::from_utf8_lossy(&meid[..len]).into_owned())
+        }
+    }
 }
 
 unsafe impl Send for RMRMessageBuffer {}
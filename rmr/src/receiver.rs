@@ -17,11 +17,11 @@
 //! Functionality related to receiving RMR messages
 
 use std::convert::TryInto;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{RMRClient, RMRError, RMRMessageBuffer};
 
@@ -34,6 +34,7 @@ pub struct RMRReceiver {
     client: Arc<Mutex<RMRClient>>, // Mainly for using `RMRContext` right now.
     data_tx: Sender<RMRMessageBuffer>, // Received RMR messages will be sent to the channel.
     is_running: Arc<AtomicBool>,   // Required to 'signal' receiver thread to stop.
+    last_active: Arc<AtomicU64>,   // Updated by the receiver thread in every iteration.
 }
 
 impl RMRReceiver {
@@ -47,9 +48,26 @@ impl RMRReceiver {
             client,
             data_tx,
             is_running,
+            last_active: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Time when the receiver thread was last active, in milliseconds since the UNIX Epoch.
+    ///
+    /// The receiver thread updates this at least once every second while it is running, this can
+    /// be used to check whether the receiver thread is alive.
+    pub fn last_active(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.last_active)
+    }
+
+    fn mark_active(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        self.last_active.store(now as u64, Ordering::Relaxed);
+    }
+
     /// Start the Receiver thread
     ///
     /// First waits till the unerlying RMR context is ready. After that registers our own receive
@@ -63,6 +81,7 @@ impl RMRReceiver {
             //Wait for RMR to be Ready first
             loop {
                 let receiver = this.lock().expect("RMRReceiver Lock Corrupted.");
+                receiver.mark_active();
                 let client = receiver.client.lock().expect("RMR ContextMutex Corrupted");
                 if client.is_ready() {
                     drop(client);
@@ -112,6 +131,7 @@ impl RMRReceiver {
                 if !receiver.is_running.load(Ordering::Relaxed) {
                     break;
                 }
+                receiver.mark_active();
                 drop(receiver);
 
                 let mut events = [epoll::Event::new(epoll::Events::empty(), 0); 1];
@@ -129,6 +129,26 @@ pub(crate) fn rts_msg_internal(msg: &RMRMessageBuffer) -> Result<(), RMRError> {
     }
 }
 
+pub(crate) fn send_msg_internal(msg: &mut RMRMessageBuffer) -> Result<(), RMRError> {
+    // Safety: We are making sure that only one client can be constructed and the following
+    // `CONTEXT` can be only accessed through that client.
+    unsafe {
+        let send_buff = rmr_send_msg(CONTEXT, msg.buff);
+        if send_buff.is_null() {
+            return Err(RMRError);
+        }
+
+        // RMR may return a different buffer than the one that was sent, which is to be used (and
+        // freed) by the caller.
+        msg.buff = send_buff;
+        if (*send_buff).state == RMR_OK as i32 {
+            Ok(())
+        } else {
+            Err(RMRError)
+        }
+    }
+}
+
 pub(crate) fn rmr_close_internal() {
     // Safety: We are making sure that only one client can be constructed and the following
     // `CONTEXT` can be only accessed through that client.
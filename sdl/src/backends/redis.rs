@@ -19,6 +19,7 @@
 use std::env;
 use std::iter::{zip, FromIterator};
 use std::str::FromStr;
+use std::time::Duration;
 
 use redis::Commands;
 
@@ -32,6 +33,20 @@ const SERVICE_PORT_NAME_ENV_VAR: &str = "DBAAS_SERVICE_PORT";
 // - "DBAAS_SERVICE_NODE_COUNT
 // - "DBAAS_SERVICE_CLUSTER_ADDR_LIST"
 
+// Span for an SDL operation, entered till the end of the enclosing block.
+#[cfg(feature = "tracing")]
+macro_rules! sdl_span {
+    ($namespace:expr, $operation:expr) => {
+        let _span =
+            tracing::info_span!("sdl", namespace = $namespace, operation = $operation).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! sdl_span {
+    ($namespace:expr, $operation:expr) => {};
+}
+
 // Internal structure holding a redis hostname and port used to connect to redis server.
 #[derive(Debug)]
 struct RedisHostPort {
@@ -141,6 +156,25 @@ impl RedisStorage {
         })
     }
 
+    /// Check the connection to the Redis DB used for the `namespace`.
+    ///
+    /// Unlike `is_ready`, the connection is checked every time. The check fails if there is no
+    /// response from the DB within the `timeout`.
+    pub fn ping(&mut self, namespace: &str, timeout: Duration) -> Result<(), SdlError> {
+        let db = self.db_handle_for_ns(namespace)?;
+        let mut connection = db
+            .get_connection_with_timeout(timeout)
+            .map_err(|e| SdlError::from(e.to_string()))?;
+        connection
+            .set_read_timeout(Some(timeout))
+            .map_err(|e| SdlError::from(e.to_string()))?;
+
+        redis::cmd("PING")
+            .query::<String>(&mut connection)
+            .map(|_| ())
+            .map_err(|e| SdlError::from(e.to_string()))
+    }
+
     // for the redis backend, we will have to use the same keys as are being used by the Go and
     // Python frameworks, otherwise we won't be able to read keys used by code written in SDK used
     // in other languages.
@@ -179,6 +213,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn set(&mut self, namespace: &str, data: &DataMap) -> Result<(), SdlError> {
+        sdl_span!(namespace, "set");
         let db = self.db_handle_for_ns(namespace)?;
         let dataset = data
             .iter()
@@ -195,6 +230,7 @@ impl SdlStorageApi for RedisStorage {
         key: &str,
         value: &[u8],
     ) -> Result<(), SdlError> {
+        sdl_span!(namespace, "set_if_not_exists");
         let db = self.db_handle_for_ns(namespace)?;
         db.set_nx::<_, Vec<u8>, ()>(Self::key_from_ns_and_key(namespace, key), value.to_vec())
             .map_err(|e| SdlError::from(e.to_string()))?;
@@ -203,6 +239,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn get(&mut self, namespace: &str, keys: &KeySet) -> Result<DataMap, SdlError> {
+        sdl_span!(namespace, "get");
         let db = self.db_handle_for_ns(namespace)?;
         let db_keys = keys
             .iter()
@@ -217,6 +254,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn delete(&mut self, namespace: &str, keys: &KeySet) -> Result<(), SdlError> {
+        sdl_span!(namespace, "delete");
         let db = self.db_handle_for_ns(namespace)?;
         let db_keys = keys
             .iter()
@@ -229,6 +267,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn delete_all(&mut self, namespace: &str) -> Result<(), SdlError> {
+        sdl_span!(namespace, "delete_all");
         let db = self.db_handle_for_ns(namespace)?;
         let all_keys_pattern = Self::key_from_ns_and_key(namespace, "*");
         let db_keys = db
@@ -243,6 +282,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn delete_if(&mut self, namespace: &str, key: &str, value: &[u8]) -> Result<bool, SdlError> {
+        sdl_span!(namespace, "delete_if");
         let db = self.db_handle_for_ns(namespace)?;
         let stored_value = db
             .get::<_, Vec<u8>>(Self::key_from_ns_and_key(namespace, key))
@@ -252,6 +292,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn list_keys(&mut self, namespace: &str, pattern: &str) -> Result<KeySet, SdlError> {
+        sdl_span!(namespace, "list_keys");
         let db = self.db_handle_for_ns(namespace)?;
         let keys_pattern = Self::key_from_ns_and_key(namespace, pattern);
         let db_keys = db
@@ -267,6 +308,7 @@ impl SdlStorageApi for RedisStorage {
         group: &str,
         value: &ValueType,
     ) -> Result<(), SdlError> {
+        sdl_span!(namespace, "add_member");
         let db = self.db_handle_for_ns(namespace)?;
         let set_name = Self::key_from_ns_and_key(namespace, group);
         db.sadd(set_name, value)
@@ -280,6 +322,7 @@ impl SdlStorageApi for RedisStorage {
         group: &str,
         value: &ValueType,
     ) -> Result<(), SdlError> {
+        sdl_span!(namespace, "delete_member");
         let db = self.db_handle_for_ns(namespace)?;
         let set_name = Self::key_from_ns_and_key(namespace, group);
         db.srem(set_name, value)
@@ -288,6 +331,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn get_members(&mut self, namespace: &str, group: &str) -> Result<Vec<Vec<u8>>, SdlError> {
+        sdl_span!(namespace, "get_members");
         let db = self.db_handle_for_ns(namespace)?;
         let set_name = Self::key_from_ns_and_key(namespace, group);
         let members = db
@@ -297,6 +341,7 @@ impl SdlStorageApi for RedisStorage {
     }
 
     fn del_group(&mut self, namespace: &str, group: &str) -> Result<(), SdlError> {
+        sdl_span!(namespace, "del_group");
         let db = self.db_handle_for_ns(namespace)?;
         let set_name = Self::key_from_ns_and_key(namespace, group);
         db.del::<_, ()>(set_name)
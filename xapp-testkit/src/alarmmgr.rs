@@ -0,0 +1,221 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Fake of the Alarm Manager
+//!
+//! Keeps the Active Alarms as per the Alarm Messages received, ie. an Alarm is active after it is
+//! raised and until it is cleared. `CLEARALL` clears all the Alarms of the Application.
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use serde::{Deserialize, Serialize};
+
+use crate::server::FakeServer;
+use crate::TestkitError;
+
+/// Path of the Alarms API
+pub const ALARMS_PATH: &str = "/ric/v1/alarms";
+
+/// Path of the Active Alarms API
+pub const ACTIVE_ALARMS_PATH: &str = "/ric/v1/alarms/active";
+
+/// Action of an Alarm Message
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum AlarmAction {
+    #[serde(rename = "RAISE")]
+    Raise,
+
+    #[serde(rename = "CLEAR")]
+    Clear,
+
+    #[serde(rename = "CLEARALL")]
+    ClearAll,
+}
+
+/// Alarm Message received by the fake Alarm Manager
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AlarmMessage {
+    #[serde(rename = "managedObjectId")]
+    pub managed_object_id: String,
+
+    #[serde(rename = "applicationId")]
+    pub application_id: String,
+
+    #[serde(rename = "specificProblem")]
+    pub specific_problem: i32,
+
+    #[serde(rename = "perceivedSeverity")]
+    pub perceived_severity: String,
+
+    #[serde(rename = "identifyingInfo")]
+    pub identifying_info: String,
+
+    #[serde(rename = "additionalInfo")]
+    pub additional_info: String,
+
+    #[serde(rename = "AlarmAction")]
+    pub action: AlarmAction,
+
+    #[serde(rename = "AlarmTime")]
+    pub alarm_time: u64,
+}
+
+#[derive(Default)]
+struct AlarmMgrState {
+    messages: Vec<AlarmMessage>,
+    active: Vec<AlarmMessage>,
+}
+
+// Alarms are matched on the Managed Object, Application, Specific Problem and Identifying Info.
+fn same_alarm(a: &AlarmMessage, b: &AlarmMessage) -> bool {
+    a.managed_object_id == b.managed_object_id
+        && a.application_id == b.application_id
+        && a.specific_problem == b.specific_problem
+        && a.identifying_info == b.identifying_info
+}
+
+type SharedState = Arc<Mutex<AlarmMgrState>>;
+
+/// Fake Alarm Manager
+pub struct FakeAlarmMgr {
+    server: FakeServer,
+    state: SharedState,
+}
+
+impl FakeAlarmMgr {
+    /// Start the fake Alarm Manager on a local port
+    pub fn start() -> Result<Self, TestkitError> {
+        let state = SharedState::default();
+
+        let router = Router::new()
+            .route(ALARMS_PATH, post(alarm))
+            .route(ACTIVE_ALARMS_PATH, get(active_alarms))
+            .with_state(Arc::clone(&state));
+
+        let server = FakeServer::start(router)?;
+
+        Ok(Self { server, state })
+    }
+
+    fake_server_methods!();
+
+    /// Alarm Messages received, in the order they were received
+    pub fn messages(&self) -> Vec<AlarmMessage> {
+        self.lock_state().messages.clone()
+    }
+
+    /// Alarms that are currently active
+    pub fn active_alarms(&self) -> Vec<AlarmMessage> {
+        self.lock_state().active.clone()
+    }
+
+    /// Is the Alarm with the given Alarm ID (Specific Problem) currently active?
+    pub fn is_active(&self, specific_problem: i32) -> bool {
+        self.lock_state()
+            .active
+            .iter()
+            .any(|m| m.specific_problem == specific_problem)
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, AlarmMgrState> {
+        self.state.lock().expect("Corrupted AlarmMgr State Mutex")
+    }
+}
+
+async fn alarm(State(state): State<SharedState>, Json(message): Json<AlarmMessage>) -> StatusCode {
+    let mut state = state.lock().expect("Corrupted AlarmMgr State Mutex");
+
+    match message.action {
+        AlarmAction::Raise => {
+            state.active.retain(|m| !same_alarm(m, &message));
+            state.active.push(message.clone());
+        }
+        AlarmAction::Clear => {
+            state.active.retain(|m| !same_alarm(m, &message));
+        }
+        AlarmAction::ClearAll => {
+            let application_id = &message.application_id;
+            state.active.retain(|m| &m.application_id != application_id);
+        }
+    }
+    state.messages.push(message);
+
+    StatusCode::OK
+}
+
+async fn active_alarms(State(state): State<SharedState>) -> Json<Vec<AlarmMessage>> {
+    let state = state.lock().expect("Corrupted AlarmMgr State Mutex");
+    Json(state.active.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm_message(specific_problem: i32, action: &str) -> serde_json::Value {
+        serde_json::json!({
+            "managedObjectId": "SEP-12/XYZ",
+            "applicationId": "my-xapp",
+            "specificProblem": specific_problem,
+            "perceivedSeverity": "MAJOR",
+            "identifyingInfo": "gnb_1",
+            "additionalInfo": "",
+            "AlarmAction": action,
+            "AlarmTime": 0,
+        })
+    }
+
+    #[test]
+    fn test_raise_clear_alarms() {
+        let alarmmgr = FakeAlarmMgr::start().unwrap();
+        let client = reqwest::blocking::Client::new();
+        let url = format!("{}{}", alarmmgr.url(), ALARMS_PATH);
+
+        for (id, action) in [(8004, "RAISE"), (8005, "RAISE"), (8004, "CLEAR")] {
+            let response = client
+                .post(&url)
+                .json(&alarm_message(id, action))
+                .send()
+                .unwrap();
+            assert!(response.status().is_success());
+        }
+        assert!(!alarmmgr.is_active(8004));
+        assert!(alarmmgr.is_active(8005));
+
+        let active: Vec<AlarmMessage> = client
+            .get(format!("{}{}", alarmmgr.url(), ACTIVE_ALARMS_PATH))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(active, alarmmgr.active_alarms());
+
+        alarmmgr.fail_next(1, 503);
+        let response = client
+            .post(&url)
+            .json(&alarm_message(8005, "CLEARALL"))
+            .send()
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 503);
+        assert!(alarmmgr.is_active(8005));
+        assert_eq!(alarmmgr.messages().len(), 3);
+    }
+}
@@ -0,0 +1,370 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Fake of the App Manager
+//!
+//! Serves the registration, XApp discovery and XApp events subscription APIs of the App Manager.
+//! The XApps listed by the discovery APIs are the ones added using `add_xapp`, along with the
+//! instances that are currently registered. The XApp events are sent to the subscribers using
+//! `notify_xapp_event`.
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+
+use registration_api::models::{
+    DeregisterRequest, EventType, RegisterRequest, Subscription, SubscriptionNotification,
+    SubscriptionRequest, SubscriptionResponse, Xapp, XappInstance,
+};
+
+use crate::server::FakeServer;
+use crate::TestkitError;
+
+/// Path of the Registration API
+pub const REGISTER_PATH: &str = "/ric/v1/register";
+
+/// Path of the Deregistration API
+pub const DEREGISTER_PATH: &str = "/ric/v1/deregister";
+
+/// Path of the XApps Discovery API
+pub const XAPPS_PATH: &str = "/ric/v1/xapps";
+
+/// Path of the XApp Events Subscriptions API
+pub const SUBSCRIPTIONS_PATH: &str = "/ric/v1/subscriptions";
+
+const ALIVE_PATH: &str = "/ric/v1/health/alive";
+const READY_PATH: &str = "/ric/v1/health/ready";
+
+#[derive(Default)]
+struct AppMgrState {
+    registered: Vec<RegisterRequest>,
+    xapps: Vec<Xapp>,
+    subscriptions: Vec<Subscription>,
+    next_subscription_id: u32,
+}
+
+impl AppMgrState {
+    // The XApps added by the test with the registered instances.
+    fn xapps(&self) -> Vec<Xapp> {
+        let mut xapps = self.xapps.clone();
+        for request in &self.registered {
+            let idx = match xapps.iter().position(|x| x.name == request.app_name) {
+                Some(idx) => idx,
+                None => {
+                    xapps.push(Xapp::new(request.app_name.clone()));
+                    xapps.len() - 1
+                }
+            };
+            xapps[idx]
+                .instances
+                .get_or_insert_with(Vec::new)
+                .push(registered_instance(request));
+        }
+        xapps
+    }
+}
+
+// XApp Instance for the Registration Request, with the IP and Port of the HTTP Endpoint.
+fn registered_instance(request: &RegisterRequest) -> XappInstance {
+    let mut instance = XappInstance::new(request.app_instance_name.clone());
+    if let Some((ip, port)) = request.http_endpoint.rsplit_once(':') {
+        instance.ip = Some(ip.to_string());
+        instance.port = port.parse().ok();
+    }
+    instance
+}
+
+type SharedState = Arc<Mutex<AppMgrState>>;
+
+/// Fake App Manager
+pub struct FakeAppMgr {
+    server: FakeServer,
+    state: SharedState,
+}
+
+impl FakeAppMgr {
+    /// Start the fake App Manager on a local port
+    pub fn start() -> Result<Self, TestkitError> {
+        let state = SharedState::default();
+
+        let router = Router::new()
+            .route(REGISTER_PATH, post(register))
+            .route(DEREGISTER_PATH, post(deregister))
+            .route(ALIVE_PATH, get(|| async { StatusCode::OK }))
+            .route(READY_PATH, get(|| async { StatusCode::OK }))
+            .route(XAPPS_PATH, get(list_xapps))
+            .route(&format!("{}/:name", XAPPS_PATH), get(get_xapp))
+            .route(
+                &format!("{}/:name/instances/:instance", XAPPS_PATH),
+                get(get_xapp_instance),
+            )
+            .route(SUBSCRIPTIONS_PATH, post(subscribe).get(list_subscriptions))
+            .route(&format!("{}/:id", SUBSCRIPTIONS_PATH), delete(unsubscribe))
+            .with_state(Arc::clone(&state));
+
+        let server = FakeServer::start(router)?;
+
+        Ok(Self { server, state })
+    }
+
+    fake_server_methods!();
+
+    /// Registration Requests of the XApp instances that are currently registered
+    pub fn registered(&self) -> Vec<RegisterRequest> {
+        self.lock_state().registered.clone()
+    }
+
+    /// Is the XApp instance with the given name currently registered?
+    pub fn is_registered(&self, app_instance_name: &str) -> bool {
+        self.lock_state()
+            .registered
+            .iter()
+            .any(|r| r.app_instance_name == app_instance_name)
+    }
+
+    /// Add an XApp to the XApps listed by the discovery APIs
+    ///
+    /// An XApp with the same name that was added earlier is replaced.
+    pub fn add_xapp(&self, xapp: Xapp) {
+        let mut state = self.lock_state();
+        state.xapps.retain(|x| x.name != xapp.name);
+        state.xapps.push(xapp);
+    }
+
+    /// Remove an XApp added using `add_xapp`
+    pub fn remove_xapp(&self, name: &str) {
+        self.lock_state().xapps.retain(|x| x.name != name);
+    }
+
+    /// Current Subscriptions for the XApp events
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.lock_state().subscriptions.clone()
+    }
+
+    /// Send the XApp event to all the subscribers for the event type of the `notification`
+    ///
+    /// The `id` of the `notification` is set to the ID of each of the subscriptions. Returns the
+    /// number of subscribers notified successfully.
+    pub fn notify_xapp_event(&self, notification: SubscriptionNotification) -> usize {
+        let event_type = notification.event_type.unwrap_or_default();
+        let subscriptions = self.subscriptions();
+
+        let client = reqwest::blocking::Client::new();
+        let mut notified = 0;
+        for subscription in subscriptions {
+            let data = match subscription.data {
+                Some(data) => data,
+                None => continue,
+            };
+            if data.event_type != event_type && data.event_type != EventType::All {
+                continue;
+            }
+
+            let mut notification = notification.clone();
+            notification.id = subscription.id.clone();
+            let result = client
+                .post(&data.target_url)
+                .json(&notification)
+                .send()
+                .map_err(TestkitError::from)
+                .and_then(|response| {
+                    if response.status().is_success() {
+                        Ok(())
+                    } else {
+                        Err(TestkitError(format!("Error : {}", response.status())))
+                    }
+                });
+            match result {
+                Ok(()) => notified += 1,
+                Err(e) => log::warn!("Error notifying '{}': {}", data.target_url, e),
+            }
+        }
+
+        notified
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, AppMgrState> {
+        self.state.lock().expect("Corrupted AppMgr State Mutex")
+    }
+}
+
+async fn register(
+    State(state): State<SharedState>,
+    Json(request): Json<RegisterRequest>,
+) -> StatusCode {
+    let mut state = state.lock().expect("Corrupted AppMgr State Mutex");
+    state
+        .registered
+        .retain(|r| r.app_instance_name != request.app_instance_name);
+    state.registered.push(request);
+
+    StatusCode::CREATED
+}
+
+async fn deregister(
+    State(state): State<SharedState>,
+    Json(request): Json<DeregisterRequest>,
+) -> StatusCode {
+    let mut state = state.lock().expect("Corrupted AppMgr State Mutex");
+    let registered = state.registered.len();
+    state.registered.retain(|r| {
+        r.app_name != request.app_name || r.app_instance_name != request.app_instance_name
+    });
+
+    if state.registered.len() < registered {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+async fn list_xapps(State(state): State<SharedState>) -> Json<Vec<Xapp>> {
+    let state = state.lock().expect("Corrupted AppMgr State Mutex");
+    Json(state.xapps())
+}
+
+async fn get_xapp(State(state): State<SharedState>, Path(name): Path<String>) -> Response {
+    let state = state.lock().expect("Corrupted AppMgr State Mutex");
+    match state.xapps().into_iter().find(|x| x.name == name) {
+        Some(xapp) => Json(xapp).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn get_xapp_instance(
+    State(state): State<SharedState>,
+    Path((name, instance)): Path<(String, String)>,
+) -> Response {
+    let state = state.lock().expect("Corrupted AppMgr State Mutex");
+    let found = state
+        .xapps()
+        .into_iter()
+        .find(|x| x.name == name)
+        .and_then(|x| x.instances)
+        .and_then(|instances| instances.into_iter().find(|i| i.name == instance));
+    match found {
+        Some(instance) => Json(instance).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn subscribe(
+    State(state): State<SharedState>,
+    Json(request): Json<SubscriptionRequest>,
+) -> (StatusCode, Json<SubscriptionResponse>) {
+    let mut state = state.lock().expect("Corrupted AppMgr State Mutex");
+    state.next_subscription_id += 1;
+    let id = state.next_subscription_id.to_string();
+
+    let mut response = SubscriptionResponse::new();
+    response.id = Some(id.clone());
+    response.version = Some(0);
+    response.event_type = Some(request.data.event_type);
+
+    let mut subscription = Subscription::new();
+    subscription.id = Some(id);
+    subscription.data = Some(request.data);
+    state.subscriptions.push(subscription);
+
+    (StatusCode::CREATED, Json(response))
+}
+
+async fn list_subscriptions(State(state): State<SharedState>) -> Json<Vec<Subscription>> {
+    let state = state.lock().expect("Corrupted AppMgr State Mutex");
+    Json(state.subscriptions.clone())
+}
+
+async fn unsubscribe(State(state): State<SharedState>, Path(id): Path<String>) -> StatusCode {
+    let mut state = state.lock().expect("Corrupted AppMgr State Mutex");
+    let subscriptions = state.subscriptions.len();
+    state
+        .subscriptions
+        .retain(|s| s.id.as_deref() != Some(id.as_str()));
+
+    if state.subscriptions.len() < subscriptions {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_discover_deregister() {
+        let appmgr = FakeAppMgr::start().unwrap();
+        appmgr.add_xapp(Xapp::new("other-xapp".to_string()));
+
+        let client = reqwest::blocking::Client::new();
+        let request = RegisterRequest::new(
+            "my-xapp".to_string(),
+            "my-xapp-0".to_string(),
+            "10.0.0.1:8080".to_string(),
+            "10.0.0.1:4560".to_string(),
+        );
+        let response = client
+            .post(format!("{}{}", appmgr.url(), REGISTER_PATH))
+            .json(&request)
+            .send()
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 201);
+        assert!(appmgr.is_registered("my-xapp-0"));
+
+        let xapps: Vec<Xapp> = client
+            .get(format!("{}{}", appmgr.url(), XAPPS_PATH))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(xapps.len(), 2);
+
+        let instance: XappInstance = client
+            .get(format!(
+                "{}{}/my-xapp/instances/my-xapp-0",
+                appmgr.url(),
+                XAPPS_PATH
+            ))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(instance.ip.as_deref(), Some("10.0.0.1"));
+        assert_eq!(instance.port, Some(8080));
+
+        appmgr.fail_next_on(DEREGISTER_PATH, 1, 503);
+        let deregister = DeregisterRequest::new("my-xapp".to_string(), "my-xapp-0".to_string());
+        let url = format!("{}{}", appmgr.url(), DEREGISTER_PATH);
+        let response = client.post(&url).json(&deregister).send().unwrap();
+        assert_eq!(response.status().as_u16(), 503);
+        assert!(appmgr.is_registered("my-xapp-0"));
+
+        let response = client.post(&url).json(&deregister).send().unwrap();
+        assert_eq!(response.status().as_u16(), 204);
+        assert!(!appmgr.is_registered("my-xapp-0"));
+
+        assert_eq!(appmgr.requests_to("POST", DEREGISTER_PATH).len(), 2);
+        let recorded: DeregisterRequest = appmgr.requests_to("POST", DEREGISTER_PATH)[0]
+            .json()
+            .unwrap();
+        assert_eq!(recorded, deregister);
+    }
+}
@@ -0,0 +1,39 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+/// TestkitError: Errors in starting the fakes or in sending the notifications from the fakes.
+#[derive(Debug)]
+pub struct TestkitError(pub(crate) String);
+
+impl std::fmt::Display for TestkitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for TestkitError {}
+
+impl From<std::io::Error> for TestkitError {
+    fn from(e: std::io::Error) -> Self {
+        TestkitError(format!("IO Error: {}", e))
+    }
+}
+
+impl From<reqwest::Error> for TestkitError {
+    fn from(e: reqwest::Error) -> Self {
+        TestkitError(format!("Request Error: {}", e))
+    }
+}
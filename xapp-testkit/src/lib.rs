@@ -0,0 +1,107 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! In-process fakes of the RIC Platform services for testing XApps
+//!
+//! The App Manager, Subscription Manager and the Alarm Manager are faked by `axum` servers
+//! listening on local ports. The fakes record the requests received, return scripted responses
+//! and can be made to fail the requests. The fake Subscription Manager notifies the final result
+//! of the Subscriptions asynchronously, like the Subscription Manager of the Platform.
+//!
+//! ```ignore
+//!     let platform = FakePlatform::start()?;
+//!     let endpoints = platform.endpoints();
+//!     xapp.set_platform_endpoints(PlatformEndpoints {
+//!         appmgr: endpoints.appmgr,
+//!         submgr: endpoints.submgr,
+//!         alarmmgr: endpoints.alarmmgr,
+//!     });
+//!
+//!     platform.submgr.push_outcome(SubscriptionOutcome::Failure {
+//!         cause: "RAN Function not supported".to_string(),
+//!         source: ErrorSource::E2Node,
+//!     });
+//!     let result = xapp.subscribe_with_notification(params)?.wait(Duration::from_secs(5))?;
+//!     assert!(!result.is_success());
+//!
+//!     platform.appmgr.fail_next(1, 503);
+//!     assert!(xapp.register_xapp("my-xapp", "my-xapp-0", "{}", None).is_err());
+//! ```
+
+#[macro_use]
+mod server;
+pub use server::{FakeServer, RecordedRequest};
+
+mod error;
+pub use error::TestkitError;
+
+pub mod alarmmgr;
+pub use alarmmgr::FakeAlarmMgr;
+
+pub mod appmgr;
+pub use appmgr::FakeAppMgr;
+
+pub mod submgr;
+pub use submgr::{FakeSubMgr, FakeSubscription, SubscriptionOutcome};
+
+pub use ric_subscriptions::models::subscription_instance::{ErrorSource, TimeoutType};
+
+/// Base URLs of the fakes started by a `FakePlatform`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FakeEndpoints {
+    /// Base URL of the fake App Manager
+    pub appmgr: String,
+
+    /// Base URL of the fake Subscription Manager
+    pub submgr: String,
+
+    /// Base URL of the fake Alarm Manager
+    pub alarmmgr: String,
+}
+
+/// Fakes of all the RIC Platform services used by the XApp
+pub struct FakePlatform {
+    /// Fake App Manager
+    pub appmgr: FakeAppMgr,
+
+    /// Fake Subscription Manager
+    pub submgr: FakeSubMgr,
+
+    /// Fake Alarm Manager
+    pub alarmmgr: FakeAlarmMgr,
+}
+
+impl FakePlatform {
+    /// Start all the fakes, each on a local port
+    ///
+    /// The fakes are stopped when the `FakePlatform` is dropped.
+    pub fn start() -> Result<Self, TestkitError> {
+        Ok(Self {
+            appmgr: FakeAppMgr::start()?,
+            submgr: FakeSubMgr::start()?,
+            alarmmgr: FakeAlarmMgr::start()?,
+        })
+    }
+
+    /// Base URLs of the fakes, for the `PlatformEndpoints` of the XApp
+    pub fn endpoints(&self) -> FakeEndpoints {
+        FakeEndpoints {
+            appmgr: self.appmgr.url().to_string(),
+            submgr: self.submgr.url().to_string(),
+            alarmmgr: self.alarmmgr.url().to_string(),
+        }
+    }
+}
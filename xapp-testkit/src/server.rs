@@ -0,0 +1,294 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! In-process HTTP server of the fakes
+//!
+//! Every fake is an `axum` `Router` served on a local port from a thread of its own. All the
+//! requests to a fake pass through a middleware, that records the request and applies the
+//! delays and failures injected by the test, before the request reaches the fake. The tests can
+//! serve their own routes (eg. the endpoints of the XApp) the same way, using `FakeServer`.
+
+use std::collections::VecDeque;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde::de::DeserializeOwned;
+use tokio::sync::oneshot;
+
+use crate::TestkitError;
+
+/// A Request received by a fake
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// HTTP Method of the Request (eg. `POST`)
+    pub method: String,
+
+    /// Path of the Request (eg. `/ric/v1/register`)
+    pub path: String,
+
+    /// Body of the Request
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Deserialize the JSON Body of the Request
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+// A Failure injected for the next request (to the `path`, if given).
+struct Failure {
+    path: Option<String>,
+    status: StatusCode,
+}
+
+// Requests recorded by and the Failures injected in a fake.
+#[derive(Default)]
+pub(crate) struct Control {
+    requests: Mutex<Vec<RecordedRequest>>,
+    failures: Mutex<VecDeque<Failure>>,
+    delay: Mutex<Duration>,
+}
+
+impl Control {
+    pub(crate) fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .expect("Corrupted Requests Mutex")
+            .clone()
+    }
+
+    pub(crate) fn clear_requests(&self) {
+        self.requests
+            .lock()
+            .expect("Corrupted Requests Mutex")
+            .clear();
+    }
+
+    pub(crate) fn fail_next(&self, path: Option<&str>, count: usize, status: u16) {
+        let status = StatusCode::from_u16(status).expect("Invalid HTTP Status");
+        let mut failures = self.failures.lock().expect("Corrupted Failures Mutex");
+        for _ in 0..count {
+            failures.push_back(Failure {
+                path: path.map(|p| p.to_string()),
+                status,
+            });
+        }
+    }
+
+    pub(crate) fn clear_failures(&self) {
+        self.failures
+            .lock()
+            .expect("Corrupted Failures Mutex")
+            .clear();
+    }
+
+    pub(crate) fn set_delay(&self, delay: Duration) {
+        *self.delay.lock().expect("Corrupted Delay Mutex") = delay;
+    }
+
+    fn record(&self, request: RecordedRequest) {
+        self.requests
+            .lock()
+            .expect("Corrupted Requests Mutex")
+            .push(request);
+    }
+
+    fn delay(&self) -> Duration {
+        *self.delay.lock().expect("Corrupted Delay Mutex")
+    }
+
+    // Takes the first Failure injected for all requests or for the given `path`.
+    fn take_failure(&self, path: &str) -> Option<StatusCode> {
+        let mut failures = self.failures.lock().expect("Corrupted Failures Mutex");
+        let idx = failures
+            .iter()
+            .position(|f| f.path.as_deref().is_none_or(|p| p == path))?;
+        failures.remove(idx).map(|f| f.status)
+    }
+}
+
+async fn intercept(
+    State(control): State<Arc<Control>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+
+    let path = parts.uri.path().to_string();
+    control.record(RecordedRequest {
+        method: parts.method.to_string(),
+        path: path.clone(),
+        body: body.to_vec(),
+    });
+
+    let delay = control.delay();
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+
+    if let Some(status) = control.take_failure(&path) {
+        log::debug!("Injected Failure {} for {} {}", status, parts.method, path);
+        return (status, "Injected Failure").into_response();
+    }
+
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+/// An HTTP Server for the routes of a test, serving them on a local port
+///
+/// The requests are recorded like the requests to the fakes. The server is shut down when
+/// dropped.
+pub struct FakeServer {
+    url: String,
+    pub(crate) control: Arc<Control>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeServer {
+    /// Start serving the `router` on a local port
+    pub fn start(router: Router) -> Result<Self, TestkitError> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let control = Arc::new(Control::default());
+        let router = router.layer(middleware::from_fn_with_state(
+            Arc::clone(&control),
+            intercept,
+        ));
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().expect("Error creating Tokio Runtime");
+            runtime.block_on(async move {
+                let server = match axum::Server::from_tcp(listener) {
+                    Ok(server) => server,
+                    Err(e) => {
+                        log::error!("Error starting the Fake Server: {}", e);
+                        return;
+                    }
+                };
+                let result = server
+                    .serve(router.into_make_service())
+                    .with_graceful_shutdown(async {
+                        let _ = shutdown_rx.await;
+                    })
+                    .await;
+                if let Err(e) = result {
+                    log::error!("Fake Server Error: {}", e);
+                }
+            });
+        });
+
+        Ok(Self {
+            url,
+            control,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+
+    /// Base URL of the server (eg. `http://127.0.0.1:40123`)
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Requests received by the server, in the order they were received
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.control.requests()
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Methods common to all the fakes, for the requests recorded and the failures injected. The fake
+// should have a `server: FakeServer` field.
+macro_rules! fake_server_methods {
+    () => {
+        /// Base URL of the fake (eg. `http://127.0.0.1:40123`)
+        pub fn url(&self) -> &str {
+            self.server.url()
+        }
+
+        /// Requests received by the fake, in the order they were received
+        ///
+        /// This includes the requests that were failed using `fail_next`.
+        pub fn requests(&self) -> Vec<$crate::RecordedRequest> {
+            self.server.control.requests()
+        }
+
+        /// Requests received by the fake with the given `method` and `path`
+        pub fn requests_to(&self, method: &str, path: &str) -> Vec<$crate::RecordedRequest> {
+            self.requests()
+                .into_iter()
+                .filter(|r| r.method == method && r.path == path)
+                .collect()
+        }
+
+        /// Forget the requests received so far
+        pub fn clear_requests(&self) {
+            self.server.control.clear_requests()
+        }
+
+        /// Fail the next `count` requests with the HTTP `status`
+        ///
+        /// Panics if `status` is not a valid HTTP Status.
+        pub fn fail_next(&self, count: usize, status: u16) {
+            self.server.control.fail_next(None, count, status)
+        }
+
+        /// Fail the next `count` requests to the `path` with the HTTP `status`
+        ///
+        /// Panics if `status` is not a valid HTTP Status.
+        pub fn fail_next_on(&self, path: &str, count: usize, status: u16) {
+            self.server.control.fail_next(Some(path), count, status)
+        }
+
+        /// Remove the failures injected that are not yet used
+        pub fn clear_failures(&self) {
+            self.server.control.clear_failures()
+        }
+
+        /// Delay all the responses of the fake by `delay`
+        pub fn set_delay(&self, delay: std::time::Duration) {
+            self.server.control.set_delay(delay)
+        }
+    };
+}
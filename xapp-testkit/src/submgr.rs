@@ -0,0 +1,459 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Fake of the Subscription Manager
+//!
+//! Subscriptions are accepted with a Subscription ID and E2 Event Instance IDs allocated by the
+//! fake. The final result of each Subscription is then notified asynchronously to the client
+//! endpoint in the `SubscriptionParams`, as scripted using `set_outcome` or `push_outcome`. A
+//! notification can also be sent explicitly using `notify`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, post};
+use axum::{Json, Router};
+
+use ric_subscriptions::models::{
+    subscription_instance::{ErrorSource, TimeoutType},
+    SubscriptionData, SubscriptionInstance, SubscriptionParams, SubscriptionResponse,
+};
+
+use crate::server::FakeServer;
+use crate::TestkitError;
+
+/// Path of the Subscriptions API
+pub const SUBSCRIPTIONS_PATH: &str = "/ric/v1/subscriptions";
+
+// Path of the XApp end point for the Subscription Notifications.
+const NOTIFICATION_PATH: &str = "/ric/v1/subscriptions";
+
+/// Final result of a Subscription, notified by the fake Subscription Manager
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubscriptionOutcome {
+    /// All the E2 Event Instances of the Subscription succeed
+    Success,
+
+    /// All the E2 Event Instances of the Subscription fail with the given cause
+    Failure {
+        /// Cause of the Failure
+        cause: String,
+
+        /// Source of the Failure
+        source: ErrorSource,
+    },
+
+    /// All the E2 Event Instances of the Subscription time out
+    Timeout(TimeoutType),
+
+    /// No notification is sent for the Subscription
+    Silent,
+}
+
+impl SubscriptionOutcome {
+    // Final result of the instance as per the outcome.
+    fn apply(&self, instance: &mut SubscriptionInstance) {
+        match self {
+            Self::Failure { cause, source } => {
+                instance.error_cause = Some(cause.clone());
+                instance.error_source = Some(*source);
+            }
+            Self::Timeout(timeout_type) => {
+                instance.error_cause = Some("Timeout".to_string());
+                instance.timeout_type = Some(*timeout_type);
+            }
+            Self::Success | Self::Silent => {}
+        }
+    }
+}
+
+/// A Subscription accepted by the fake Subscription Manager
+#[derive(Clone, Debug, PartialEq)]
+pub struct FakeSubscription {
+    /// Subscription ID allocated by the fake
+    pub subscription_id: String,
+
+    /// Parameters of the Subscription Request
+    pub params: SubscriptionParams,
+
+    /// E2 Event Instances allocated for the Subscription
+    pub instances: Vec<SubscriptionInstance>,
+}
+
+impl FakeSubscription {
+    // URL of the XApp end point for the Subscription Notifications.
+    fn notification_url(&self) -> Option<String> {
+        let endpoint = &self.params.client_endpoint;
+        match (&endpoint.host, endpoint.http_port) {
+            (Some(host), Some(port)) => {
+                Some(format!("http://{}:{}{}", host, port, NOTIFICATION_PATH))
+            }
+            _ => None,
+        }
+    }
+}
+
+struct SubMgrState {
+    subscriptions: Vec<FakeSubscription>,
+    notifications: Vec<SubscriptionResponse>,
+    outcomes: VecDeque<SubscriptionOutcome>,
+    default_outcome: SubscriptionOutcome,
+    notification_delay: Duration,
+    next_subscription_id: u32,
+    next_e2_instance_id: u32,
+}
+
+impl Default for SubMgrState {
+    fn default() -> Self {
+        Self {
+            subscriptions: vec![],
+            notifications: vec![],
+            outcomes: VecDeque::new(),
+            default_outcome: SubscriptionOutcome::Success,
+            notification_delay: Duration::ZERO,
+            next_subscription_id: 0,
+            next_e2_instance_id: 0,
+        }
+    }
+}
+
+type SharedState = Arc<Mutex<SubMgrState>>;
+
+/// Fake Subscription Manager
+pub struct FakeSubMgr {
+    server: FakeServer,
+    state: SharedState,
+}
+
+impl FakeSubMgr {
+    /// Start the fake Subscription Manager on a local port
+    ///
+    /// All the Subscriptions succeed, unless scripted otherwise.
+    pub fn start() -> Result<Self, TestkitError> {
+        let state = SharedState::default();
+
+        let router = Router::new()
+            .route(SUBSCRIPTIONS_PATH, post(subscribe).get(list_subscriptions))
+            .route(&format!("{}/:id", SUBSCRIPTIONS_PATH), delete(unsubscribe))
+            .with_state(Arc::clone(&state));
+
+        let server = FakeServer::start(router)?;
+
+        Ok(Self { server, state })
+    }
+
+    fake_server_methods!();
+
+    /// Set the outcome for the Subscriptions that do not have an outcome pushed using
+    /// `push_outcome`
+    pub fn set_outcome(&self, outcome: SubscriptionOutcome) {
+        self.lock_state().default_outcome = outcome;
+    }
+
+    /// Push the outcome for the next Subscription
+    ///
+    /// The outcomes pushed are used for the Subscriptions in the order they are pushed.
+    pub fn push_outcome(&self, outcome: SubscriptionOutcome) {
+        self.lock_state().outcomes.push_back(outcome);
+    }
+
+    /// Delay the notifications by `delay` after the Subscription Response
+    pub fn set_notification_delay(&self, delay: Duration) {
+        self.lock_state().notification_delay = delay;
+    }
+
+    /// Subscriptions that are currently active
+    pub fn subscriptions(&self) -> Vec<FakeSubscription> {
+        self.lock_state().subscriptions.clone()
+    }
+
+    /// Notifications sent so far, in the order they were sent
+    pub fn notifications(&self) -> Vec<SubscriptionResponse> {
+        self.lock_state().notifications.clone()
+    }
+
+    /// Send a notification with the given `instances` for the Subscription
+    ///
+    /// Returns an error if the Subscription is not active, or if the XApp does not accept the
+    /// notification.
+    pub fn notify(
+        &self,
+        subscription_id: &str,
+        instances: Vec<SubscriptionInstance>,
+    ) -> Result<(), TestkitError> {
+        let url = {
+            let state = self.lock_state();
+            let subscription = state
+                .subscriptions
+                .iter()
+                .find(|s| s.subscription_id == subscription_id)
+                .ok_or_else(|| {
+                    TestkitError(format!("Subscription '{}' Not Found.", subscription_id))
+                })?;
+            subscription.notification_url().ok_or_else(|| {
+                TestkitError(format!(
+                    "Client Endpoint of Subscription '{}' is incomplete.",
+                    subscription_id
+                ))
+            })?
+        };
+
+        let notification = SubscriptionResponse::new(subscription_id.to_string(), instances);
+        self.lock_state().notifications.push(notification.clone());
+
+        let response = reqwest::blocking::Client::new()
+            .post(&url)
+            .json(&notification)
+            .send()?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(TestkitError(format!("Error : {}", response.status())))
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, SubMgrState> {
+        self.state.lock().expect("Corrupted SubMgr State Mutex")
+    }
+}
+
+async fn subscribe(
+    State(state): State<SharedState>,
+    Json(params): Json<SubscriptionParams>,
+) -> Response {
+    let (subscription, outcome, delay) = {
+        let mut state = state.lock().expect("Corrupted SubMgr State Mutex");
+
+        state.next_subscription_id += 1;
+        let subscription_id = params
+            .subscription_id
+            .clone()
+            .unwrap_or_else(|| state.next_subscription_id.to_string());
+
+        let mut instances = vec![];
+        for detail in &params.subscription_details {
+            state.next_e2_instance_id += 1;
+            instances.push(SubscriptionInstance::new(
+                detail.xapp_event_instance_id,
+                state.next_e2_instance_id,
+            ));
+        }
+
+        let subscription = FakeSubscription {
+            subscription_id,
+            params,
+            instances,
+        };
+        if subscription.notification_url().is_none() {
+            return (StatusCode::BAD_REQUEST, "Invalid Client Endpoint").into_response();
+        }
+        state.subscriptions.push(subscription.clone());
+
+        let outcome = state
+            .outcomes
+            .pop_front()
+            .unwrap_or_else(|| state.default_outcome.clone());
+        (subscription, outcome, state.notification_delay)
+    };
+
+    let response = SubscriptionResponse::new(
+        subscription.subscription_id.clone(),
+        subscription.instances.clone(),
+    );
+
+    if outcome != SubscriptionOutcome::Silent {
+        tokio::spawn(send_notification(state, subscription, outcome, delay));
+    }
+
+    (StatusCode::CREATED, Json(response)).into_response()
+}
+
+// Sends the notification for the Subscription as per the `outcome`, after the `delay`.
+async fn send_notification(
+    state: SharedState,
+    subscription: FakeSubscription,
+    outcome: SubscriptionOutcome,
+    delay: Duration,
+) {
+    tokio::time::sleep(delay).await;
+
+    let mut instances = subscription.instances.clone();
+    instances.iter_mut().for_each(|i| outcome.apply(i));
+    let notification = SubscriptionResponse::new(subscription.subscription_id.clone(), instances);
+
+    state
+        .lock()
+        .expect("Corrupted SubMgr State Mutex")
+        .notifications
+        .push(notification.clone());
+
+    let url = match subscription.notification_url() {
+        Some(url) => url,
+        None => return,
+    };
+    log::debug!(
+        "Notifying Subscription {} to {}",
+        subscription.subscription_id,
+        url
+    );
+    let result = reqwest::Client::new()
+        .post(&url)
+        .json(&notification)
+        .send()
+        .await;
+    match result {
+        Ok(response) if !response.status().is_success() => {
+            log::warn!("Notification to '{}' failed: {}", url, response.status());
+        }
+        Err(e) => log::warn!("Error sending Notification to '{}': {}", url, e),
+        Ok(_) => {}
+    }
+}
+
+async fn list_subscriptions(State(state): State<SharedState>) -> Json<Vec<SubscriptionData>> {
+    let state = state.lock().expect("Corrupted SubMgr State Mutex");
+    let subscriptions = state
+        .subscriptions
+        .iter()
+        .map(|s| {
+            let endpoint = &s.params.client_endpoint;
+            let mut data = SubscriptionData::new();
+            data.subscription_id = s.subscription_id.parse().ok();
+            data.meid = Some(s.params.meid.clone());
+            data.client_endpoint = Some(
+                endpoint
+                    .host
+                    .iter()
+                    .zip(endpoint.http_port.iter())
+                    .map(|(host, port)| format!("{}:{}", host, port))
+                    .collect(),
+            );
+            data.subscription_instances = Some(s.instances.clone());
+            data
+        })
+        .collect();
+
+    Json(subscriptions)
+}
+
+async fn unsubscribe(State(state): State<SharedState>, Path(id): Path<String>) -> StatusCode {
+    let mut state = state.lock().expect("Corrupted SubMgr State Mutex");
+    let subscriptions = state.subscriptions.len();
+    state.subscriptions.retain(|s| s.subscription_id != id);
+
+    if state.subscriptions.len() < subscriptions {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ric_subscriptions::models::{SubscriptionDetail, SubscriptionParamsClientEndpoint};
+
+    // An XApp end point for the notifications, returns the notifications received.
+    fn notification_receiver() -> (FakeServer, std::sync::mpsc::Receiver<SubscriptionResponse>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = Arc::new(Mutex::new(tx));
+
+        let router = Router::new().route(
+            NOTIFICATION_PATH,
+            post(
+                |Json(notification): Json<SubscriptionResponse>| async move {
+                    let _ = tx.lock().unwrap().send(notification);
+                    StatusCode::OK
+                },
+            ),
+        );
+
+        (FakeServer::start(router).unwrap(), rx)
+    }
+
+    fn subscription_params(http_port: u32) -> SubscriptionParams {
+        let mut endpoint = SubscriptionParamsClientEndpoint::new();
+        endpoint.host = Some("127.0.0.1".to_string());
+        endpoint.http_port = Some(http_port);
+
+        let details = vec![
+            SubscriptionDetail::new(1, vec![1], vec![]),
+            SubscriptionDetail::new(2, vec![1], vec![]),
+        ];
+
+        SubscriptionParams::new(endpoint, "gnb_1".to_string(), 1, details)
+    }
+
+    #[test]
+    fn test_subscribe_scripted_notifications() {
+        let submgr = FakeSubMgr::start().unwrap();
+        let (receiver, notifications) = notification_receiver();
+        let port = receiver.url().rsplit_once(':').unwrap().1.parse().unwrap();
+        let client = reqwest::blocking::Client::new();
+        let url = format!("{}{}", submgr.url(), SUBSCRIPTIONS_PATH);
+
+        let response: SubscriptionResponse = client
+            .post(&url)
+            .json(&subscription_params(port))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(response.subscription_instances.len(), 2);
+
+        let notification = notifications.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(notification.subscription_id, response.subscription_id);
+        assert!(notification
+            .subscription_instances
+            .iter()
+            .all(|i| i.error_cause.is_none()));
+
+        submgr.push_outcome(SubscriptionOutcome::Failure {
+            cause: "RAN Function not supported".to_string(),
+            source: ErrorSource::E2Node,
+        });
+        let response: SubscriptionResponse = client
+            .post(&url)
+            .json(&subscription_params(port))
+            .send()
+            .unwrap()
+            .json()
+            .unwrap();
+        let notification = notifications.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(notification.subscription_id, response.subscription_id);
+        assert!(notification
+            .subscription_instances
+            .iter()
+            .all(|i| i.error_source == Some(ErrorSource::E2Node)));
+
+        submgr.fail_next_on(SUBSCRIPTIONS_PATH, 1, 500);
+        let response = client
+            .post(&url)
+            .json(&subscription_params(port))
+            .send()
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 500);
+
+        assert_eq!(submgr.subscriptions().len(), 2);
+        assert_eq!(submgr.notifications().len(), 2);
+        assert_eq!(submgr.requests_to("POST", SUBSCRIPTIONS_PATH).len(), 3);
+    }
+}
@@ -0,0 +1,179 @@
+#![cfg(test)]
+
+use std::time::{Duration, Instant};
+
+use xapp::{
+    ActionBuilder, ActionType, PlatformEndpoints, SubscribeError, SubscriptionDetailBuilder, XApp,
+};
+use xapp_testkit::{ErrorSource, FakePlatform, SubscriptionOutcome};
+
+use ric_subscriptions::models::{SubscriptionParams, SubscriptionParamsClientEndpoint};
+
+fn get_config_data(xapp_name: &str, http_port: u16) -> xapp::XAppConfig {
+    let config_json = format!(
+        r#"{{
+        "messaging": {{
+            "ports" : [
+                {{
+                    "name": "rmrdata",
+                    "port": 4560
+                }},
+                {{
+                    "name": "http",
+                    "port": {}
+                }}
+            ]
+        }}
+    }}"#,
+        http_port
+    );
+
+    xapp::XAppConfig {
+        metadata: Box::new(xapp::ConfigMetadata {
+            xapp_name: xapp_name.to_string(),
+            config_type: "json".to_string(),
+        }),
+        config: serde_json::from_str(&config_json).unwrap(),
+    }
+}
+
+fn platform_endpoints(platform: &FakePlatform) -> PlatformEndpoints {
+    let endpoints = platform.endpoints();
+    PlatformEndpoints {
+        appmgr: endpoints.appmgr,
+        submgr: endpoints.submgr,
+        alarmmgr: endpoints.alarmmgr,
+    }
+}
+
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+fn wait_until<F: Fn() -> bool>(condition: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+// Starts the XApp with its Web Server on a free port. Someone else may bind the port before the
+// Web Server does, in which case the XApp is started again on another port.
+fn start_xapp(xapp_name: &str, platform: &FakePlatform) -> (XApp, u16) {
+    for _ in 0..5 {
+        let http_port = free_port();
+        let (app_tx, _) = std::sync::mpsc::channel();
+        let mut xapp = XApp::from_config(get_config_data(xapp_name, http_port), app_tx).unwrap();
+        xapp.set_platform_endpoints(platform_endpoints(platform));
+        xapp.start();
+
+        let alive_url = format!("http://127.0.0.1:{}/ric/v1/health/alive", http_port);
+        if wait_until(|| reqwest::blocking::get(&alive_url).is_ok_and(|r| r.status().is_success()))
+        {
+            return (xapp, http_port);
+        }
+
+        xapp.stop();
+        let _ = xapp.join();
+    }
+    panic!("Web Server of the XApp did not start.");
+}
+
+fn subscription_params(http_port: u16) -> SubscriptionParams {
+    let mut endpoint = SubscriptionParamsClientEndpoint::new();
+    endpoint.host = Some("127.0.0.1".to_string());
+    endpoint.http_port = Some(http_port as u32);
+
+    xapp::SubscriptionParamsBuilder::new("gnb_734_733_b5c67788", 1)
+        .client_endpoint(endpoint)
+        .subscription_detail(
+            SubscriptionDetailBuilder::new(1, vec![1, 2])
+                .action(ActionBuilder::new(1, ActionType::Report).definition(vec![1])),
+        )
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_registration_with_fake_appmgr() {
+    let platform = FakePlatform::start().unwrap();
+
+    // The XApp is not started, the HTTP port is only used for the registration.
+    let (app_tx, _) = std::sync::mpsc::channel();
+    let mut xapp = XApp::from_config(get_config_data("testkit-reg", 8080), app_tx).unwrap();
+    xapp.set_platform_endpoints(platform_endpoints(&platform));
+    xapp.set_service_endpoints("127.0.0.1:8080", "127.0.0.1:4560");
+
+    platform
+        .appmgr
+        .fail_next_on(xapp_testkit::appmgr::REGISTER_PATH, 1, 503);
+    let result = xapp.register_xapp("testkit-reg", "testkit-reg-0", "{}", None);
+    assert!(result.is_err());
+    assert!(!platform.appmgr.is_registered("testkit-reg-0"));
+
+    let result = xapp.register_xapp("testkit-reg", "testkit-reg-0", "{}", None);
+    assert!(result.is_ok(), "{}", result.err().unwrap());
+    assert!(platform.appmgr.is_registered("testkit-reg-0"));
+
+    let instance = xapp.get_xapp_instance("testkit-reg", "testkit-reg-0");
+    assert!(instance.is_ok(), "{}", instance.err().unwrap());
+
+    let result = xapp.deregister_xapp();
+    assert!(result.is_ok(), "{}", result.err().unwrap());
+    assert!(!platform.appmgr.is_registered("testkit-reg-0"));
+
+    let requests = platform
+        .appmgr
+        .requests_to("POST", xapp_testkit::appmgr::REGISTER_PATH);
+    assert_eq!(requests.len(), 2);
+}
+
+#[test]
+fn test_subscriptions_and_alarms_with_fake_platform() {
+    let platform = FakePlatform::start().unwrap();
+
+    let (mut xapp, http_port) = start_xapp("testkit-sub", &platform);
+
+    let pending = xapp
+        .subscribe_with_notification(subscription_params(http_port))
+        .unwrap();
+    let result = pending.wait(Duration::from_secs(5)).unwrap();
+    assert!(result.is_success(), "{:?}", result);
+
+    platform.submgr.push_outcome(SubscriptionOutcome::Failure {
+        cause: "RAN Function not supported".to_string(),
+        source: ErrorSource::E2Node,
+    });
+    let pending = xapp
+        .subscribe_with_notification(subscription_params(http_port))
+        .unwrap();
+    let result = pending.wait(Duration::from_secs(5)).unwrap();
+    assert!(!result.is_success(), "{:?}", result);
+
+    platform
+        .submgr
+        .fail_next_on(xapp_testkit::submgr::SUBSCRIPTIONS_PATH, 1, 500);
+    let result = xapp.subscribe(subscription_params(http_port));
+    assert!(matches!(result, Err(SubscribeError::Status500(_))));
+    assert_eq!(platform.submgr.subscriptions().len(), 2);
+
+    let result = xapp.raise_alarm(
+        8004,
+        xapp::AlarmSeverity::Major,
+        "gnb_734_733_b5c67788".to_string(),
+        "".to_string(),
+    );
+    assert!(result.is_ok(), "{}", result.err().unwrap());
+    assert!(wait_until(|| platform.alarmmgr.is_active(8004)));
+
+    let active = xapp.list_active_alarms().unwrap();
+    assert_eq!(active.alarm_manager.len(), 1);
+
+    xapp.stop();
+    let _ = xapp.join();
+}
@@ -69,6 +69,8 @@ fn main() {
     let (app_tx, app_rx) = std::sync::mpsc::channel();
     let mut xapp = XApp::from_config(get_config_data(), app_tx).unwrap();
 
+    // Messages are received on the channel, till the XApp is stopped by a SIGTERM or SIGINT.
+    xapp.enable_signal_handling();
     xapp.start();
 
     loop {
@@ -91,5 +93,8 @@ fn main() {
 
     xapp.stop();
 
-    xapp.join();
+    let summary = xapp.join();
+    if !summary.is_clean() {
+        eprintln!("XApp not stopped cleanly: {:?}", summary);
+    }
 }
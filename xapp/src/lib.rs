@@ -22,6 +22,53 @@ pub use error::XAppError;
 
 mod xapp;
 pub use crate::xapp::XApp;
-pub use crate::xapp::{ConfigMetadata, XAppConfig};
+pub use crate::xapp::{ConfigMetadata, ConfigValidationError, XAppConfig};
 
-pub use crate::xapp::alarms::types::{Alarm, AlarmSeverity};
+pub use crate::xapp::config::store::{ConfigChangeCallback, ConfigValidator};
+pub use crate::xapp::config::PlatformEndpoints;
+
+pub use crate::xapp::discovery::XAppEventCallback;
+pub use registration_api::models::{EventType, SubscriptionNotification, Xapp, XappInstance};
+
+/// Generated client for the App Manager REST API, from the `registration-api` crate.
+///
+/// Use `PlatformEndpoints::appmgr_configuration` (or `appmgr_blocking_configuration`) for the
+/// `Configuration` of the client.
+#[cfg(feature = "appmgr-client")]
+pub use registration_api::apis as appmgr_api;
+
+pub use crate::xapp::health::HealthCheck;
+
+pub use crate::xapp::logging::{mdc_clear, mdc_put, mdc_remove};
+
+pub use crate::xapp::message_log::{MessageDirection, MessageFilter, MessageRecord};
+
+pub use crate::xapp::metrics::handles::{CounterHandle, GaugeHandle, HistogramHandle};
+pub use crate::xapp::metrics::ves::VesConfig;
+
+pub use crate::xapp::processor::{RMRHandler, RMRSender};
+
+pub use crate::xapp::shutdown::ShutdownSummary;
+
+pub use crate::xapp::symptomdata::SymptomDataHook;
+
+/// `axum` used by the Web Server of the XApp, for the routes added using `XApp::add_route`.
+pub use axum;
+
+pub use crate::xapp::alarms::definitions::AlarmDefinition;
+pub use crate::xapp::alarms::types::{
+    ActiveAlarms, Alarm, AlarmAction, AlarmMessage, AlarmSeverity, AlarmTransport,
+};
+
+pub use crate::xapp::subscription::builder::{
+    ActionBuilder, SubscriptionDetailBuilder, SubscriptionParamsBuilder, SubscriptionParamsError,
+};
+pub use crate::xapp::subscription::notification::{PendingSubscription, SubscriptionResult};
+pub use crate::xapp::subscription::registry::{ReconcileSummary, SubscriptionRecord};
+pub use crate::xapp::subscription::types::{
+    GetAllSubscriptionsError, SubscribeError, UnsubscribeError,
+};
+pub use ric_subscriptions::models::{
+    action_to_be_setup::ActionType, subsequent_action::SubsequentActionType,
+};
+pub use ric_subscriptions::models::{SubscriptionData, SubscriptionParams, SubscriptionResponse};
@@ -16,47 +16,88 @@
 
 use std::convert::TryInto;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::Sender as StdSender;
+use std::sync::mpsc::{channel, Sender as StdSender};
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
-
-use tokio::sync::mpsc::{channel as sync_channel, Sender};
+use std::time::{Duration, Instant};
 
 use rmr::{RMRClient, RMRError, RMRMessageBuffer, RMRReceiver};
 
-pub use registration_api::models::{ConfigMetadata, XAppConfig};
+use registration_api::models::RegisterRequest;
+pub use registration_api::models::{ConfigMetadata, ConfigValidationError, XAppConfig};
 use rnib::{entities::NbIdentity, RnibApi};
-use sdl::RedisStorage;
+use sdl::{RedisStorage, SdlStorageApi};
 
 use crate::XAppError;
 
 use self::alarms::client::AlarmClient;
+use self::alarms::types::AlarmTransport;
+use self::config::store::ConfigStore;
+use self::config::PlatformEndpoints;
+use self::discovery::XAppEventHandler;
+use self::health::{HealthMonitor, SdlCheck};
+use self::message_log::MessageLog;
+use self::metrics::instrumentation::{Instrumentation, InstrumentedStorage};
+use self::metrics::ves::VesConfig;
 use self::metrics::MetricsRegistry;
+use self::processor::{MessageProcessor, RMRSender};
+use self::shutdown::{ShutdownSummary, DEFAULT_SHUTDOWN_TIMEOUT};
+use self::subscription::client::SubscriptionClient;
+use self::subscription::notification::PendingSubscriptions;
+use self::subscription::registry::{SdlHandle, SubscriptionRegistry};
+use self::symptomdata::SymptomDataHooks;
+use self::webserver::AppRoutes;
 
 // XApp modules
 pub(crate) mod alarms;
+pub(crate) mod config;
+pub(crate) mod discovery;
+pub(crate) mod health;
 pub(crate) mod metrics;
 
+pub(crate) mod backoff;
+
+pub(crate) mod logging;
+
+pub(crate) mod message_log;
+
+pub(crate) mod processor;
 pub(crate) mod registration;
+pub(crate) mod shutdown;
 pub(crate) mod subscription;
+pub(crate) mod symptomdata;
+
+pub(crate) mod telemetry;
 
 pub(crate) mod webserver;
 
+#[cfg(test)]
+pub(crate) mod test_utils;
+
 pub(crate) const DEFAULT_XAPP_NS: &str = "ricxapp";
 
+pub(crate) const DEFAULT_UNSUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// The main XApp structure
 ///
 /// An application using this structure, should create an instance of this structure and use this
 /// instance during the application. This is a wrapper structure over underlying RMR, SDL and RNIB
 /// APIs of the RIC platform.
 pub struct XApp {
-    // App Configuration
-    config: XAppConfig,
+    // App Configuration, updated by the App Manager using the Web Server
+    config: Arc<ConfigStore>,
 
     // Thread for receiving RMR Messages
     receiver: Arc<Mutex<RMRReceiver>>,
     receiver_thread: Option<JoinHandle<Result<(), RMRError>>>,
 
+    // Thread for processing the received RMR Messages
+    processor: Mutex<Option<MessageProcessor>>,
+    processor_thread: Option<JoinHandle<()>>,
+
+    // Recent RMR Messages received and sent, if enabled
+    message_log: Arc<MessageLog>,
+
     // Client communicating with SDL
     sdl_client: Arc<Mutex<RedisStorage>>,
 
@@ -69,16 +110,60 @@ pub struct XApp {
     app_name: Option<String>,
     app_instance_name: Option<String>,
 
+    // Endpoints of the RIC Platform services
+    endpoints: PlatformEndpoints,
+
+    // HTTP and RMR Endpoints of the XApp, if set explicitly
+    service_endpoints: Option<(String, String)>,
+
+    // Registration managed by the framework
+    registration_request: Option<RegisterRequest>,
+    registration_thread: Option<JoinHandle<()>>,
+
+    // Subscriptions for the App Manager events about the XApps
+    appmgr_subscriptions: Mutex<Vec<String>>,
+    xapp_event_handler: XAppEventHandler,
+
     // Client for communicating with Alarm Manager
-    alarm_client: Mutex<AlarmClient>,
+    alarm_client: Arc<Mutex<AlarmClient>>,
+    alarm_sender_thread: Option<JoinHandle<()>>,
+    alarm_transport: AlarmTransport,
+
+    // Client for communicating with Subscription Manager
+    subscription_client: Arc<Mutex<SubscriptionClient>>,
+    pending_subscriptions: Arc<Mutex<PendingSubscriptions>>,
+    subscription_registry: Arc<Mutex<SubscriptionRegistry>>,
+    reconcile_thread: Option<JoinHandle<()>>,
+    unsubscribe_timeout: Duration,
 
     // Metrics support for the XApp
     metrics: Option<Arc<Mutex<MetricsRegistry>>>,
-    metrics_thread: Option<JoinHandle<Result<(), XAppError>>>,
+    instrumentation: Instrumentation,
+    ves_config: Option<VesConfig>,
+    ves_exporter_thread: Option<JoinHandle<()>>,
+
+    // Exporter of the spans, when tracing is enabled
+    #[cfg(feature = "tracing")]
+    tracer_provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+
+    // Health of the XApp, for the readiness and liveness probes
+    health: Arc<Mutex<HealthMonitor>>,
 
-    // Web Server for serving health, metrics etc.
+    // Files added by the XApp to the Symptom Data
+    symptomdata_hooks: SymptomDataHooks,
+
+    // Web Server for serving health, metrics etc. and the routes added by the XApp
+    app_routes: Option<AppRoutes>,
     webserver_thread: Option<JoinHandle<Result<(), XAppError>>>,
-    _ws_data_tx: Option<Sender<String>>,
+
+    // Shutdown of the XApp
+    stopped: bool,
+    shutdown_tx: tokio::sync::watch::Sender<bool>,
+    shutdown_timeout: Duration,
+    shutdown_deadline: Option<Instant>,
+    shutdown_summary: ShutdownSummary,
+    handle_signals: bool,
+    signal_thread: Option<JoinHandle<Result<(), String>>>,
 }
 
 impl XApp {
@@ -95,6 +180,9 @@ impl XApp {
         config: XAppConfig,
         app_tx: StdSender<RMRMessageBuffer>,
     ) -> Result<Self, XAppError> {
+        let xapp_name = config.metadata.xapp_name.clone();
+        let config = Arc::new(ConfigStore::new(config)?);
+
         let rmr_client = RMRClient::new(rmr_port, RMRClient::RMR_MAX_RCV_BYTES, rmr_flags)?;
         let receiver_client = Arc::new(Mutex::new(rmr_client));
         let rmr_client = Arc::clone(&receiver_client);
@@ -102,20 +190,48 @@ impl XApp {
         let app_is_running = Arc::new(AtomicBool::new(false));
         let receiver_running = Arc::clone(&app_is_running);
 
-        let receiver = RMRReceiver::new(receiver_client, app_tx, receiver_running);
+        // Received messages are sent to the application by the Message Processor, unless a
+        // handler is registered for the message type.
+        let (data_tx, data_rx) = channel();
+        let receiver = RMRReceiver::new(receiver_client, data_tx, receiver_running);
+        let message_log = Arc::new(MessageLog::default());
+        let processor = MessageProcessor::new(
+            data_rx,
+            app_tx,
+            RMRSender::new(
+                Arc::clone(&rmr_client),
+                Instrumentation::default(),
+                Arc::clone(&message_log),
+            ),
+            Arc::clone(&app_is_running),
+        );
 
         // Uses `DBAAS_SERVICE_HOST` and `DBAAS_SERVICE_PORT` env variables setup.
         let sdl_client = RedisStorage::new_from_env().map_err(|e| XAppError(e.to_string()))?;
 
+        let sdl_client = Arc::new(Mutex::new(sdl_client));
+
         let app_is_registered = Arc::new(AtomicBool::new(false));
 
+        // A separate SDL Client for the health checks.
+        let sdl_check = RedisStorage::new_from_env().map_err(|e| XAppError(e.to_string()))?;
+        let health = HealthMonitor::new(
+            Arc::clone(&rmr_client),
+            SdlCheck::new(sdl_check, &xapp_name),
+        );
+
         Ok(Self {
             config,
 
             receiver: Arc::new(Mutex::new(receiver)),
             receiver_thread: None,
 
-            sdl_client: Arc::new(Mutex::new(sdl_client)),
+            processor: Mutex::new(Some(processor)),
+            processor_thread: None,
+
+            message_log,
+
+            sdl_client,
 
             rmr_client,
 
@@ -124,13 +240,45 @@ impl XApp {
             app_name: None,
             app_instance_name: None,
 
-            alarm_client: Mutex::new(AlarmClient::new()),
+            endpoints: PlatformEndpoints::default(),
+
+            service_endpoints: None,
+            registration_request: None,
+            registration_thread: None,
+
+            appmgr_subscriptions: Mutex::new(vec![]),
+            xapp_event_handler: Arc::new(Mutex::new(None)),
+
+            alarm_client: Arc::new(Mutex::new(AlarmClient::new()?)),
+            alarm_sender_thread: None,
+            alarm_transport: AlarmTransport::default(),
+
+            subscription_client: Arc::new(Mutex::new(SubscriptionClient::new()?)),
+            pending_subscriptions: Arc::new(Mutex::new(PendingSubscriptions::new())),
+            subscription_registry: Arc::new(Mutex::new(SubscriptionRegistry::new())),
+            reconcile_thread: None,
+            unsubscribe_timeout: DEFAULT_UNSUBSCRIBE_TIMEOUT,
 
             metrics: None,
-            metrics_thread: None,
+            instrumentation: Instrumentation::default(),
+            ves_config: None,
+            ves_exporter_thread: None,
+            #[cfg(feature = "tracing")]
+            tracer_provider: None,
 
+            health: Arc::new(Mutex::new(health)),
+            symptomdata_hooks: Arc::new(Mutex::new(vec![])),
+
+            app_routes: Some(AppRoutes::default()),
             webserver_thread: None,
-            _ws_data_tx: None,
+
+            stopped: false,
+            shutdown_tx: tokio::sync::watch::channel(false).0,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            shutdown_deadline: None,
+            shutdown_summary: ShutdownSummary::default(),
+            handle_signals: false,
+            signal_thread: None,
         })
     }
 
@@ -147,7 +295,9 @@ impl XApp {
         let rmr_port_num = Self::port_from_config(&config, "rmrdata")?;
         let port_num_str = format!("{}", rmr_port_num);
 
-        let metrics = metrics::registry_for_ns_app(DEFAULT_XAPP_NS, &config.metadata.xapp_name)?;
+        let mut metrics =
+            metrics::registry_for_ns_app(DEFAULT_XAPP_NS, &config.metadata.xapp_name)?;
+        let instrumentation = Instrumentation::register(&mut metrics)?;
 
         #[allow(deprecated)]
         let mut xapp = Self::new(&port_num_str, RMRClient::RMRFL_NONE, config, app_tx)?;
@@ -155,6 +305,7 @@ impl XApp {
         let metrics = Arc::new(Mutex::new(metrics));
 
         let _ = xapp.metrics.replace(metrics);
+        xapp.set_instrumentation(instrumentation);
 
         Ok(xapp)
     }
@@ -172,7 +323,7 @@ impl XApp {
     /// ...
     /// let mut xapp = Xapp::new(...);
     ///
-    /// xapp.register_hanlder(10000, rmr_message_logger_handler);
+    /// xapp.register_handler(10000, rmr_message_logger_handler)?;
     ///
     /// xapp.start();
     /// ...
@@ -183,39 +334,101 @@ impl XApp {
 
         let receiver_thread = RMRReceiver::start(Arc::clone(&self.receiver));
         self.receiver_thread = Some(receiver_thread);
+        let last_active = self
+            .receiver
+            .lock()
+            .expect("RMRReceiver Lock Corrupted.")
+            .last_active();
+        self.add_heartbeat("rmr_receiver", last_active.into());
 
-        let (ws_data_tx, ws_data_rx) = sync_channel::<String>(2);
+        self.start_processor();
+
+        if let Err(e) = self.start_registration_manager() {
+            log::error!("Error starting registration manager: {}", e);
+        }
 
-        if let Some(ref metrics) = self.metrics {
-            let app_is_running = Arc::clone(&self.app_is_running);
-            let metrics = Arc::clone(metrics);
-            let metrics_thread = std::thread::spawn(move || {
-                metrics::run_metrics_server(metrics, ws_data_tx.clone(), app_is_running)
-            });
-            // TODO: Make sure it is None.
-            self.metrics_thread = Some(metrics_thread);
+        if let Err(e) = self.start_alarm_sender() {
+            log::error!("Error starting alarm sender: {}", e);
         }
 
-        let config = self.config.clone();
-        let webserver_thread =
-            std::thread::spawn(move || webserver::run_ready_live_server(config, ws_data_rx));
+        self.start_subscriptions_reconcile();
+
+        if let Err(e) = self.start_ves_exporter() {
+            log::error!("Error starting VES exporter: {}", e);
+        }
+
+        self.set_registration_readiness();
+
+        let state = self.framework_state();
+        let app_routes = self.take_app_routes();
+        let shutdown = self.shutdown_receiver();
+        let webserver_thread = std::thread::spawn(move || {
+            webserver::run_ready_live_server(state, app_routes, shutdown)
+        });
         self.webserver_thread = Some(webserver_thread);
 
+        self.start_signal_handler();
+
         log::info!("xapp started!");
     }
 
     /// Join the application threads.
-    pub fn join(&mut self) {
-        // Make sure that both the threads are stopped.
-        //
-        if self.receiver_thread.is_some() {
-            let receiver_thread = self.receiver_thread.take();
-            log::debug!("Waiting for Receiver thread to join!");
-            let _ = receiver_thread.unwrap().join();
-            log::debug!("Receiver thread joined!");
+    ///
+    /// Waits for the XApp to be stopped, using `stop` or by a signal, and then for all the threads
+    /// to stop within the shutdown timeout (See `set_shutdown_timeout`). Returns the summary of
+    /// the threads and the shutdown steps that failed.
+    pub fn join(&mut self) -> ShutdownSummary {
+        self.wait_for_stop();
+
+        // Completes the shutdown, if the XApp is stopped by a signal.
+        self.stop();
+
+        let deadline = self
+            .shutdown_deadline
+            .unwrap_or_else(|| Instant::now() + self.shutdown_timeout);
+        let mut summary = std::mem::take(&mut self.shutdown_summary);
+
+        summary.join_thread("rmr_receiver", self.receiver_thread.take(), deadline, |r| {
+            r.map_err(|e| e.to_string())
+        });
+        summary.join_thread(
+            "message_processor",
+            self.processor_thread.take(),
+            deadline,
+            Ok,
+        );
+        summary.join_thread(
+            "subscriptions_reconcile",
+            self.reconcile_thread.take(),
+            deadline,
+            Ok,
+        );
+        summary.join_thread(
+            "alarm_sender",
+            self.alarm_sender_thread.take(),
+            deadline,
+            Ok,
+        );
+        summary.join_thread(
+            "ves_exporter",
+            self.ves_exporter_thread.take(),
+            deadline,
+            Ok,
+        );
+        summary.join_thread("webserver", self.webserver_thread.take(), deadline, |r| {
+            r.map_err(|e| e.to_string())
+        });
+        summary.join_thread("signal_handler", self.signal_thread.take(), deadline, |r| r);
+
+        #[cfg(feature = "tracing")]
+        self.shutdown_tracing();
+
+        if summary.is_clean() {
+            log::info!("XApp stopped!");
+        } else {
+            log::warn!("XApp stopped: {:?}", summary);
         }
-
-        // TODO: How to stop webserver thread?
+        summary
     }
 
     /// Check if RMR is ready!
@@ -241,14 +454,63 @@ impl XApp {
     }
 
     /// Stop the XApp
+    ///
+    /// Stops all the threads of the XApp, deletes the Subscriptions and deregisters the XApp. Use
+    /// `join` to wait for the threads to stop.
     pub fn stop(&mut self) {
+        if self.stopped {
+            return;
+        }
         log::info!("Stopping XApp!");
 
+        self.stopped = true;
+        self.app_is_running.store(false, Ordering::Relaxed);
+        let _ = self.shutdown_tx.send_replace(true);
+
+        let deadline = Instant::now() + self.shutdown_timeout;
+        let _ = self.shutdown_deadline.replace(deadline);
+
+        // Make sure that Registration Manager does not register the XApp again, after we
+        // deregister it.
+        let registration_thread = self.registration_thread.take();
+        self.shutdown_summary.join_thread(
+            "registration_manager",
+            registration_thread,
+            deadline,
+            Ok,
+        );
+
+        if let Err(e) = self.unsubscribe_all_on_stop() {
+            log::error!("Error: '{}' during deleting Subscriptions.", e);
+            self.shutdown_summary
+                .failed
+                .push(("unsubscribe".to_string(), e.to_string()));
+        }
+
+        if let Err(e) = self.unsubscribe_xapp_events_on_stop() {
+            log::error!("Error: '{}' during deleting XApp Events Subscriptions.", e);
+            self.shutdown_summary
+                .failed
+                .push(("unsubscribe_xapp_events".to_string(), e.to_string()));
+        }
+
         let registered = self.app_is_registered.load(Ordering::SeqCst);
         if registered {
-            let _ = self.deregister_xapp();
+            if let Err(e) = self.deregister_xapp() {
+                log::error!("Error: '{}' during Deregistering XApp.", e);
+                self.shutdown_summary
+                    .failed
+                    .push(("deregister".to_string(), e.to_string()));
+            }
         }
-        self.app_is_running.store(false, Ordering::Relaxed);
+    }
+
+    /// Set the Endpoints of the RIC Platform services
+    ///
+    /// By default the endpoints are derived from the `PLT_NAMESPACE` environment variable. This
+    /// should be called before `start`.
+    pub fn set_platform_endpoints(&mut self, endpoints: PlatformEndpoints) {
+        self.endpoints = endpoints;
     }
 
     /// Get Nodeb IDs using RNIB API
@@ -262,6 +524,39 @@ impl XApp {
         Arc::clone(&self.rmr_client)
     }
 
+    /// Get a handle to the SDL Client
+    ///
+    /// When the metrics are enabled, the latency and the errors of the SDL operations performed
+    /// using this handle are recorded by the framework.
+    pub fn get_sdl_client(&self) -> Arc<Mutex<dyn SdlStorageApi + Send>> {
+        self.sdl_handle()
+    }
+
+    pub(crate) fn sdl_handle(&self) -> SdlHandle {
+        let sdl: SdlHandle = self.sdl_client.clone();
+        if self.instrumentation.is_enabled() {
+            Arc::new(Mutex::new(InstrumentedStorage::new(
+                sdl,
+                self.instrumentation.clone(),
+            )))
+        } else {
+            sdl
+        }
+    }
+
+    // Framework instrumentation is used by all the clients of the RIC Platform services.
+    fn set_instrumentation(&mut self, instrumentation: Instrumentation) {
+        self.subscription_client
+            .lock()
+            .expect("Corrupted SubscriptionClient Mutex")
+            .instrumentation = instrumentation.clone();
+        self.alarm_client
+            .lock()
+            .expect("Corrupted AlarmClient Mutex")
+            .instrumentation = instrumentation.clone();
+        self.instrumentation = instrumentation;
+    }
+
     pub(crate) fn port_from_config(config: &XAppConfig, service: &str) -> Result<u16, XAppError> {
         let mut port_num = -1;
         let ports = &config.config["messaging"]["ports"];
@@ -14,38 +14,121 @@
 //   limitations under the License.
 // ==================================================================================
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use reqwest::blocking::Client as ReqwestClient;
 
+use crate::xapp::metrics::instrumentation::{Instrumentation, ALARMMGR_SERVICE};
 use crate::XAppError;
 
-use super::types::{Alarm, AlarmAction, AlarmMessage};
+use super::queue::{AlarmQueue, DEFAULT_ALARM_QUEUE_CAPACITY};
+use super::types::{Alarm, AlarmAction, AlarmMessage, AlarmSeverity};
 
+const ACTIVE_ALARMS_URL: &str = "ric/v1/alarms/active";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Alarm Messages are not sent directly, they are queued and are sent by the `AlarmSender`.
 pub(crate) struct AlarmClient {
     pub(crate) http_client: ReqwestClient,
+    pub(crate) instrumentation: Instrumentation,
+
+    // Alarms raised by this client that are not yet cleared.
+    active_alarms: Vec<Alarm>,
+
+    queue: Arc<AlarmQueue>,
 }
 
 impl AlarmClient {
-    pub(crate) fn new() -> Self {
-        Self {
-            http_client: ReqwestClient::new(),
-        }
+    pub(crate) fn new() -> Result<Self, XAppError> {
+        let http_client = ReqwestClient::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|e| XAppError(format!("Error creating HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http_client,
+            instrumentation: Instrumentation::default(),
+            active_alarms: vec![],
+            queue: Arc::new(AlarmQueue::new(DEFAULT_ALARM_QUEUE_CAPACITY)),
+        })
     }
 
-    pub(crate) fn raise(&self, alarm: Alarm) -> Result<(), XAppError> {
-        self.send_alarm_message(alarm, AlarmAction::Raise)
+    pub(crate) fn queue(&self) -> Arc<AlarmQueue> {
+        Arc::clone(&self.queue)
     }
 
-    pub(crate) fn clear(&self, alarm: Alarm) -> Result<(), XAppError> {
-        self.send_alarm_message(alarm, AlarmAction::Clear)
+    // Raise the Alarm. If a matching Alarm with the same severity is already active, the Alarm is
+    // not sent again.
+    pub(crate) fn raise(&mut self, alarm: Alarm) -> Result<(), XAppError> {
+        let active = self.active_alarms.iter().position(|a| a.matches(&alarm));
+        if let Some(idx) = active {
+            if self.active_alarms[idx].perceived_severity == alarm.perceived_severity {
+                log::debug!(
+                    "Alarm {} is already active, not raising again.",
+                    alarm.specific_problem
+                );
+                return Ok(());
+            }
+        }
+
+        self.queue_alarm_message(alarm.clone(), AlarmAction::Raise);
+
+        match active {
+            Some(idx) => self.active_alarms[idx] = alarm,
+            None => self.active_alarms.push(alarm),
+        }
+        Ok(())
     }
 
-    pub(crate) fn clear_all(&self) -> Result<(), XAppError> {
+    // Clear the Alarm. Only an active Alarm can be cleared.
+    pub(crate) fn clear(&mut self, alarm: Alarm) -> Result<(), XAppError> {
+        let idx = self
+            .active_alarms
+            .iter()
+            .position(|a| a.matches(&alarm))
+            .ok_or_else(|| {
+                XAppError(format!(
+                    "Alarm {} ({}) is not active.",
+                    alarm.specific_problem, alarm.identifying_info
+                ))
+            })?;
+
+        self.queue_alarm_message(alarm, AlarmAction::Clear);
+
+        let _ = self.active_alarms.remove(idx);
         Ok(())
     }
 
-    fn send_alarm_message(&self, alarm: Alarm, action: AlarmAction) -> Result<(), XAppError> {
+    // Clear all the Alarms of the Application.
+    pub(crate) fn clear_all(
+        &mut self,
+        managed_object_id: &str,
+        application_id: &str,
+    ) -> Result<(), XAppError> {
+        let alarm = Alarm {
+            managed_object_id: managed_object_id.to_string(),
+            application_id: application_id.to_string(),
+            specific_problem: 0,
+            perceived_severity: AlarmSeverity::Def,
+            identifying_info: "".to_string(),
+            additional_info: "".to_string(),
+        };
+
+        self.queue_alarm_message(alarm, AlarmAction::ClearAll);
+
+        self.active_alarms.retain(|a| {
+            a.managed_object_id != managed_object_id || a.application_id != application_id
+        });
+        Ok(())
+    }
+
+    pub(crate) fn active_alarms(&self) -> Vec<Alarm> {
+        self.active_alarms.clone()
+    }
+
+    fn queue_alarm_message(&self, alarm: Alarm, action: AlarmAction) {
         let alarm_time = SystemTime::now();
         let alarm_time = alarm_time
             .duration_since(UNIX_EPOCH)
@@ -58,37 +141,86 @@ impl AlarmClient {
             alarm_time,
         };
 
-        let json = serde_json::to_string(&alarm_message)
-            .map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+        self.queue.push(alarm_message);
+    }
+}
 
-        self.send_alarm(json)
+// Get the list of Active Alarms from the Alarm Manager. The `http_client` and `instrumentation` are
+// of the `AlarmClient`, so that the request is made without holding the lock on the client.
+pub(crate) fn get_active_alarms(
+    http_client: &ReqwestClient,
+    instrumentation: &Instrumentation,
+    alarmmgr: &str,
+) -> Result<Vec<AlarmMessage>, XAppError> {
+    let path = format!("{}/{}", alarmmgr, ACTIVE_ALARMS_URL);
+
+    log::debug!("Getting Active Alarms from URL: {}", path);
+    let response =
+        instrumentation.send_request(ALARMMGR_SERVICE, http_client, http_client.get(path));
+    let response = response.map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(XAppError(format!(
+            "Error getting Active Alarms, Server returned: {}",
+            status
+        )));
     }
 
-    fn send_alarm(&self, json: String) -> Result<(), XAppError> {
-        let plt_ns = std::env::var("PLT_NAMESPACE").unwrap_or("ricplt".to_string());
+    response
+        .json::<Vec<AlarmMessage>>()
+        .map_err(|e| XAppError(format!("Error decoding Active Alarms: {}", e)))
+}
 
-        let alarm_mgr_url = format!(
-            "http://service-{}-alarmmanager-http.{}:8080",
-            plt_ns, plt_ns
-        );
-        let path = format!("{}/{}", alarm_mgr_url, "ric/v1/alarms");
-
-        log::debug!("Sending Alarm Json: {}, URL: {}", json, path);
-        let response = self
-            .http_client
-            .post(path)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(json)
-            .send()
-            .map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
-
-        if response.status().is_success() {
-            log::debug!("Alarm sent Succesfully!");
-        } else {
-            // We are not erroring out on Server returning an Error.
-            log::warn!(" Server returned! {}", response.status());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(id: i32, severity: AlarmSeverity) -> Alarm {
+        Alarm {
+            managed_object_id: "RIC".to_string(),
+            application_id: "test-xapp".to_string(),
+            specific_problem: id,
+            perceived_severity: severity,
+            identifying_info: "identifying".to_string(),
+            additional_info: "additional".to_string(),
         }
+    }
 
-        Ok(())
+    #[test]
+    fn test_duplicate_raise_is_suppressed() {
+        let mut client = AlarmClient::new().unwrap();
+
+        let result = client.raise(alarm(8004, AlarmSeverity::Major));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let result = client.raise(alarm(8004, AlarmSeverity::Major));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert_eq!(client.active_alarms().len(), 1);
+        assert_eq!(client.queue().len(), 1);
+
+        // Change in the severity is raised again.
+        let result = client.raise(alarm(8004, AlarmSeverity::Minor));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert_eq!(client.queue().len(), 2);
+        assert_eq!(
+            client.active_alarms()[0].perceived_severity,
+            AlarmSeverity::Minor
+        );
+    }
+
+    #[test]
+    fn test_clear_inactive_alarm() {
+        let mut client = AlarmClient::new().unwrap();
+        let _ = client.raise(alarm(8004, AlarmSeverity::Major));
+
+        let result = client.clear(alarm(8005, AlarmSeverity::Major));
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("not active"));
+        assert_eq!(client.active_alarms().len(), 1);
+
+        let result = client.clear(alarm(8004, AlarmSeverity::Major));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert!(client.active_alarms().is_empty());
+        assert_eq!(client.queue().len(), 2);
     }
 }
@@ -0,0 +1,301 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Alarm Definitions declared in the XApp config
+//!
+//! Alarms raised by an XApp can be declared in the `alarms` section of the XApp config.
+//!
+//! ```ignore
+//!     "alarms": [
+//!         {
+//!             "id": 8004,
+//!             "text": "E2 Node not responding",
+//!             "default_severity": "MAJOR",
+//!             "probable_cause": "E2 Node is down or not reachable",
+//!             "managed_object": "RIC",
+//!             "identifying_info": "E2 Node: {meid}"
+//!         }
+//!     ]
+//! ```
+//!
+//! `identifying_info` is a template, where `{name}` is replaced by the value of the parameter
+//! `name` when the Alarm is raised or cleared.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{XAppConfig, XAppError};
+
+use super::types::AlarmSeverity;
+use super::MANAGED_OBJECT_ID;
+
+// Key in the XApp config for the Alarm Definitions.
+pub(crate) const ALARMS_CONFIG_KEY: &str = "alarms";
+
+/// Definition of an Alarm raised by the XApp
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AlarmDefinition {
+    /// Alarm ID (Specific Problem)
+    pub id: i32,
+
+    /// Text describing the Alarm
+    pub text: String,
+
+    /// Severity used when the Alarm is raised without a Severity
+    #[serde(default = "default_severity")]
+    pub default_severity: AlarmSeverity,
+
+    /// Probable Cause of the Alarm
+    #[serde(default)]
+    pub probable_cause: String,
+
+    /// Managed Object ID of the Alarm (`RIC` if not specified)
+    #[serde(default = "default_managed_object")]
+    pub managed_object: String,
+
+    /// Template for the Identifying Info of the Alarm
+    #[serde(default)]
+    pub identifying_info: String,
+}
+
+fn default_severity() -> AlarmSeverity {
+    AlarmSeverity::Major
+}
+
+fn default_managed_object() -> String {
+    MANAGED_OBJECT_ID.to_string()
+}
+
+impl AlarmDefinition {
+    /// Render the Identifying Info of the Alarm from the template using the given parameters
+    ///
+    /// Returns an error if any of the parameters used by the template is not given.
+    pub fn render_identifying_info(&self, params: &[(&str, &str)]) -> Result<String, XAppError> {
+        let segments = parse_template(&self.identifying_info)
+            .map_err(|e| XAppError(format!("Alarm {}: {}", self.id, e)))?;
+
+        let mut rendered = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Param(name) => {
+                    let value = params
+                        .iter()
+                        .find(|(param, _)| *param == name)
+                        .map(|(_, value)| *value)
+                        .ok_or_else(|| {
+                            XAppError(format!(
+                                "Alarm {}: Parameter '{}' for Identifying Info not given.",
+                                self.id, name
+                            ))
+                        })?;
+                    rendered.push_str(value);
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    /// Additional Info of the Alarm derived from the Text and the Probable Cause
+    pub fn additional_info(&self) -> String {
+        if self.probable_cause.is_empty() {
+            self.text.clone()
+        } else {
+            format!("{} (Probable Cause: {})", self.text, self.probable_cause)
+        }
+    }
+
+    fn validate(&self) -> Result<(), XAppError> {
+        if self.id <= 0 {
+            return Err(XAppError(format!("Alarm {}: Invalid Alarm ID.", self.id)));
+        }
+        if self.text.is_empty() {
+            return Err(XAppError(format!("Alarm {}: Text is empty.", self.id)));
+        }
+        if self.managed_object.is_empty() {
+            return Err(XAppError(format!(
+                "Alarm {}: Managed Object is empty.",
+                self.id
+            )));
+        }
+        if self.default_severity == AlarmSeverity::Cleared {
+            return Err(XAppError(format!(
+                "Alarm {}: Default Severity cannot be 'CLEARED'.",
+                self.id
+            )));
+        }
+        parse_template(&self.identifying_info)
+            .map(|_| ())
+            .map_err(|e| XAppError(format!("Alarm {}: {}", self.id, e)))
+    }
+}
+
+// Alarm Definitions from the XApp config.
+#[derive(Debug, Default)]
+pub(crate) struct AlarmCatalog {
+    definitions: BTreeMap<i32, AlarmDefinition>,
+}
+
+impl AlarmCatalog {
+    // Load and validate the Alarm Definitions from the XApp config. It's not an error if there are
+    // no Alarm Definitions.
+    pub(crate) fn from_config(config: &XAppConfig) -> Result<Self, XAppError> {
+        let alarms = &config.config[ALARMS_CONFIG_KEY];
+        if alarms.is_null() {
+            return Ok(Self::default());
+        }
+
+        let alarms = Vec::<AlarmDefinition>::deserialize(alarms)
+            .map_err(|e| XAppError(format!("Invalid Alarm Definitions in the config: {}", e)))?;
+
+        let mut definitions = BTreeMap::new();
+        for alarm in alarms {
+            alarm.validate()?;
+            let id = alarm.id;
+            if definitions.insert(id, alarm).is_some() {
+                return Err(XAppError(format!("Alarm {}: Duplicate Alarm ID.", id)));
+            }
+        }
+
+        Ok(Self { definitions })
+    }
+
+    pub(crate) fn get(&self, id: i32) -> Option<&AlarmDefinition> {
+        self.definitions.get(&id)
+    }
+
+    pub(crate) fn definitions(&self) -> impl Iterator<Item = &AlarmDefinition> {
+        self.definitions.values()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Param(&'a str),
+}
+
+// Parse the template into text and `{param}` segments.
+fn parse_template(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = vec![];
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(format!("Unmatched '}}' in template '{}'.", template));
+        }
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("Unmatched '{{' in template '{}'.", template))?;
+        let name = rest[start + 1..start + end].trim();
+        if name.is_empty() || name.contains('{') {
+            return Err(format!("Invalid parameter in template '{}'.", template));
+        }
+        segments.push(Segment::Param(name));
+        rest = &rest[start + end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xapp::alarms::types::AlarmSeverity;
+
+    use super::AlarmCatalog;
+
+    fn config(alarms: &str) -> crate::XAppConfig {
+        crate::XAppConfig {
+            metadata: Box::new(crate::ConfigMetadata {
+                xapp_name: "tests".to_string(),
+                config_type: "json".to_string(),
+            }),
+            config: serde_json::from_str(&format!(r#"{{ "alarms": {} }}"#, alarms)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_catalog_from_config() {
+        let result = AlarmCatalog::from_config(&config(
+            r#"[
+                {
+                    "id": 8004,
+                    "text": "E2 Node not responding",
+                    "probable_cause": "E2 Node is down",
+                    "identifying_info": "E2 Node: {meid}, Cell: {cell}"
+                },
+                {
+                    "id": 8005,
+                    "text": "SDL not available",
+                    "default_severity": "MINOR",
+                    "managed_object": "SDL"
+                }
+            ]"#,
+        ));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let catalog = result.unwrap();
+
+        let alarm = catalog.get(8004).unwrap();
+        assert_eq!(alarm.default_severity, AlarmSeverity::Major);
+        assert_eq!(alarm.managed_object, "RIC");
+        assert_eq!(
+            alarm.additional_info(),
+            "E2 Node not responding (Probable Cause: E2 Node is down)"
+        );
+        let info = alarm.render_identifying_info(&[("cell", "1"), ("meid", "gnb_1")]);
+        assert_eq!(info.unwrap(), "E2 Node: gnb_1, Cell: 1");
+        assert!(alarm.render_identifying_info(&[("meid", "gnb_1")]).is_err());
+
+        let alarm = catalog.get(8005).unwrap();
+        assert_eq!(alarm.default_severity, AlarmSeverity::Minor);
+        assert_eq!(alarm.render_identifying_info(&[]).unwrap(), "");
+    }
+
+    #[test]
+    fn test_catalog_validation() {
+        let invalid = [
+            r#"[{ "id": 8004, "text": "One" }, { "id": 8004, "text": "Two" }]"#,
+            r#"[{ "id": 0, "text": "Zero" }]"#,
+            r#"[{ "id": 8004, "text": "" }]"#,
+            r#"[{ "id": 8004, "text": "Cleared", "default_severity": "CLEARED" }]"#,
+            r#"[{ "id": 8004, "text": "Template", "identifying_info": "E2 Node: {meid" }]"#,
+            r#"[{ "id": 8004, "text": "Template", "identifying_info": "E2 Node: {}" }]"#,
+            r#"[{ "id": 8004, "text": "Template", "identifying_info": "E2 Node: meid}" }]"#,
+        ];
+
+        for alarms in invalid {
+            let result = AlarmCatalog::from_config(&config(alarms));
+            assert!(result.is_err(), "{}", alarms);
+        }
+
+        let result = AlarmCatalog::from_config(&crate::xapp::tests::get_config_data(4560));
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_empty());
+    }
+}
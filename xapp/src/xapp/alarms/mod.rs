@@ -18,18 +18,50 @@
 //!
 //! This module defines client side API for interacting with Alarm Manager of the NearRT RIC
 //! Platform.
+//!
+//! Alarms are not sent to the Alarm Manager directly, instead they are queued and are sent by a
+//! background thread (started by `XApp::start`). Alarms that cannot be sent are retried with a
+//! backoff. Optionally, the queue can be persisted in SDL (`enable_persistent_alarms`).
+//!
+//! Alarms are sent using HTTP by default. An XApp can choose to send them as RMR messages instead
+//! (`set_alarm_transport`).
+//!
+//! Alarms can be declared in the XApp config (see `definitions`) and raised using their ID with
+//! `raise_defined_alarm`. The Alarm Definitions are updated along with the XApp config.
 
+pub mod definitions;
 pub mod types;
 
 pub(crate) mod client;
+pub(crate) mod queue;
+pub(crate) mod sender;
+pub(crate) mod transport;
+
+// Alarm queue of an XApp is persisted in the SDL namespace `<xapp_name>-alarms`.
+const ALARMS_NS_SUFFIX: &str = "alarms";
+
+const MANAGED_OBJECT_ID: &str = "RIC";
+
+const DEFAULT_APPLICATION_ID: &str = "xapp-frame-rust";
 
 mod alarm_xapp {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+
     use crate::{XApp, XAppError};
 
-    use super::types;
+    use super::client;
+    use super::definitions::AlarmDefinition;
+    use super::sender::AlarmSender;
+    use super::transport::{HttpTransport, RmrTransport, Transport};
+    use super::types::{self, ActiveAlarms, AlarmTransport};
+    use super::{ALARMS_NS_SUFFIX, DEFAULT_APPLICATION_ID, MANAGED_OBJECT_ID};
 
     impl XApp {
         /// Raise the Alarm with given Alarm ID, Cause, Severity and Additional Info
+        ///
+        /// If the same Alarm (with the same severity) is already active, it is not raised again.
+        /// The Alarm is queued for sending to the Alarm Manager, hence this does not block.
         pub fn raise_alarm(
             &self,
             id: i32,
@@ -37,10 +69,20 @@ mod alarm_xapp {
             identifying_info: String,
             additional_info: String,
         ) -> Result<(), crate::XAppError> {
-            self.alarm_raise_or_clear(id, severity, identifying_info, additional_info, true)
+            let managed_object = self.alarm_managed_object(id);
+            self.alarm_raise_or_clear(
+                &managed_object,
+                id,
+                severity,
+                identifying_info,
+                additional_info,
+                true,
+            )
         }
 
         /// Clear the Alarm with given Alarm ID, Cause, Severity and Additional Info
+        ///
+        /// Returns an error if the Alarm was not raised by this XApp or is already cleared.
         pub fn clear_alarm(
             &self,
             id: i32,
@@ -48,21 +90,208 @@ mod alarm_xapp {
             identifying_info: String,
             additional_info: String,
         ) -> Result<(), crate::XAppError> {
-            self.alarm_raise_or_clear(id, severity, identifying_info, additional_info, false)
+            let managed_object = self.alarm_managed_object(id);
+            self.alarm_raise_or_clear(
+                &managed_object,
+                id,
+                severity,
+                identifying_info,
+                additional_info,
+                false,
+            )
+        }
+
+        /// Raise the Alarm declared in the XApp config with the given Alarm ID
+        ///
+        /// The Default Severity of the Alarm is used, if `severity` is not given. The Identifying
+        /// Info is rendered from the template in the Alarm Definition using `params`.
+        ///
+        /// ```ignore
+        ///     xapp.raise_defined_alarm(8004, None, &[("meid", "gnb_734_733_b5c67788")])?;
+        /// ```
+        pub fn raise_defined_alarm(
+            &self,
+            id: i32,
+            severity: Option<crate::AlarmSeverity>,
+            params: &[(&str, &str)],
+        ) -> Result<(), crate::XAppError> {
+            let definition = self.defined_alarm(id)?;
+            self.alarm_raise_or_clear(
+                &definition.managed_object,
+                id,
+                severity.unwrap_or(definition.default_severity),
+                definition.render_identifying_info(params)?,
+                definition.additional_info(),
+                true,
+            )
+        }
+
+        /// Clear the Alarm declared in the XApp config with the given Alarm ID
+        ///
+        /// `params` should be the same as the ones used for raising the Alarm.
+        pub fn clear_defined_alarm(
+            &self,
+            id: i32,
+            params: &[(&str, &str)],
+        ) -> Result<(), crate::XAppError> {
+            let definition = self.defined_alarm(id)?;
+            self.alarm_raise_or_clear(
+                &definition.managed_object,
+                id,
+                definition.default_severity,
+                definition.render_identifying_info(params)?,
+                definition.additional_info(),
+                false,
+            )
+        }
+
+        /// Get the Definition of the Alarm with the given Alarm ID from the current XApp config
+        pub fn alarm_definition(&self, id: i32) -> Option<AlarmDefinition> {
+            self.config.alarm_catalog().get(id).cloned()
+        }
+
+        /// Get all the Alarm Definitions from the current XApp config
+        pub fn alarm_definitions(&self) -> Vec<AlarmDefinition> {
+            self.config.alarm_catalog().definitions().cloned().collect()
         }
 
         /// Clear All Alarms for the App
         pub fn clear_all_alarms(&self) -> Result<(), crate::XAppError> {
-            let client = self
+            let mut client = self
                 .alarm_client
                 .lock()
                 .expect("Corrupted AlarmClient Mutex");
 
-            (*client).clear_all()
+            let alarm_catalog = self.config.alarm_catalog();
+            let mut managed_objects = alarm_catalog
+                .definitions()
+                .map(|d| d.managed_object.as_str())
+                .collect::<BTreeSet<_>>();
+            let _ = managed_objects.insert(MANAGED_OBJECT_ID);
+
+            let application_id = self.alarm_application_id();
+            for managed_object in managed_objects {
+                (*client).clear_all(managed_object, &application_id)?;
+            }
+            Ok(())
+        }
+
+        /// Persist the queue of Alarms not yet sent in SDL
+        ///
+        /// The queue is persisted in the SDL namespace `<xapp_name>-alarms`. Alarms queued during
+        /// an earlier run of the XApp are sent after they are loaded from SDL.
+        pub fn enable_persistent_alarms(&mut self) -> Result<(), XAppError> {
+            let namespace = format!(
+                "{}-{}",
+                self.config.current().metadata.xapp_name,
+                ALARMS_NS_SUFFIX
+            );
+            let sdl = self.sdl_handle();
+
+            let queue = self
+                .alarm_client
+                .lock()
+                .expect("Corrupted AlarmClient Mutex")
+                .queue();
+
+            queue.enable_persistence(&namespace, sdl)
+        }
+
+        /// Set the Transport used for sending the Alarms to the Alarm Manager
+        ///
+        /// Alarms are sent using HTTP by default. This should be called before `start`.
+        pub fn set_alarm_transport(&mut self, transport: AlarmTransport) {
+            self.alarm_transport = transport;
+        }
+
+        // Starts the Alarm Sender thread for sending the queued Alarms.
+        pub(crate) fn start_alarm_sender(&mut self) -> Result<(), XAppError> {
+            let queue = self
+                .alarm_client
+                .lock()
+                .expect("Corrupted AlarmClient Mutex")
+                .queue();
+
+            if let Some(ref metrics) = self.metrics {
+                let mut metrics = metrics.lock().unwrap();
+                queue.register_metrics(&mut metrics)?;
+            }
+
+            let transport: Box<dyn Transport> = match self.alarm_transport {
+                AlarmTransport::Http => Box::new(HttpTransport::new(
+                    self.endpoints.alarmmgr.clone(),
+                    self.instrumentation.clone(),
+                )?),
+                AlarmTransport::Rmr => Box::new(RmrTransport::new(self.get_rmr_sender())),
+            };
+
+            let mut sender = AlarmSender::new(queue, transport, Arc::clone(&self.app_is_running));
+            sender.heartbeat = self.heartbeat("alarm_sender");
+
+            let alarm_sender_thread = std::thread::spawn(move || sender.run());
+            let _ = self.alarm_sender_thread.replace(alarm_sender_thread);
+
+            Ok(())
+        }
+
+        /// List the Active Alarms
+        ///
+        /// Returns the Alarms raised by this XApp that are not yet cleared, along with the Active
+        /// Alarms reported by the Alarm Manager.
+        pub fn list_active_alarms(&self) -> Result<ActiveAlarms, crate::XAppError> {
+            // Alarms can be raised and cleared while the Alarm Manager is queried.
+            let (http_client, instrumentation, raised) = {
+                let client = self
+                    .alarm_client
+                    .lock()
+                    .expect("Corrupted AlarmClient Mutex");
+                (
+                    client.http_client.clone(),
+                    client.instrumentation.clone(),
+                    client.active_alarms(),
+                )
+            };
+
+            let alarm_manager = client::get_active_alarms(
+                &http_client,
+                &instrumentation,
+                &self.endpoints.alarmmgr,
+            )?;
+
+            Ok(ActiveAlarms {
+                raised,
+                alarm_manager,
+            })
+        }
+
+        fn alarm_application_id(&self) -> String {
+            self.app_name
+                .clone()
+                .unwrap_or_else(|| DEFAULT_APPLICATION_ID.to_string())
+        }
+
+        fn defined_alarm(&self, id: i32) -> Result<AlarmDefinition, XAppError> {
+            self.alarm_definition(id)
+                .ok_or_else(|| XAppError(format!("Alarm {} is not defined in the config.", id)))
+        }
+
+        // Managed Object of the Alarm from the Alarm Definition (if declared).
+        fn alarm_managed_object(&self, id: i32) -> String {
+            let alarm_catalog = self.config.alarm_catalog();
+            match alarm_catalog.get(id) {
+                Some(definition) => definition.managed_object.clone(),
+                None => {
+                    if !alarm_catalog.is_empty() {
+                        log::warn!("Alarm {} is not defined in the config.", id);
+                    }
+                    MANAGED_OBJECT_ID.to_string()
+                }
+            }
         }
 
         fn alarm_raise_or_clear(
             &self,
+            managed_object: &str,
             id: i32,
             severity: crate::AlarmSeverity,
             identifying_info: String,
@@ -70,18 +299,15 @@ mod alarm_xapp {
             raise: bool,
         ) -> Result<(), XAppError> {
             let alarm = types::Alarm {
-                managed_object_id: "RIC".to_string(),
-                application_id: self
-                    .app_name
-                    .clone()
-                    .unwrap_or("xapp-frame-rust".to_string()),
+                managed_object_id: managed_object.to_string(),
+                application_id: self.alarm_application_id(),
                 specific_problem: id,
                 perceived_severity: severity,
                 identifying_info,
                 additional_info,
             };
 
-            let client = self
+            let mut client = self
                 .alarm_client
                 .lock()
                 .expect("Corrupted AlarmClient Mutex");
@@ -0,0 +1,325 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Bounded queue of Alarm Messages to be delivered to the Alarm Manager
+//!
+//! The messages are delivered in order by the `AlarmSender`. When the queue is full, the oldest
+//! message is dropped. Optionally the queue is persisted in SDL, so that the messages that are
+//! not yet delivered survive a restart of the XApp. The queue is persisted by the `AlarmSender`
+//! thread, so that queueing an Alarm does not block on the SDL.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::Mutex;
+
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::gauge::Gauge;
+use serde::{Deserialize, Serialize};
+
+use sdl::DataMap;
+
+use crate::xapp::metrics::MetricsRegistry;
+use crate::xapp::subscription::registry::SdlHandle;
+use crate::XAppError;
+
+use super::types::AlarmMessage;
+
+pub(crate) const DEFAULT_ALARM_QUEUE_CAPACITY: usize = 1000;
+
+// SDL Key used for the persisted queue.
+const ALARM_QUEUE_KEY: &str = "alarm_queue";
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub(crate) struct QueuedAlarm {
+    pub(crate) seq: u64,
+    pub(crate) message: AlarmMessage,
+}
+
+// Metrics for the delivery of the Alarms.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AlarmQueueMetrics {
+    pub(crate) sent: Counter,
+    pub(crate) retried: Counter,
+    pub(crate) dropped: Counter,
+    pub(crate) queued: Gauge,
+}
+
+#[derive(Clone)]
+struct AlarmStore {
+    namespace: String,
+    sdl: SdlHandle,
+}
+
+impl AlarmStore {
+    fn load(&self) -> Result<Vec<AlarmMessage>, XAppError> {
+        let mut sdl = self.sdl.lock().expect("Corrupted SDL Mutex");
+
+        let values = sdl
+            .get(
+                &self.namespace,
+                &BTreeSet::from([ALARM_QUEUE_KEY.to_string()]),
+            )
+            .map_err(|e| XAppError(e.to_string()))?;
+
+        match values.get(ALARM_QUEUE_KEY) {
+            Some(value) => serde_json::from_slice(value)
+                .map_err(|e| XAppError(format!("Invalid Alarm Queue in SDL: {}", e))),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn save(&self, messages: &[AlarmMessage]) -> Result<(), XAppError> {
+        let json = serde_json::to_vec(&messages).map_err(|e| XAppError(e.to_string()))?;
+        let mut data = DataMap::new();
+        let _ = data.insert(ALARM_QUEUE_KEY.to_string(), json);
+
+        let mut sdl = self.sdl.lock().expect("Corrupted SDL Mutex");
+        sdl.set(&self.namespace, &data)
+            .map_err(|e| XAppError(e.to_string()))
+    }
+}
+
+struct QueueInner {
+    messages: VecDeque<QueuedAlarm>,
+    next_seq: u64,
+    store: Option<AlarmStore>,
+    // The queue has changed since it was last persisted.
+    dirty: bool,
+}
+
+pub(crate) struct AlarmQueue {
+    inner: Mutex<QueueInner>,
+    capacity: usize,
+    metrics: AlarmQueueMetrics,
+}
+
+impl AlarmQueue {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(QueueInner {
+                messages: VecDeque::new(),
+                next_seq: 0,
+                store: None,
+                dirty: false,
+            }),
+            capacity,
+            metrics: AlarmQueueMetrics::default(),
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> &AlarmQueueMetrics {
+        &self.metrics
+    }
+
+    pub(crate) fn register_metrics(&self, registry: &mut MetricsRegistry) -> Result<(), XAppError> {
+        registry.register_metric(
+            "alarms_sent",
+            "Number of Alarm Messages sent to the Alarm Manager",
+            self.metrics.sent.clone(),
+        )?;
+        registry.register_metric(
+            "alarms_retried",
+            "Number of retries for sending Alarm Messages to the Alarm Manager",
+            self.metrics.retried.clone(),
+        )?;
+        registry.register_metric(
+            "alarms_dropped",
+            "Number of Alarm Messages dropped without being delivered",
+            self.metrics.dropped.clone(),
+        )?;
+        registry.register_metric(
+            "alarms_queued",
+            "Number of Alarm Messages waiting to be delivered",
+            self.metrics.queued.clone(),
+        )
+    }
+
+    // Persist the queue in the given SDL namespace. Any messages already persisted are put in the
+    // front of the queue.
+    pub(crate) fn enable_persistence(
+        &self,
+        namespace: &str,
+        sdl: SdlHandle,
+    ) -> Result<(), XAppError> {
+        let store = AlarmStore {
+            namespace: namespace.to_string(),
+            sdl,
+        };
+        let persisted = store.load()?;
+
+        let mut inner = self.inner.lock().expect("Corrupted AlarmQueue Mutex");
+        let current = std::mem::take(&mut inner.messages);
+        for message in persisted
+            .into_iter()
+            .chain(current.into_iter().map(|q| q.message))
+        {
+            Self::push_locked(&mut inner, message, self.capacity, &self.metrics);
+        }
+        let _ = inner.store.replace(store);
+        inner.dirty = true;
+        self.metrics.queued.set(inner.messages.len() as i64);
+        drop(inner);
+
+        self.persist();
+
+        Ok(())
+    }
+
+    pub(crate) fn push(&self, message: AlarmMessage) {
+        let mut inner = self.inner.lock().expect("Corrupted AlarmQueue Mutex");
+        Self::push_locked(&mut inner, message, self.capacity, &self.metrics);
+        inner.dirty = true;
+        self.metrics.queued.set(inner.messages.len() as i64);
+    }
+
+    // Persist the queue in SDL, if it has changed since it was last persisted. The queue is not
+    // locked while it is being written to SDL.
+    pub(crate) fn persist(&self) {
+        let (store, messages) = {
+            let mut inner = self.inner.lock().expect("Corrupted AlarmQueue Mutex");
+            let store = match inner.store {
+                Some(ref store) if inner.dirty => store.clone(),
+                _ => return,
+            };
+            inner.dirty = false;
+            let messages = inner
+                .messages
+                .iter()
+                .map(|q| q.message.clone())
+                .collect::<Vec<_>>();
+            (store, messages)
+        };
+
+        if let Err(e) = store.save(&messages) {
+            log::warn!("Error persisting Alarm Queue: {}", e);
+            self.inner.lock().expect("Corrupted AlarmQueue Mutex").dirty = true;
+        }
+    }
+
+    pub(crate) fn front(&self) -> Option<QueuedAlarm> {
+        let inner = self.inner.lock().expect("Corrupted AlarmQueue Mutex");
+        inner.messages.front().cloned()
+    }
+
+    // Remove the message from the front of the queue, if it was not dropped in the meanwhile.
+    pub(crate) fn pop(&self, seq: u64) {
+        let mut inner = self.inner.lock().expect("Corrupted AlarmQueue Mutex");
+        if inner.messages.front().map(|q| q.seq) == Some(seq) {
+            let _ = inner.messages.pop_front();
+            inner.dirty = true;
+        }
+        self.metrics.queued.set(inner.messages.len() as i64);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        let inner = self.inner.lock().expect("Corrupted AlarmQueue Mutex");
+        inner.messages.len()
+    }
+
+    fn push_locked(
+        inner: &mut QueueInner,
+        message: AlarmMessage,
+        capacity: usize,
+        metrics: &AlarmQueueMetrics,
+    ) {
+        while inner.messages.len() >= capacity {
+            if let Some(dropped) = inner.messages.pop_front() {
+                log::warn!(
+                    "Alarm Queue full, dropping Alarm {} ({:?}).",
+                    dropped.message.alarm.specific_problem,
+                    dropped.message.action
+                );
+                metrics.dropped.inc();
+            }
+        }
+
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        inner.messages.push_back(QueuedAlarm { seq, message });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::xapp::alarms::types::{Alarm, AlarmAction, AlarmMessage, AlarmSeverity};
+    use crate::xapp::subscription::registry::SdlHandle;
+    use crate::xapp::test_utils::MemoryStorage;
+
+    use super::AlarmQueue;
+
+    fn message(id: i32) -> AlarmMessage {
+        AlarmMessage {
+            alarm: Alarm {
+                managed_object_id: "RIC".to_string(),
+                application_id: "test-xapp".to_string(),
+                specific_problem: id,
+                perceived_severity: AlarmSeverity::Major,
+                identifying_info: "".to_string(),
+                additional_info: "".to_string(),
+            },
+            action: AlarmAction::Raise,
+            alarm_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_queue_drops_oldest_when_full() {
+        let queue = AlarmQueue::new(2);
+        queue.push(message(1));
+        queue.push(message(2));
+        queue.push(message(3));
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.metrics().dropped.get(), 1);
+
+        let front = queue.front().unwrap();
+        assert_eq!(front.message, message(2));
+
+        queue.pop(front.seq);
+        // Already popped, should not pop the next one.
+        queue.pop(front.seq);
+        assert_eq!(queue.front().unwrap().message, message(3));
+        assert_eq!(queue.metrics().queued.get(), 1);
+    }
+
+    #[test]
+    fn test_queue_persistence() {
+        let sdl: SdlHandle = Arc::new(Mutex::new(MemoryStorage::default()));
+
+        let queue = AlarmQueue::new(10);
+        let result = queue.enable_persistence("tests-alarms", Arc::clone(&sdl));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        queue.push(message(1));
+        queue.push(message(2));
+
+        // Not persisted until `persist` is called (by the sender thread).
+        let unpersisted = AlarmQueue::new(10);
+        let result = unpersisted.enable_persistence("tests-alarms", Arc::clone(&sdl));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert_eq!(unpersisted.len(), 0);
+
+        queue.persist();
+
+        let queue = AlarmQueue::new(10);
+        queue.push(message(3));
+        let result = queue.enable_persistence("tests-alarms", sdl);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.front().unwrap().message, message(1));
+    }
+}
@@ -0,0 +1,254 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Background sender of the Alarm Messages
+//!
+//! Alarm Messages from the `AlarmQueue` are sent to the Alarm Manager (using the configured
+//! `Transport`) in the order they are queued. When the Alarm Manager is not available, the message
+//! is retried with a backoff, before sending any further messages, thus preserving the order of
+//! the messages for every Alarm. When the XApp is stopped, the queued messages are sent once
+//! more, before the sender thread stops. The sender also persists the queue, if enabled.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::xapp::backoff::Backoff;
+
+use super::queue::AlarmQueue;
+use super::transport::{SendError, Transport};
+use crate::xapp::health::Heartbeat;
+
+const RETRY_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(crate) struct AlarmSender {
+    queue: Arc<AlarmQueue>,
+    transport: Box<dyn Transport>,
+    app_is_running: Arc<AtomicBool>,
+    pub(crate) heartbeat: Heartbeat,
+}
+
+impl AlarmSender {
+    pub(crate) fn new(
+        queue: Arc<AlarmQueue>,
+        transport: Box<dyn Transport>,
+        app_is_running: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            queue,
+            transport,
+            app_is_running,
+            heartbeat: Heartbeat::default(),
+        }
+    }
+
+    pub(crate) fn run(self) {
+        log::info!("Starting alarm sender thread!");
+
+        let mut backoff = Backoff::new(RETRY_INITIAL_DELAY, RETRY_MAX_DELAY);
+        let mut next_attempt = Instant::now();
+
+        while self.app_is_running.load(Ordering::Relaxed) {
+            self.heartbeat.beat();
+            self.queue.persist();
+            if Instant::now() < next_attempt {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+
+            let queued = match self.queue.front() {
+                Some(queued) => queued,
+                None => {
+                    std::thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+            let metrics = self.queue.metrics();
+            match self.transport.send(&queued.message) {
+                Ok(()) => {
+                    self.queue.pop(queued.seq);
+                    metrics.sent.inc();
+                    backoff.reset();
+                }
+                Err(SendError::Rejected(e)) => {
+                    log::error!(
+                        "Alarm {} rejected by Alarm Manager: {}, dropping it.",
+                        queued.message.alarm.specific_problem,
+                        e
+                    );
+                    self.queue.pop(queued.seq);
+                    metrics.dropped.inc();
+                }
+                Err(SendError::Failed(e)) => {
+                    let delay = backoff.next_delay();
+                    log::warn!(
+                        "Sending Alarm {} failed: {}, retrying after {:?}.",
+                        queued.message.alarm.specific_problem,
+                        e,
+                        delay
+                    );
+                    metrics.retried.inc();
+                    next_attempt = Instant::now() + delay;
+                }
+            }
+        }
+
+        self.flush();
+        self.queue.persist();
+
+        log::info!(
+            "Alarm sender thread stopped, {} Alarm(s) not sent.",
+            self.queue.len()
+        );
+    }
+
+    // Sends the queued messages without retries. Stops at the first failure, since the remaining
+    // messages can not be sent in order.
+    fn flush(&self) {
+        let deadline = Instant::now() + FLUSH_TIMEOUT;
+        while Instant::now() < deadline {
+            let queued = match self.queue.front() {
+                Some(queued) => queued,
+                None => break,
+            };
+
+            let metrics = self.queue.metrics();
+            match self.transport.send(&queued.message) {
+                Ok(()) => {
+                    self.queue.pop(queued.seq);
+                    metrics.sent.inc();
+                }
+                Err(SendError::Rejected(_)) => {
+                    self.queue.pop(queued.seq);
+                    metrics.dropped.inc();
+                }
+                Err(SendError::Failed(e)) => {
+                    log::warn!("Sending queued Alarms failed: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use crate::xapp::alarms::queue::AlarmQueue;
+    use crate::xapp::alarms::transport::HttpTransport;
+    use crate::xapp::alarms::types::{Alarm, AlarmAction, AlarmMessage, AlarmSeverity};
+    use crate::xapp::metrics::instrumentation::Instrumentation;
+    use crate::xapp::test_utils::http_stub;
+
+    use super::AlarmSender;
+
+    fn message(id: i32, action: AlarmAction) -> AlarmMessage {
+        AlarmMessage {
+            alarm: Alarm {
+                managed_object_id: "RIC".to_string(),
+                application_id: "test-xapp".to_string(),
+                specific_problem: id,
+                perceived_severity: AlarmSeverity::Major,
+                identifying_info: "".to_string(),
+                additional_info: "".to_string(),
+            },
+            action,
+            alarm_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_sender_retries_in_order() {
+        // Alarm Manager is unavailable for the first request, rejects the third one.
+        let alarmmgr = http_stub(vec![503, 200, 400, 200]);
+
+        let queue = Arc::new(AlarmQueue::new(10));
+        queue.push(message(1, AlarmAction::Raise));
+        queue.push(message(1, AlarmAction::Clear));
+        queue.push(message(2, AlarmAction::Raise));
+
+        let running = Arc::new(AtomicBool::new(true));
+        let transport = HttpTransport::new(alarmmgr.url().to_string(), Instrumentation::default());
+        assert!(transport.is_ok(), "{}", transport.err().unwrap());
+        let sender = AlarmSender::new(
+            Arc::clone(&queue),
+            Box::new(transport.unwrap()),
+            Arc::clone(&running),
+        );
+        let sender_thread = std::thread::spawn(move || sender.run());
+
+        let start = Instant::now();
+        while queue.len() > 0 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        running.store(false, Ordering::Relaxed);
+        let _ = sender_thread.join();
+
+        assert_eq!(queue.len(), 0);
+        let metrics = queue.metrics();
+        assert_eq!(metrics.retried.get(), 1);
+        assert_eq!(metrics.sent.get(), 2);
+        assert_eq!(metrics.dropped.get(), 1);
+
+        let messages = alarmmgr
+            .requests()
+            .iter()
+            .map(|r| r.json::<AlarmMessage>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                message(1, AlarmAction::Raise),
+                message(1, AlarmAction::Raise),
+                message(1, AlarmAction::Clear),
+                message(2, AlarmAction::Raise),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sender_flushes_on_stop() {
+        // Alarm Manager is unavailable after the first request.
+        let alarmmgr = http_stub(vec![200, 503]);
+
+        let queue = Arc::new(AlarmQueue::new(10));
+        queue.push(message(1, AlarmAction::Raise));
+        queue.push(message(2, AlarmAction::Raise));
+        queue.push(message(3, AlarmAction::Raise));
+
+        // XApp is already stopped, the queued messages are sent once till the first failure.
+        let running = Arc::new(AtomicBool::new(false));
+        let transport = HttpTransport::new(alarmmgr.url().to_string(), Instrumentation::default());
+        assert!(transport.is_ok(), "{}", transport.err().unwrap());
+        let sender = AlarmSender::new(
+            Arc::clone(&queue),
+            Box::new(transport.unwrap()),
+            Arc::clone(&running),
+        );
+        sender.run();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.metrics().sent.get(), 1);
+        assert_eq!(alarmmgr.requests().len(), 2);
+    }
+}
@@ -0,0 +1,158 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Transports for sending the Alarm Messages to the Alarm Manager
+//!
+//! The same `AlarmMessage` JSON is sent either as an HTTP POST to the Alarm Manager or as an RMR
+//! message of type `RIC_ALARM`.
+
+use std::time::Duration;
+
+use reqwest::blocking::Client as ReqwestClient;
+
+use crate::xapp::metrics::instrumentation::{Instrumentation, ALARMMGR_SERVICE};
+use crate::xapp::processor::RMRSender;
+use crate::XAppError;
+
+use super::types::AlarmMessage;
+
+const ALARMS_URL: &str = "ric/v1/alarms";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// RMR Message Type for the Alarms (`RIC_ALARM` from `RIC_message_types.h`).
+const RIC_ALARM: i32 = 110;
+
+// Error in sending an Alarm Message (also used for sending the VES Events).
+#[derive(Debug)]
+pub(crate) enum SendError {
+    // Receiver (eg. Alarm Manager) rejected the message, retrying won't help.
+    Rejected(String),
+
+    // Receiver not reachable or failed, the message should be retried.
+    Failed(String),
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected(e) => write!(f, "Rejected: {}", e),
+            Self::Failed(e) => write!(f, "Failed: {}", e),
+        }
+    }
+}
+
+// A Transport for sending the Alarm Messages.
+pub(crate) trait Transport: Send {
+    fn send(&self, message: &AlarmMessage) -> Result<(), SendError>;
+}
+
+// Alarm Messages sent as HTTP POST to the Alarm Manager.
+pub(crate) struct HttpTransport {
+    alarmmgr: String,
+    http_client: ReqwestClient,
+    instrumentation: Instrumentation,
+}
+
+impl HttpTransport {
+    pub(crate) fn new(
+        alarmmgr: String,
+        instrumentation: Instrumentation,
+    ) -> Result<Self, XAppError> {
+        let http_client = ReqwestClient::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|e| XAppError(format!("Error creating HTTP client: {}", e)))?;
+
+        Ok(Self {
+            alarmmgr,
+            http_client,
+            instrumentation,
+        })
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, message: &AlarmMessage) -> Result<(), SendError> {
+        let json = alarm_payload(message)?;
+        let path = format!("{}/{}", self.alarmmgr, ALARMS_URL);
+
+        log::debug!("Sending Alarm Json: {}, URL: {}", json, path);
+        let request = self
+            .http_client
+            .post(path)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json);
+        let response =
+            self.instrumentation
+                .send_request(ALARMMGR_SERVICE, &self.http_client, request);
+        let response =
+            response.map_err(|e| SendError::Failed(format!("Error sending request: {}", e)))?;
+
+        let status = response.status();
+        if status.is_success() {
+            log::debug!("Alarm sent Succesfully!");
+            Ok(())
+        } else if status.is_client_error() {
+            Err(SendError::Rejected(format!("Server returned: {}", status)))
+        } else {
+            Err(SendError::Failed(format!("Server returned: {}", status)))
+        }
+    }
+}
+
+// Alarm Messages sent as `RIC_ALARM` RMR Messages, routed to the Alarm Manager by the RMR Routing
+// Table.
+pub(crate) struct RmrTransport {
+    sender: RMRSender,
+}
+
+impl RmrTransport {
+    pub(crate) fn new(sender: RMRSender) -> Self {
+        Self { sender }
+    }
+}
+
+impl Transport for RmrTransport {
+    fn send(&self, message: &AlarmMessage) -> Result<(), SendError> {
+        let json = alarm_payload(message)?;
+
+        if !self.sender.is_ready() {
+            return Err(SendError::Failed("RMR is not ready.".to_string()));
+        }
+
+        let mut msg = self.sender.alloc_msg();
+        if json.len() > msg.get_payload_size() as usize {
+            msg.free();
+            return Err(SendError::Rejected(format!(
+                "Alarm Message too large ({} bytes) for the RMR Message.",
+                json.len()
+            )));
+        }
+        msg.set_mtype(RIC_ALARM);
+        msg.set_payload(json.as_bytes());
+
+        log::debug!("Sending Alarm Json: {} over RMR.", json);
+        let result = self.sender.send_msg(&mut msg);
+        msg.free();
+
+        result.map_err(|e| SendError::Failed(format!("Error sending RMR message: {}", e)))
+    }
+}
+
+fn alarm_payload(message: &AlarmMessage) -> Result<String, SendError> {
+    serde_json::to_string(message).map_err(|e| SendError::Rejected(format!("serde_json: {}", e)))
+}
@@ -16,7 +16,7 @@
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Alarm {
     #[serde(rename = "managedObjectId")]
     pub(crate) managed_object_id: String,
@@ -37,7 +37,48 @@ pub struct Alarm {
     pub(crate) additional_info: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Alarm {
+    /// Managed Object ID of the Alarm
+    pub fn managed_object_id(&self) -> &str {
+        &self.managed_object_id
+    }
+
+    /// Application ID of the Alarm
+    pub fn application_id(&self) -> &str {
+        &self.application_id
+    }
+
+    /// Alarm ID (Specific Problem)
+    pub fn specific_problem(&self) -> i32 {
+        self.specific_problem
+    }
+
+    /// Perceived Severity of the Alarm
+    pub fn perceived_severity(&self) -> AlarmSeverity {
+        self.perceived_severity
+    }
+
+    /// Identifying Info of the Alarm
+    pub fn identifying_info(&self) -> &str {
+        &self.identifying_info
+    }
+
+    /// Additional Info of the Alarm
+    pub fn additional_info(&self) -> &str {
+        &self.additional_info
+    }
+
+    // Alarms are matched on the Managed Object, Application, Specific Problem and Identifying
+    // Info. Severity and Additional Info can be different.
+    pub(crate) fn matches(&self, other: &Alarm) -> bool {
+        self.managed_object_id == other.managed_object_id
+            && self.application_id == other.application_id
+            && self.specific_problem == other.specific_problem
+            && self.identifying_info == other.identifying_info
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum AlarmSeverity {
     #[serde(rename = "UNSPECIFIED")]
     Unspecified,
@@ -58,7 +99,7 @@ pub enum AlarmSeverity {
     Def,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum AlarmAction {
     #[serde(rename = "RAISE")]
     Raise,
@@ -70,7 +111,7 @@ pub enum AlarmAction {
     ClearAll,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct AlarmMessage {
     #[serde(flatten)]
     pub(crate) alarm: Alarm,
@@ -81,3 +122,41 @@ pub struct AlarmMessage {
     #[serde(rename = "AlarmTime")]
     pub(crate) alarm_time: u64,
 }
+
+impl AlarmMessage {
+    /// The Alarm
+    pub fn alarm(&self) -> &Alarm {
+        &self.alarm
+    }
+
+    /// Action for the Alarm
+    pub fn action(&self) -> AlarmAction {
+        self.action
+    }
+
+    /// Time (seconds since the Unix Epoch) of the Alarm
+    pub fn alarm_time(&self) -> u64 {
+        self.alarm_time
+    }
+}
+
+/// Transport used for sending the Alarms to the Alarm Manager
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AlarmTransport {
+    /// HTTP POST to the Alarm Manager REST API
+    #[default]
+    Http,
+
+    /// RMR Message of type `RIC_ALARM` using the XApp's RMR Client
+    Rmr,
+}
+
+/// Active Alarms returned by `XApp::list_active_alarms`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveAlarms {
+    /// Alarms raised by this XApp that are not yet cleared
+    pub raised: Vec<Alarm>,
+
+    /// Active Alarms reported by the Alarm Manager
+    pub alarm_manager: Vec<AlarmMessage>,
+}
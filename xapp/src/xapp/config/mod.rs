@@ -13,3 +13,142 @@
 //   See the License for the specific language governing permissions and
 //   limitations under the License.
 // ==================================================================================
+
+//! Configuration used by the XApp Framework
+//!
+//! This module defines the configuration of the NearRT RIC Platform services (App Manager etc.)
+//! that the framework talks to on behalf of the XApp.
+
+pub(crate) mod store;
+
+const DEFAULT_PLT_NS: &str = "ricplt";
+
+/// Endpoints of the RIC Platform services used by the XApp framework.
+///
+/// By default, the endpoints are derived from the `PLT_NAMESPACE` environment variable (`ricplt`
+/// if not set). An XApp can override these using `XApp::set_platform_endpoints` before calling
+/// `start`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlatformEndpoints {
+    /// Base URL of the App Manager (eg. `http://service-ricplt-appmgr-http.ricplt:8080`).
+    pub appmgr: String,
+
+    /// Base URL of the Subscription Manager (eg. `http://service-ricplt-submgr-http.ricplt:8088`).
+    pub submgr: String,
+
+    /// Base URL of the Alarm Manager (eg. `http://service-ricplt-alarmmanager-http.ricplt:8080`).
+    pub alarmmgr: String,
+}
+
+impl PlatformEndpoints {
+    /// Get the Platform Endpoints from the current environment.
+    pub fn from_env() -> Self {
+        let plt_ns = std::env::var("PLT_NAMESPACE").unwrap_or_else(|_| DEFAULT_PLT_NS.to_string());
+        Self::for_namespace(&plt_ns)
+    }
+
+    /// Get the Platform Endpoints for the Platform deployed in the given namespace.
+    pub fn for_namespace(plt_ns: &str) -> Self {
+        Self {
+            appmgr: format!("http://service-{}-appmgr-http.{}:8080", plt_ns, plt_ns),
+            submgr: format!("http://service-{}-submgr-http.{}:8088", plt_ns, plt_ns),
+            alarmmgr: format!(
+                "http://service-{}-alarmmanager-http.{}:8080",
+                plt_ns, plt_ns
+            ),
+        }
+    }
+}
+
+// Base path of the App Manager REST API.
+#[cfg(feature = "appmgr-client")]
+const APPMGR_API_PATH: &str = "ric/v1";
+
+#[cfg(feature = "appmgr-client")]
+impl PlatformEndpoints {
+    /// Configuration of the generated App Manager client (`appmgr_api`), for the App Manager of
+    /// the Platform.
+    pub fn appmgr_configuration(&self) -> crate::appmgr_api::configuration::Configuration {
+        crate::appmgr_api::configuration::Configuration {
+            base_path: format!("{}/{}", self.appmgr, APPMGR_API_PATH),
+            ..Default::default()
+        }
+    }
+
+    /// Configuration of the blocking variant of the generated App Manager client
+    /// (`appmgr_api::blocking`), for the App Manager of the Platform.
+    pub fn appmgr_blocking_configuration(
+        &self,
+    ) -> crate::appmgr_api::blocking::configuration::Configuration {
+        crate::appmgr_api::blocking::configuration::Configuration {
+            base_path: format!("{}/{}", self.appmgr, APPMGR_API_PATH),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for PlatformEndpoints {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn test_platform_endpoints_for_namespace() {
+        let endpoints = super::PlatformEndpoints::for_namespace("ricplt");
+        assert_eq!(
+            endpoints.appmgr,
+            "http://service-ricplt-appmgr-http.ricplt:8080"
+        );
+        assert_eq!(
+            endpoints.submgr,
+            "http://service-ricplt-submgr-http.ricplt:8088"
+        );
+        assert_eq!(
+            endpoints.alarmmgr,
+            "http://service-ricplt-alarmmanager-http.ricplt:8080"
+        );
+    }
+
+    #[cfg(feature = "appmgr-client")]
+    #[test]
+    fn test_appmgr_configuration() {
+        let endpoints = super::PlatformEndpoints::for_namespace("ricplt");
+        assert_eq!(
+            endpoints.appmgr_configuration().base_path,
+            "http://service-ricplt-appmgr-http.ricplt:8080/ric/v1"
+        );
+        assert_eq!(
+            endpoints.appmgr_blocking_configuration().base_path,
+            "http://service-ricplt-appmgr-http.ricplt:8080/ric/v1"
+        );
+    }
+
+    #[cfg(feature = "appmgr-client")]
+    #[test]
+    fn test_appmgr_blocking_client() {
+        use crate::appmgr_api::blocking::{health_api, xapp_api};
+
+        let platform = xapp_testkit::FakePlatform::start().unwrap();
+        platform
+            .appmgr
+            .add_xapp(registration_api::models::Xapp::new("peer".to_string()));
+        let endpoints: super::PlatformEndpoints = platform.endpoints().into();
+        let configuration = endpoints.appmgr_blocking_configuration();
+
+        let result = health_api::get_health_alive(&configuration);
+        assert!(result.is_ok(), "{:?}", result.err().unwrap());
+
+        let xapps = xapp_api::get_all_xapps(&configuration);
+        assert!(xapps.is_ok(), "{:?}", xapps.err().unwrap());
+        let xapps = xapps.unwrap();
+        assert_eq!(xapps.len(), 1);
+        assert_eq!(xapps[0].name, "peer");
+
+        let result = xapp_api::get_xapp_by_name(&configuration, "unknown");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,311 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Current Configuration of the XApp
+//!
+//! The App Manager updates the configuration of a running XApp using `POST /ric/v1/config` (or
+//! `PUT`), with a list containing the configuration of the XApp, as returned by
+//! `GET /ric/v1/config`. The new configuration is validated by the framework and the validators
+//! added by the XApp using `add_config_validator`. If valid, it replaces the current
+//! configuration and the callback set using `on_config_change` is called. Otherwise, the
+//! `ConfigValidationError`s are returned to the App Manager.
+//!
+//! The framework rejects a configuration that changes the name of the XApp or the ports, since
+//! those can not be changed without a restart, or that has invalid Alarm Definitions. The log level
+//! and the Alarm Definitions from the new configuration (if present) are applied.
+//!
+//! ```ignore
+//!     xapp.add_config_validator(|config| {
+//!         if config.config["threshold"].is_u64() {
+//!             vec![]
+//!         } else {
+//!             vec![ConfigValidationError::new(
+//!                 "threshold".to_string(),
+//!                 "Should be a positive integer".to_string(),
+//!             )]
+//!         }
+//!     });
+//!     xapp.on_config_change(move |config| {
+//!         let _ = config_tx.send(config.clone());
+//!     });
+//! ```
+
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, RwLock};
+
+use registration_api::models::{ConfigValidationError, XAppConfig};
+
+use crate::xapp::alarms::definitions::{AlarmCatalog, ALARMS_CONFIG_KEY};
+use crate::xapp::logging;
+use crate::{XApp, XAppError};
+
+/// Validator of the Configuration of the XApp
+///
+/// Returns the errors in the configuration, empty if the configuration is valid.
+pub type ConfigValidator = Box<dyn Fn(&XAppConfig) -> Vec<ConfigValidationError> + Send + Sync>;
+
+/// Callback called with the new Configuration of the XApp, after it is updated
+pub type ConfigChangeCallback = Box<dyn Fn(&XAppConfig) + Send + Sync>;
+
+// Ports that can not be changed without a restart.
+const FIXED_PORTS: [&str; 2] = ["http", "rmrdata"];
+
+// Config Store: Holds the current configuration and the Alarm Definitions from it, shared with
+// the Web Server.
+pub(crate) struct ConfigStore {
+    current: RwLock<Arc<XAppConfig>>,
+    alarm_catalog: RwLock<Arc<AlarmCatalog>>,
+    validators: Mutex<Vec<ConfigValidator>>,
+    on_change: Mutex<Option<ConfigChangeCallback>>,
+}
+
+impl ConfigStore {
+    pub(crate) fn new(config: XAppConfig) -> Result<Self, XAppError> {
+        let alarm_catalog = AlarmCatalog::from_config(&config)?;
+
+        Ok(Self {
+            current: RwLock::new(Arc::new(config)),
+            alarm_catalog: RwLock::new(Arc::new(alarm_catalog)),
+            validators: Mutex::new(vec![]),
+            on_change: Mutex::new(None),
+        })
+    }
+
+    pub(crate) fn current(&self) -> Arc<XAppConfig> {
+        Arc::clone(&self.current.read().expect("Corrupted Config Lock"))
+    }
+
+    pub(crate) fn alarm_catalog(&self) -> Arc<AlarmCatalog> {
+        Arc::clone(
+            &self
+                .alarm_catalog
+                .read()
+                .expect("Corrupted Alarm Catalog Lock"),
+        )
+    }
+
+    // Validates and updates the config. Updates are serialized by the `on_change` lock, so that
+    // the callbacks are called in the order of the updates.
+    pub(crate) fn update(&self, config: XAppConfig) -> Result<(), Vec<ConfigValidationError>> {
+        let on_change = self
+            .on_change
+            .lock()
+            .expect("Corrupted Config Callback Mutex");
+
+        let current = self.current();
+        let (alarm_catalog, mut errors) = validate(&current, &config);
+        for validator in self
+            .validators
+            .lock()
+            .expect("Corrupted Config Validators Mutex")
+            .iter()
+        {
+            errors.extend(validator(&config));
+        }
+        let alarm_catalog = match alarm_catalog {
+            Some(alarm_catalog) if errors.is_empty() => alarm_catalog,
+            _ => return Err(errors),
+        };
+
+        if let Ok(Some(level)) = logging::level_from_config(&config) {
+            logging::set_level(level);
+        }
+
+        let config = Arc::new(config);
+        *self.current.write().expect("Corrupted Config Lock") = Arc::clone(&config);
+        *self
+            .alarm_catalog
+            .write()
+            .expect("Corrupted Alarm Catalog Lock") = Arc::new(alarm_catalog);
+        log::info!("XApp Config updated!");
+
+        if let Some(ref callback) = *on_change {
+            callback(&config);
+        }
+
+        Ok(())
+    }
+}
+
+// Validation of the config by the framework. Returns the Alarm Definitions from the config, if
+// they are valid, along with the errors.
+fn validate(
+    current: &XAppConfig,
+    config: &XAppConfig,
+) -> (Option<AlarmCatalog>, Vec<ConfigValidationError>) {
+    let mut errors = vec![];
+
+    if config.metadata.xapp_name != current.metadata.xapp_name {
+        errors.push(ConfigValidationError::new(
+            "metadata.xappName".to_string(),
+            format!(
+                "XApp name can not be changed from '{}'",
+                current.metadata.xapp_name
+            ),
+        ));
+    }
+
+    for name in FIXED_PORTS {
+        let port = port(config, name);
+        if port.is_none() || port != self::port(current, name) {
+            errors.push(ConfigValidationError::new(
+                "messaging.ports".to_string(),
+                format!("Port '{}' missing or changed", name),
+            ));
+        }
+    }
+
+    if let Err(e) = logging::level_from_config(config) {
+        errors.push(ConfigValidationError::new(
+            "controls.logger.level".to_string(),
+            e.to_string(),
+        ));
+    }
+
+    let alarm_catalog = match AlarmCatalog::from_config(config) {
+        Ok(alarm_catalog) => Some(alarm_catalog),
+        Err(e) => {
+            errors.push(ConfigValidationError::new(
+                ALARMS_CONFIG_KEY.to_string(),
+                e.to_string(),
+            ));
+            None
+        }
+    };
+
+    (alarm_catalog, errors)
+}
+
+// Port number of the given name, if it is valid.
+fn port(config: &XAppConfig, name: &str) -> Option<u16> {
+    config.config["messaging"]["ports"]
+        .as_array()?
+        .iter()
+        .find(|port| port["name"].as_str() == Some(name))
+        .and_then(|port| port["port"].as_u64())
+        .and_then(|port| u16::try_from(port).ok())
+}
+
+impl XApp {
+    /// Get the current Configuration of the XApp
+    pub fn config(&self) -> Arc<XAppConfig> {
+        self.config.current()
+    }
+
+    /// Add a validator for the Configuration updates
+    ///
+    /// The configuration is updated only if all the validators return no errors.
+    pub fn add_config_validator<F>(&mut self, validator: F)
+    where
+        F: Fn(&XAppConfig) -> Vec<ConfigValidationError> + Send + Sync + 'static,
+    {
+        self.config
+            .validators
+            .lock()
+            .expect("Corrupted Config Validators Mutex")
+            .push(Box::new(validator));
+    }
+
+    /// Set the callback for the Configuration updates
+    ///
+    /// The callback is called with the new configuration, after it is validated and updated. It
+    /// is called from the Web Server and should not block for long.
+    pub fn on_config_change<F>(&mut self, callback: F)
+    where
+        F: Fn(&XAppConfig) + Send + Sync + 'static,
+    {
+        let _ = self
+            .config
+            .on_change
+            .lock()
+            .expect("Corrupted Config Callback Mutex")
+            .replace(Box::new(callback));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use registration_api::models::ConfigValidationError;
+
+    use super::ConfigStore;
+    use crate::xapp::tests::get_config_data;
+
+    #[test]
+    fn test_config_update_validation() {
+        let store = ConfigStore::new(get_config_data(4560)).unwrap();
+        store.validators.lock().unwrap().push(Box::new(|config| {
+            if config.config["threshold"].is_u64() {
+                vec![]
+            } else {
+                vec![ConfigValidationError::new(
+                    "threshold".to_string(),
+                    "Should be a positive integer".to_string(),
+                )]
+            }
+        }));
+
+        let mut renamed = get_config_data(4560);
+        renamed.config["threshold"] = 10.into();
+        renamed.metadata.xapp_name = "renamed".to_string();
+        let mut port_changed = get_config_data(4561);
+        port_changed.config["threshold"] = 10.into();
+        let mut bad_level = get_config_data(4560);
+        bad_level.config["threshold"] = 10.into();
+        bad_level.config["controls"] = serde_json::json!({"logger": {"level": "LOUD"}});
+        let no_threshold = get_config_data(4560);
+
+        for (config, field) in [
+            (renamed, "metadata.xappName"),
+            (port_changed, "messaging.ports"),
+            (bad_level, "controls.logger.level"),
+            (no_threshold, "threshold"),
+        ] {
+            let result = store.update(config);
+            assert!(result.is_err(), "{}", field);
+            let errors = result.err().unwrap();
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert_eq!(errors[0].field, field);
+        }
+        assert!(store.current().config["threshold"].is_null());
+    }
+
+    #[test]
+    fn test_config_update_callback() {
+        let store = ConfigStore::new(get_config_data(4560)).unwrap();
+        let updated = Arc::new(Mutex::new(vec![]));
+        let callback_updated = Arc::clone(&updated);
+        let _ = store
+            .on_change
+            .lock()
+            .unwrap()
+            .replace(Box::new(move |config| {
+                callback_updated
+                    .lock()
+                    .unwrap()
+                    .push(config.config["threshold"].clone());
+            }));
+
+        let mut config = get_config_data(4560);
+        config.config["threshold"] = 10.into();
+        let result = store.update(config);
+        assert!(result.is_ok(), "{:?}", result.err().unwrap());
+
+        assert_eq!(store.current().config["threshold"], 10);
+        assert_eq!(*updated.lock().unwrap(), vec![serde_json::json!(10)]);
+    }
+}
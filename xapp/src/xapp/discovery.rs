@@ -0,0 +1,320 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Discovery of the XApps deployed in the RIC
+//!
+//! This module implements APIs for getting the XApps (and their instances) deployed in the RIC
+//! from the App Manager and for subscribing to the App Manager events about the XApps. The APIs
+//! use the models from the `registration-api` crate.
+//!
+//! The App Manager notifies the events to the `/ric/v1/appmgr/events` end point of the XApp, which
+//! are delivered to the callback set using `on_xapp_event`. The subscriptions are deleted when
+//! the XApp stops.
+//!
+//! ```ignore
+//!     xapp.on_xapp_event(move |event| {
+//!         for peer in event.x_apps.iter().flatten() {
+//!             log::info!("XApp {} {:?}", peer.name, event.event_type);
+//!         }
+//!     });
+//!     xapp.enable_registration("hw-rust-1", None)?;
+//!     xapp.start();
+//!
+//!     let subscription_id = xapp.subscribe_xapp_events(EventType::All)?;
+//!     let peers = xapp.list_xapps()?;
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use registration_api::models::{
+    EventType, SubscriptionData, SubscriptionNotification, SubscriptionRequest,
+    SubscriptionResponse, Xapp, XappInstance,
+};
+use serde::de::DeserializeOwned;
+
+use super::metrics::instrumentation::APPMGR_SERVICE;
+use super::registration::http_client;
+use super::{XApp, XAppError};
+
+pub(crate) const XAPPS_URL: &str = "ric/v1/xapps";
+const SUBSCRIPTIONS_URL: &str = "ric/v1/subscriptions";
+
+/// Path of the end point of the XApp, where the App Manager notifies the events.
+pub(crate) const XAPP_EVENTS_PATH: &str = "/ric/v1/appmgr/events";
+
+// The App Manager retries the notifications, that are not delivered.
+const EVENTS_MAX_RETRIES: i32 = 5;
+const EVENTS_RETRY_TIMER_SECS: i32 = 10;
+
+/// Callback for the App Manager events about the XApps deployed in the RIC
+pub type XAppEventCallback = Arc<dyn Fn(&SubscriptionNotification) + Send + Sync>;
+
+pub(crate) type XAppEventHandler = Arc<Mutex<Option<XAppEventCallback>>>;
+
+impl XApp {
+    /// Get the XApps deployed in the RIC, along with their instances
+    ///
+    /// The instances include the endpoints (`ip`, `port`) and the RMR messages sent and received
+    /// by the instance.
+    pub fn list_xapps(&self) -> Result<Vec<Xapp>, XAppError> {
+        self.appmgr_get(XAPPS_URL)
+    }
+
+    /// Get the deployed XApp with the given name
+    pub fn get_xapp(&self, xapp_name: &str) -> Result<Xapp, XAppError> {
+        self.appmgr_get(&format!("{}/{}", XAPPS_URL, xapp_name))
+    }
+
+    /// Get the instance of the deployed XApp
+    pub fn get_xapp_instance(
+        &self,
+        xapp_name: &str,
+        xapp_instance_name: &str,
+    ) -> Result<XappInstance, XAppError> {
+        self.appmgr_get(&format!(
+            "{}/{}/instances/{}",
+            XAPPS_URL, xapp_name, xapp_instance_name
+        ))
+    }
+
+    /// Set the callback for the App Manager events about the XApps
+    ///
+    /// The callback is called from the Web Server for every event notified by the App Manager
+    /// for the subscriptions created using `subscribe_xapp_events`.
+    pub fn on_xapp_event<F>(&mut self, callback: F)
+    where
+        F: Fn(&SubscriptionNotification) + Send + Sync + 'static,
+    {
+        let _ = self
+            .xapp_event_handler
+            .lock()
+            .expect("Corrupted XAppEventHandler Mutex")
+            .replace(Arc::new(callback));
+    }
+
+    /// Subscribe to the App Manager events about the XApps
+    ///
+    /// `event_type` is one of `Created`, `Deleted` or `All`. The events are notified to the HTTP
+    /// endpoint of the XApp used for the registration (see `set_service_endpoints`). Returns the ID
+    /// of the subscription from the App Manager.
+    pub fn subscribe_xapp_events(&self, event_type: EventType) -> Result<String, XAppError> {
+        if !matches!(
+            event_type,
+            EventType::Created | EventType::Deleted | EventType::All
+        ) {
+            return Err(XAppError(format!(
+                "Unsupported XApp Event Type: '{}'.",
+                event_type.to_string()
+            )));
+        }
+
+        let (http_endpoint, _) = self.client_endpoints()?;
+        let target_url = format!("http://{}{}", http_endpoint, XAPP_EVENTS_PATH);
+
+        let request = SubscriptionRequest::new(SubscriptionData::new(
+            target_url,
+            event_type,
+            EVENTS_MAX_RETRIES,
+            EVENTS_RETRY_TIMER_SECS,
+        ));
+        let json =
+            serde_json::to_string(&request).map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+
+        let path = format!("{}/{}", self.endpoints.appmgr, SUBSCRIPTIONS_URL);
+        log::debug!("Sending XApp Events Subscription: '{}' to '{}'", json, path);
+
+        let req_client = http_client()?;
+        let request = req_client
+            .post(path)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json);
+        let response = self
+            .instrumentation
+            .send_request(APPMGR_SERVICE, &req_client, request)
+            .map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(XAppError(format!("Error : {}", response.status())));
+        }
+
+        let response: SubscriptionResponse = response
+            .json()
+            .map_err(|e| XAppError(format!("Invalid Subscription Response: {}", e)))?;
+        let id = response
+            .id
+            .ok_or_else(|| XAppError("Subscription ID missing in the Response.".to_string()))?;
+
+        log::info!("Subscribed to XApp Events: {}", id);
+        self.appmgr_subscriptions
+            .lock()
+            .expect("Corrupted AppMgr Subscriptions Mutex")
+            .push(id.clone());
+
+        Ok(id)
+    }
+
+    /// Delete the subscription for the App Manager events about the XApps
+    pub fn unsubscribe_xapp_events(&self, subscription_id: &str) -> Result<(), XAppError> {
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoints.appmgr, SUBSCRIPTIONS_URL, subscription_id
+        );
+
+        let req_client = http_client()?;
+        let response = self
+            .instrumentation
+            .send_request(APPMGR_SERVICE, &req_client, req_client.delete(path))
+            .map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(XAppError(format!("Error : {}", response.status())));
+        }
+
+        self.appmgr_subscriptions
+            .lock()
+            .expect("Corrupted AppMgr Subscriptions Mutex")
+            .retain(|id| id != subscription_id);
+
+        Ok(())
+    }
+
+    // Deletes all the subscriptions for the App Manager events, when the XApp is stopped.
+    pub(crate) fn unsubscribe_xapp_events_on_stop(&self) -> Result<(), XAppError> {
+        let ids = self
+            .appmgr_subscriptions
+            .lock()
+            .expect("Corrupted AppMgr Subscriptions Mutex")
+            .clone();
+
+        let mut errors = vec![];
+        for id in ids {
+            if let Err(e) = self.unsubscribe_xapp_events(&id) {
+                errors.push(format!("{}: {}", id, e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(XAppError(format!(
+                "Error deleting XApp Events Subscriptions: {}",
+                errors.join(", ")
+            )))
+        }
+    }
+
+    fn appmgr_get<T: DeserializeOwned>(&self, url: &str) -> Result<T, XAppError> {
+        let path = format!("{}/{}", self.endpoints.appmgr, url);
+
+        let req_client = http_client()?;
+        let response = self
+            .instrumentation
+            .send_request(APPMGR_SERVICE, &req_client, req_client.get(path))
+            .map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(XAppError(format!("Error : {}", response.status())));
+        }
+
+        response
+            .json()
+            .map_err(|e| XAppError(format!("Invalid Response from the App Manager: {}", e)))
+    }
+}
+
+// Delivers the event notified by the App Manager to the callback of the XApp. The callback is
+// called without holding the lock, so that it may set the callback again.
+pub(crate) fn deliver_xapp_event(handler: &XAppEventHandler, event: &SubscriptionNotification) {
+    log::debug!("Received XApp Event: {:?}", event);
+
+    let callback = handler
+        .lock()
+        .expect("Corrupted XAppEventHandler Mutex")
+        .clone();
+    match callback {
+        Some(callback) => callback(event),
+        None => log::debug!("No callback for the XApp Events, ignoring."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use registration_api::models::{EventType, SubscriptionNotification, Xapp, XappInstance};
+    use xapp_testkit::{FakePlatform, FakeServer};
+
+    use crate::xapp::test_utils::test_xapp;
+    use crate::xapp::webserver::framework_routes;
+
+    #[test]
+    fn test_xapp_discovery_and_events() {
+        let platform = FakePlatform::start().unwrap();
+        let appmgr = &platform.appmgr;
+        let mut instance = XappInstance::new("peer-1".to_string());
+        instance.ip = Some("service-ricxapp-peer-rmr.ricxapp".to_string());
+        instance.port = Some(4560);
+        let mut peer = Xapp::new("peer".to_string());
+        peer.instances = Some(vec![instance]);
+        appmgr.add_xapp(peer);
+
+        let mut xapp = test_xapp();
+        xapp.set_platform_endpoints(platform.endpoints().into());
+
+        let xapps = xapp.list_xapps();
+        assert!(xapps.is_ok(), "{}", xapps.err().unwrap());
+        let xapps = xapps.unwrap();
+        assert_eq!(xapps.len(), 1);
+        let instances = xapps[0].instances.as_ref().unwrap();
+        assert_eq!(instances[0].port, Some(4560));
+        assert!(xapp.get_xapp("peer").is_ok());
+        assert!(xapp.get_xapp("unknown").is_err());
+        assert!(xapp.subscribe_xapp_events(EventType::Deployed).is_err());
+
+        // The events are notified to the Web Server of the XApp.
+        let webserver = FakeServer::start(framework_routes(xapp.framework_state())).unwrap();
+        let http_endpoint = webserver.url().trim_start_matches("http://").to_string();
+        xapp.set_service_endpoints(&http_endpoint, "127.0.0.1:4560");
+
+        let id = xapp.subscribe_xapp_events(EventType::Created);
+        assert!(id.is_ok(), "{}", id.err().unwrap());
+        let subscriptions = appmgr.subscriptions();
+        assert_eq!(subscriptions.len(), 1);
+        assert_eq!(subscriptions[0].id, Some(id.unwrap()));
+        let data = subscriptions[0].data.as_ref().unwrap();
+        assert_eq!(data.event_type, EventType::Created);
+        assert_eq!(
+            data.target_url,
+            format!("{}{}", webserver.url(), super::XAPP_EVENTS_PATH)
+        );
+
+        // The callback removes itself, which should not deadlock.
+        let received = Arc::new(Mutex::new(vec![]));
+        let callback_received = Arc::clone(&received);
+        let handler = Arc::clone(&xapp.xapp_event_handler);
+        xapp.on_xapp_event(move |event| {
+            callback_received.lock().unwrap().push(event.event_type);
+            let _ = handler.lock().unwrap().take();
+        });
+        let mut event = SubscriptionNotification::new();
+        event.event_type = Some(EventType::Created);
+        assert_eq!(appmgr.notify_xapp_event(event.clone()), 1);
+        assert_eq!(appmgr.notify_xapp_event(event), 1);
+        assert_eq!(*received.lock().unwrap(), vec![Some(EventType::Created)]);
+
+        let result = xapp.unsubscribe_xapp_events_on_stop();
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert!(xapp.appmgr_subscriptions.lock().unwrap().is_empty());
+        assert!(appmgr.subscriptions().is_empty());
+    }
+}
@@ -0,0 +1,445 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Readiness and Liveness of the XApp
+//!
+//! The XApp is ready, when -
+//!
+//! - the RMR Client is ready,
+//! - the SDL is reachable (Unless disabled using `set_sdl_readiness_check`),
+//! - the XApp is registered with the App Manager, if the registration is managed by the framework,
+//! - all the readiness checks added by the XApp pass.
+//!
+//! The XApp is alive, when all the threads of the framework are active (ie. they have updated
+//! their heartbeat within the liveness timeout) and all the liveness checks added by the XApp
+//! pass.
+//!
+//! `/ric/v1/health/ready` and `/ric/v1/health/alive` return `503 Service Unavailable` with the
+//! names of the failed checks, when the XApp is not ready or not alive. `/ric/v1/health` returns
+//! the details of all the checks.
+//!
+//! ```ignore
+//!     xapp.add_readiness_check("model", move || {
+//!         if model.is_loaded() {
+//!             Ok(())
+//!         } else {
+//!             Err("Model not loaded".to_string())
+//!         }
+//!     })?;
+//! ```
+
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use rmr::RMRClient;
+use sdl::{RedisStorage, SdlStorageApi};
+
+use super::XApp;
+use crate::XAppError;
+
+const DEFAULT_LIVENESS_TIMEOUT: Duration = Duration::from_secs(30);
+
+const SDL_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A named Health Check added by the XApp
+///
+/// Returns an error describing the problem, when the check fails.
+pub type HealthCheck = Box<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+// Heartbeat of a thread of the framework: Time of the last beat in milliseconds since the UNIX
+// Epoch.
+#[derive(Clone, Default)]
+pub(crate) struct Heartbeat(Arc<AtomicU64>);
+
+impl Heartbeat {
+    pub(crate) fn beat(&self) {
+        self.0.store(epoch_millis(), Ordering::Relaxed);
+    }
+
+    fn since_last_beat(&self) -> Duration {
+        Duration::from_millis(epoch_millis().saturating_sub(self.0.load(Ordering::Relaxed)))
+    }
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Probe {
+    Readiness,
+    Liveness,
+}
+
+// Result of a check, as shown in the health details.
+#[derive(Debug, Serialize)]
+pub(crate) struct CheckResult {
+    name: String,
+    probe: Probe,
+    healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl CheckResult {
+    fn new(name: &str, probe: Probe, result: Result<(), String>) -> Self {
+        Self {
+            name: name.to_string(),
+            probe,
+            healthy: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+// Health of the XApp, from the results of all the checks.
+#[derive(Debug, Serialize)]
+pub(crate) struct HealthReport {
+    pub(crate) ready: bool,
+    pub(crate) alive: bool,
+    pub(crate) checks: Vec<CheckResult>,
+}
+
+impl HealthReport {
+    // Names of the failed checks of the probe.
+    pub(crate) fn failed(&self, probe: Probe) -> Vec<&str> {
+        self.checks
+            .iter()
+            .filter(|check| check.probe == probe && !check.healthy)
+            .map(|check| check.name.as_str())
+            .collect()
+    }
+}
+
+// The SDL is checked using an SDL Client of its own, so that a slow DB does not block the users
+// of the SDL Client of the XApp while the check is in progress.
+pub(crate) struct SdlCheck {
+    storage: Mutex<RedisStorage>,
+    namespace: String,
+}
+
+impl SdlCheck {
+    pub(crate) fn new(storage: RedisStorage, namespace: &str) -> Self {
+        Self {
+            storage: Mutex::new(storage),
+            namespace: namespace.to_string(),
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let mut sdl = self
+            .storage
+            .lock()
+            .map_err(|_| "Corrupted SDL Client".to_string())?;
+        sdl.ping(&self.namespace, SDL_PING_TIMEOUT)
+            .map_err(|e| format!("SDL is not reachable: {}", e))?;
+        if sdl.is_ready(&self.namespace) {
+            Ok(())
+        } else {
+            Err("SDL is not ready".to_string())
+        }
+    }
+}
+
+// Health Monitor: Shared with the Web Server. The checks may block, hence they are run on a clone
+// of the Health Monitor taken out of the lock.
+#[derive(Clone)]
+pub(crate) struct HealthMonitor {
+    rmr_client: Arc<Mutex<RMRClient>>,
+    sdl: Arc<SdlCheck>,
+    sdl_enabled: bool,
+    registered: Option<Arc<AtomicBool>>,
+    heartbeats: Vec<(String, Heartbeat)>,
+    checks: Vec<(String, Probe, SharedHealthCheck)>,
+    liveness_timeout: Duration,
+}
+
+type SharedHealthCheck = Arc<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+impl HealthMonitor {
+    pub(crate) fn new(rmr_client: Arc<Mutex<RMRClient>>, sdl: SdlCheck) -> Self {
+        Self {
+            rmr_client,
+            sdl: Arc::new(sdl),
+            sdl_enabled: true,
+            registered: None,
+            heartbeats: vec![],
+            checks: vec![],
+            liveness_timeout: DEFAULT_LIVENESS_TIMEOUT,
+        }
+    }
+
+    // Results of all the checks.
+    pub(crate) fn report(&self) -> HealthReport {
+        self.report_for(&[Probe::Readiness, Probe::Liveness])
+    }
+
+    // Results of the checks for the given probes only. `ready` and `alive` of the report are
+    // `true` for the probes that are not checked.
+    pub(crate) fn report_for(&self, probes: &[Probe]) -> HealthReport {
+        let mut checks = vec![];
+
+        if probes.contains(&Probe::Readiness) {
+            self.readiness_checks(&mut checks);
+        }
+        if probes.contains(&Probe::Liveness) {
+            self.liveness_checks(&mut checks);
+        }
+
+        for (name, probe, check) in &self.checks {
+            if !probes.contains(probe) {
+                continue;
+            }
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| check()))
+                .unwrap_or_else(|_| Err("Health Check panicked".to_string()));
+            checks.push(CheckResult::new(name, *probe, result));
+        }
+
+        HealthReport {
+            ready: checks
+                .iter()
+                .all(|check| check.probe != Probe::Readiness || check.healthy),
+            alive: checks
+                .iter()
+                .all(|check| check.probe != Probe::Liveness || check.healthy),
+            checks,
+        }
+    }
+
+    fn readiness_checks(&self, checks: &mut Vec<CheckResult>) {
+        checks.push(CheckResult::new("rmr", Probe::Readiness, self.check_rmr()));
+
+        if self.sdl_enabled {
+            checks.push(CheckResult::new("sdl", Probe::Readiness, self.sdl.check()));
+        }
+
+        if let Some(ref registered) = self.registered {
+            let result = if registered.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err("Not registered with the App Manager".to_string())
+            };
+            checks.push(CheckResult::new("registration", Probe::Readiness, result));
+        }
+    }
+
+    fn liveness_checks(&self, checks: &mut Vec<CheckResult>) {
+        for (name, heartbeat) in &self.heartbeats {
+            let since_last_beat = heartbeat.since_last_beat();
+            let result = if since_last_beat <= self.liveness_timeout {
+                Ok(())
+            } else {
+                Err(format!(
+                    "No heartbeat since {} seconds",
+                    since_last_beat.as_secs()
+                ))
+            };
+            checks.push(CheckResult::new(name, Probe::Liveness, result));
+        }
+    }
+
+    fn check_rmr(&self) -> Result<(), String> {
+        // A poisoned lock means, a thread using the RMR Client has died.
+        let ready = self
+            .rmr_client
+            .lock()
+            .map(|client| client.is_ready())
+            .unwrap_or(false);
+        if ready {
+            Ok(())
+        } else {
+            Err("RMR Client is not ready".to_string())
+        }
+    }
+
+    fn add_check(&mut self, name: &str, probe: Probe, check: HealthCheck) -> Result<(), XAppError> {
+        if self.checks.iter().any(|(n, p, _)| n == name && *p == probe) {
+            return Err(XAppError(format!(
+                "Health Check '{}' is already added.",
+                name
+            )));
+        }
+        self.checks
+            .push((name.to_string(), probe, Arc::from(check)));
+
+        Ok(())
+    }
+}
+
+impl XApp {
+    /// Add a check for the readiness of the XApp
+    ///
+    /// The XApp is not ready, when the check returns an error. The checks are run for every
+    /// request to the readiness probe and should hence be quick.
+    pub fn add_readiness_check<F>(&mut self, name: &str, check: F) -> Result<(), XAppError>
+    where
+        F: Fn() -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .add_check(name, Probe::Readiness, Box::new(check))
+    }
+
+    /// Add a check for the liveness of the XApp
+    ///
+    /// The XApp is not alive, when the check returns an error. See `add_readiness_check` for
+    /// details.
+    pub fn add_liveness_check<F>(&mut self, name: &str, check: F) -> Result<(), XAppError>
+    where
+        F: Fn() -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .add_check(name, Probe::Liveness, Box::new(check))
+    }
+
+    /// Set the time after which a thread of the framework without a heartbeat is considered dead
+    ///
+    /// Default is 30 seconds.
+    pub fn set_liveness_timeout(&mut self, timeout: Duration) {
+        self.health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .liveness_timeout = timeout;
+    }
+
+    /// Enable or disable the SDL check for the readiness of the XApp
+    ///
+    /// By default the XApp is not ready, when the SDL is not reachable. XApps not using the SDL
+    /// can disable this check.
+    pub fn set_sdl_readiness_check(&mut self, enabled: bool) {
+        self.health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .sdl_enabled = enabled;
+    }
+
+    // Heartbeat for a thread of the framework, that is checked for the liveness of the XApp.
+    pub(crate) fn heartbeat(&self, thread: &str) -> Heartbeat {
+        let heartbeat = Heartbeat::default();
+        self.add_heartbeat(thread, heartbeat.clone());
+
+        heartbeat
+    }
+
+    // The thread is considered alive at the start.
+    pub(crate) fn add_heartbeat(&self, thread: &str, heartbeat: Heartbeat) {
+        heartbeat.beat();
+        let mut health = self.health.lock().expect("Corrupted HealthMonitor Mutex");
+        health.heartbeats.retain(|(name, _)| name != thread);
+        health.heartbeats.push((thread.to_string(), heartbeat));
+    }
+
+    // Readiness depends on the registration only if it is managed by the framework.
+    pub(crate) fn set_registration_readiness(&self) {
+        let registered = self
+            .registration_request
+            .as_ref()
+            .map(|_| Arc::clone(&self.app_is_registered));
+        self.health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .registered = registered;
+    }
+}
+
+impl From<Arc<AtomicU64>> for Heartbeat {
+    fn from(last_active: Arc<AtomicU64>) -> Self {
+        Self(last_active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::Probe;
+    use crate::xapp::test_utils::test_xapp;
+
+    #[test]
+    fn test_health_checks() {
+        let mut xapp = test_xapp();
+        xapp.set_sdl_readiness_check(false);
+
+        let result = xapp.add_readiness_check("model", || Err("Model not loaded".to_string()));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert!(xapp.add_readiness_check("model", || Ok(())).is_err());
+        assert!(xapp.add_liveness_check("model", || Ok(())).is_ok());
+
+        let heartbeat = xapp.heartbeat("test_thread");
+        xapp.set_liveness_timeout(Duration::from_millis(200));
+
+        let report = xapp.health.lock().unwrap().report();
+        assert!(!report.ready);
+        assert!(report.failed(Probe::Readiness).contains(&"model"));
+        assert!(report.alive, "{:?}", report);
+
+        let json = serde_json::to_value(&report).unwrap();
+        let checks = json["checks"].as_array().unwrap();
+        assert!(!checks.iter().any(|check| check["name"] == "sdl"));
+        assert!(checks.contains(&serde_json::json!({
+            "name": "model",
+            "probe": "readiness",
+            "healthy": false,
+            "error": "Model not loaded",
+        })));
+
+        std::thread::sleep(Duration::from_millis(300));
+        let report = xapp.health.lock().unwrap().report();
+        assert!(!report.alive);
+        assert_eq!(report.failed(Probe::Liveness), vec!["test_thread"]);
+
+        heartbeat.beat();
+        let report = xapp.health.lock().unwrap().report();
+        assert!(report.alive, "{:?}", report);
+    }
+
+    #[test]
+    fn test_liveness_does_not_run_readiness_checks() {
+        let mut xapp = test_xapp();
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        let readiness_runs = Arc::clone(&runs);
+        let result = xapp.add_readiness_check("slow", move || {
+            readiness_runs.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+
+        let health = xapp.health.lock().unwrap().clone();
+        let report = health.report_for(&[Probe::Liveness]);
+        assert!(report.alive, "{:?}", report);
+        assert!(report.checks.iter().all(|c| c.probe == Probe::Liveness));
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        // The SDL is not reachable in the tests, only the readiness fails.
+        let report = health.report_for(&[Probe::Readiness]);
+        assert!(report.failed(Probe::Readiness).contains(&"sdl"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,431 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! RIC compatible structured logging
+//!
+//! The framework provides a logger for the `log` facade, that logs every record as a JSON line in
+//! the format used by the `mdclog` library of the RIC Platform -
+//!
+//! ```text
+//! {"ts":1678000000000,"crit":"INFO","id":"hw-rust","mdc":{"MEID":"gnb_001","MTYPE":"12010","PID":"1","XAPP_INSTANCE":"hw-rust-1","XAPP_NAME":"hw-rust"},"msg":"Received message"}
+//! ```
+//!
+//! The logger is installed using `XApp::enable_ric_logging`. Mapped Diagnostic Context (MDC)
+//! contains the XApp name and instance for all the records. `MEID` and `MTYPE` are added to the
+//! MDC while a handler registered with `register_handler` processes a message. XApp can add its
+//! own fields using `mdc_put` (for the current thread).
+//!
+//! The log level is taken from the `logger.level` (or `controls.logger.level`) of the XApp config
+//! and can be changed at run time using `XApp::set_log_level` or the `/ric/v1/loglevel` end point
+//! of the XApp.
+//!
+//! The most recent log lines are also kept in memory, for the symptom data of the XApp.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{XApp, XAppConfig, XAppError};
+
+/// MDC Key for the name of the XApp
+pub(crate) const MDC_XAPP_NAME: &str = "XAPP_NAME";
+
+/// MDC Key for the instance name of the XApp
+pub(crate) const MDC_XAPP_INSTANCE: &str = "XAPP_INSTANCE";
+
+/// MDC Key for the MEID of the RMR Message being processed
+pub(crate) const MDC_MEID: &str = "MEID";
+
+/// MDC Key for the type of the RMR Message being processed
+pub(crate) const MDC_MTYPE: &str = "MTYPE";
+
+const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+// Number of the most recent log lines kept in memory.
+const RECENT_LINES_CAPACITY: usize = 1000;
+
+static LOGGER: OnceLock<RicLogger> = OnceLock::new();
+
+// Is the `LOGGER` installed for the `log` facade? Also serializes the installation.
+static INSTALLED: Mutex<bool> = Mutex::new(false);
+
+thread_local! {
+    static THREAD_MDC: RefCell<BTreeMap<String, String>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+// Logger writing the `mdclog` format JSON lines to the standard output.
+struct RicLogger {
+    id: RwLock<String>,
+    mdc: RwLock<BTreeMap<String, String>>,
+    recent: Mutex<VecDeque<String>>,
+}
+
+#[derive(Serialize)]
+struct LogLine<'a> {
+    ts: u128,
+    crit: &'static str,
+    id: &'a str,
+    mdc: BTreeMap<String, String>,
+    msg: String,
+}
+
+impl RicLogger {
+    fn new(id: &str) -> Self {
+        let mut mdc = BTreeMap::new();
+        let _ = mdc.insert("PID".to_string(), std::process::id().to_string());
+        Self {
+            id: RwLock::new(id.to_string()),
+            mdc: RwLock::new(mdc),
+            recent: Mutex::new(VecDeque::with_capacity(RECENT_LINES_CAPACITY)),
+        }
+    }
+
+    // Keeps the line in the ring buffer of the recent lines.
+    fn remember(&self, line: &str) {
+        let mut recent = self
+            .recent
+            .lock()
+            .expect("Corrupted Logger Recent Lines Mutex");
+        if recent.len() == RECENT_LINES_CAPACITY {
+            let _ = recent.pop_front();
+        }
+        recent.push_back(line.to_string());
+    }
+
+    fn format(&self, record: &Record) -> String {
+        let mut mdc = self.mdc.read().expect("Corrupted Logger MDC Lock").clone();
+        THREAD_MDC.with(|thread_mdc| {
+            mdc.extend(
+                thread_mdc
+                    .borrow()
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            )
+        });
+
+        let line = LogLine {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or_default(),
+            crit: crit(record.level()),
+            id: &self.id.read().expect("Corrupted Logger ID Lock"),
+            mdc,
+            msg: record.args().to_string(),
+        };
+
+        serde_json::to_string(&line).unwrap_or_default()
+    }
+}
+
+impl Log for RicLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let line = self.format(record);
+            let _ = writeln!(std::io::stdout().lock(), "{}", line);
+            self.remember(&line);
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().lock().flush();
+    }
+}
+
+// `mdclog` does not have `TRACE` level, those are logged as `DEBUG`.
+fn crit(level: Level) -> &'static str {
+    match level {
+        Level::Error => "ERROR",
+        Level::Warn => "WARNING",
+        Level::Info => "INFO",
+        Level::Debug | Level::Trace => "DEBUG",
+    }
+}
+
+/// Log Level as used in the XApp config and the `/ric/v1/loglevel` end point
+///
+/// Either the `mdclog` numeric level (`1`: ERROR, `2`: WARNING, `3`: INFO, `4`: DEBUG) or the
+/// name of the level (eg. `"DEBUG"`).
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum LogLevel {
+    Numeric(u64),
+    Name(String),
+}
+
+impl LogLevel {
+    pub(crate) fn to_level_filter(&self) -> Result<LevelFilter, XAppError> {
+        match self {
+            LogLevel::Numeric(0) => Ok(LevelFilter::Off),
+            LogLevel::Numeric(1) => Ok(LevelFilter::Error),
+            LogLevel::Numeric(2) => Ok(LevelFilter::Warn),
+            LogLevel::Numeric(3) => Ok(LevelFilter::Info),
+            LogLevel::Numeric(4) => Ok(LevelFilter::Debug),
+            LogLevel::Numeric(5) => Ok(LevelFilter::Trace),
+            LogLevel::Name(name) if name.eq_ignore_ascii_case("warning") => Ok(LevelFilter::Warn),
+            LogLevel::Name(name) => name
+                .parse()
+                .map_err(|_| XAppError(format!("Invalid Log Level: '{}'", name))),
+            LogLevel::Numeric(level) => Err(XAppError(format!("Invalid Log Level: {}", level))),
+        }
+    }
+
+    pub(crate) fn from_level_filter(level: LevelFilter) -> Self {
+        let name = match level {
+            LevelFilter::Off => "OFF",
+            LevelFilter::Error => "ERROR",
+            LevelFilter::Warn => "WARNING",
+            LevelFilter::Info => "INFO",
+            LevelFilter::Debug => "DEBUG",
+            LevelFilter::Trace => "TRACE",
+        };
+        LogLevel::Name(name.to_string())
+    }
+}
+
+/// Get the Log Level from the XApp config, if present.
+pub(crate) fn level_from_config(config: &XAppConfig) -> Result<Option<LevelFilter>, XAppError> {
+    let config = &config.config;
+    let level = if !config["logger"]["level"].is_null() {
+        &config["logger"]["level"]
+    } else {
+        &config["controls"]["logger"]["level"]
+    };
+    if level.is_null() {
+        return Ok(None);
+    }
+
+    let level: LogLevel = serde_json::from_value(level.clone())
+        .map_err(|e| XAppError(format!("Invalid Log Level in the config: {}", e)))?;
+    level.to_level_filter().map(Some)
+}
+
+/// Set the Log Level of the framework logger.
+pub(crate) fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+/// Add a field to the MDC of the records logged from the current thread.
+pub fn mdc_put(key: &str, value: &str) {
+    THREAD_MDC.with(|mdc| {
+        let _ = mdc.borrow_mut().insert(key.to_string(), value.to_string());
+    });
+}
+
+/// Remove a field from the MDC of the records logged from the current thread.
+pub fn mdc_remove(key: &str) {
+    THREAD_MDC.with(|mdc| {
+        let _ = mdc.borrow_mut().remove(key);
+    });
+}
+
+/// Clear the MDC of the records logged from the current thread.
+pub fn mdc_clear() {
+    THREAD_MDC.with(|mdc| mdc.borrow_mut().clear());
+}
+
+// Fields of the MDC common to all the records.
+pub(crate) fn global_mdc_put(key: &str, value: &str) {
+    if let Some(logger) = installed_logger() {
+        let _ = logger
+            .mdc
+            .write()
+            .expect("Corrupted Logger MDC Lock")
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+// Recent log lines, oldest first. Empty if the framework logger is not installed.
+pub(crate) fn recent_lines() -> Vec<String> {
+    installed_logger()
+        .map(|logger| {
+            logger
+                .recent
+                .lock()
+                .expect("Corrupted Logger Recent Lines Mutex")
+                .iter()
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// The framework logger, if it is installed.
+fn installed_logger() -> Option<&'static RicLogger> {
+    if *INSTALLED.lock().expect("Corrupted Logger Installed Mutex") {
+        LOGGER.get()
+    } else {
+        None
+    }
+}
+
+// Installs the framework logger, unless it is already installed, in which case only the ID of
+// the logger is updated.
+fn install(xapp_name: &str) -> Result<(), XAppError> {
+    let mut installed = INSTALLED.lock().expect("Corrupted Logger Installed Mutex");
+
+    let logger = LOGGER.get_or_init(|| RicLogger::new(xapp_name));
+    if *installed {
+        *logger.id.write().expect("Corrupted Logger ID Lock") = xapp_name.to_string();
+    } else {
+        log::set_logger(logger)
+            .map_err(|e| XAppError(format!("Error installing the logger: {}", e)))?;
+        *installed = true;
+    }
+
+    Ok(())
+}
+
+impl XApp {
+    /// Log in the RIC compatible JSON format
+    ///
+    /// Installs the framework logger for the `log` facade, with the log level from the XApp
+    /// config (`INFO` if not present). Returns an error if a different logger is already
+    /// installed.
+    pub fn enable_ric_logging(&mut self) -> Result<(), XAppError> {
+        let config = self.config.current();
+        let level = level_from_config(&config)?.unwrap_or(DEFAULT_LOG_LEVEL);
+        let xapp_name = &config.metadata.xapp_name;
+
+        install(xapp_name)?;
+        global_mdc_put(MDC_XAPP_NAME, xapp_name);
+        if let Some(ref instance) = self.app_instance_name {
+            global_mdc_put(MDC_XAPP_INSTANCE, instance);
+        }
+        set_level(level);
+
+        Ok(())
+    }
+
+    /// Set the Log Level
+    pub fn set_log_level(&self, level: LevelFilter) {
+        set_level(level);
+    }
+
+    /// Get the current Log Level
+    pub fn log_level(&self) -> LevelFilter {
+        log::max_level()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::{Level, LevelFilter};
+
+    use super::{LogLevel, RicLogger};
+
+    #[test]
+    fn test_format() {
+        let logger = RicLogger::new("test-xapp");
+        super::mdc_put(super::MDC_MEID, "gnb_001");
+
+        let format = |logger: &RicLogger| {
+            logger.format(
+                &log::Record::builder()
+                    .level(Level::Warn)
+                    .args(format_args!("Hello {}", "RIC"))
+                    .build(),
+            )
+        };
+        let line = format(&logger);
+        super::mdc_clear();
+
+        let line: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(line["crit"], "WARNING");
+        assert_eq!(line["id"], "test-xapp");
+        assert_eq!(line["msg"], "Hello RIC");
+        assert_eq!(line["mdc"]["MEID"], "gnb_001");
+        assert_eq!(line["mdc"]["PID"], std::process::id().to_string());
+        assert!(line["ts"].as_u64().unwrap() > 0);
+
+        // Thread MDC is cleared.
+        let line: serde_json::Value = serde_json::from_str(&format(&logger)).unwrap();
+        assert!(line["mdc"]["MEID"].is_null());
+    }
+
+    #[test]
+    fn test_recent_lines() {
+        let logger = RicLogger::new("test-xapp");
+        for i in 0..super::RECENT_LINES_CAPACITY + 2 {
+            logger.remember(&i.to_string());
+        }
+
+        let recent = logger.recent.lock().unwrap();
+        assert_eq!(recent.len(), super::RECENT_LINES_CAPACITY);
+        assert_eq!(recent.front().unwrap(), "2");
+    }
+
+    #[test]
+    fn test_install_with_other_logger() {
+        // No other test installs a logger.
+        static OTHER: NoLogger = NoLogger;
+        assert!(log::set_logger(&OTHER).is_ok());
+
+        // Not installed, even if tried again.
+        assert!(super::install("test-xapp").is_err());
+        assert!(super::install("test-xapp").is_err());
+        assert!(super::installed_logger().is_none());
+        assert!(super::recent_lines().is_empty());
+    }
+
+    struct NoLogger;
+
+    impl log::Log for NoLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            false
+        }
+
+        fn log(&self, _: &log::Record) {}
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_level_from_config() {
+        let mut config = crate::xapp::tests::get_config_data(4560);
+        assert!(matches!(super::level_from_config(&config), Ok(None)));
+
+        config.config["logger"] = serde_json::json!({ "level": 4 });
+        assert!(matches!(
+            super::level_from_config(&config),
+            Ok(Some(LevelFilter::Debug))
+        ));
+
+        config.config["logger"] = serde_json::json!({ "level": "warning" });
+        assert!(matches!(
+            super::level_from_config(&config),
+            Ok(Some(LevelFilter::Warn))
+        ));
+
+        config.config["logger"] = serde_json::json!({ "level": 9 });
+        assert!(super::level_from_config(&config).is_err());
+
+        assert_eq!(
+            LogLevel::from_level_filter(LevelFilter::Error),
+            LogLevel::Name("ERROR".to_string())
+        );
+    }
+}
@@ -0,0 +1,252 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Recent RMR Messages of the XApp
+//!
+//! For troubleshooting the message flows (eg. E2), the framework can keep the most recent RMR
+//! Messages received and sent by the XApp, in a bounded ring buffer. For every message, the time,
+//! message type, MEID, subscription ID, length and the first few bytes of the payload (as hex) are
+//! kept. This is disabled by default and is enabled using `enable_message_log`.
+//!
+//! The messages are served on `/ric/v1/debug/messages` of the XApp, optionally filtered by the
+//! message type and / or the MEID (eg. `/ric/v1/debug/messages?mtype=12050&meid=gnb_001`).
+//!
+//! ```ignore
+//!     xapp.enable_message_log(1000);
+//!     xapp.start();
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use rmr::RMRMessageBuffer;
+
+use super::XApp;
+
+// Only these many bytes of the payload are kept for a message.
+const MAX_PAYLOAD_BYTES: usize = 64;
+
+/// Direction of an RMR Message, with respect to the XApp
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDirection {
+    /// Received by the XApp
+    Received,
+
+    /// Sent by the XApp
+    Sent,
+}
+
+/// An RMR Message received or sent by the XApp
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MessageRecord {
+    /// Time of the message in milliseconds since the UNIX Epoch
+    pub timestamp: u64,
+
+    /// Received or Sent
+    pub direction: MessageDirection,
+
+    /// Message Type
+    pub mtype: i32,
+
+    /// MEID (Managed Entity ID) of the message, if set
+    pub meid: Option<String>,
+
+    /// Subscription ID of the message (`-1` if not set)
+    pub sub_id: i32,
+
+    /// Length of the payload in bytes
+    pub length: usize,
+
+    /// Hex of the payload, truncated to the first 64 bytes
+    pub payload: String,
+}
+
+impl MessageRecord {
+    fn new(direction: MessageDirection, msg: &RMRMessageBuffer) -> Self {
+        let payload = msg.get_payload();
+        let mut hex = String::with_capacity(2 * MAX_PAYLOAD_BYTES);
+        for byte in payload.iter().take(MAX_PAYLOAD_BYTES) {
+            let _ = write!(hex, "{:02x}", byte);
+        }
+
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            direction,
+            mtype: msg.get_msgtype(),
+            meid: msg.get_meid(),
+            sub_id: msg.get_sub_id(),
+            length: payload.len(),
+            payload: hex,
+        }
+    }
+}
+
+/// Filter for the Recent RMR Messages
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct MessageFilter {
+    /// Only the messages of this message type
+    pub mtype: Option<i32>,
+
+    /// Only the messages for this MEID
+    pub meid: Option<String>,
+}
+
+impl MessageFilter {
+    fn matches(&self, record: &MessageRecord) -> bool {
+        self.mtype.is_none_or(|mtype| record.mtype == mtype)
+            && self
+                .meid
+                .as_ref()
+                .is_none_or(|meid| record.meid.as_ref() == Some(meid))
+    }
+}
+
+// Message Log: Shared by the `RMRSender`s, Message Processor and the Web Server. Disabled, when
+// the capacity is zero.
+#[derive(Default)]
+pub(crate) struct MessageLog {
+    capacity: AtomicUsize,
+    records: Mutex<VecDeque<MessageRecord>>,
+}
+
+impl MessageLog {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.capacity.load(Ordering::Relaxed) > 0
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        let mut records = self.records.lock().expect("Corrupted MessageLog Mutex");
+        self.capacity.store(capacity, Ordering::Relaxed);
+        while records.len() > capacity {
+            let _ = records.pop_front();
+        }
+    }
+
+    pub(crate) fn record(&self, direction: MessageDirection, msg: &RMRMessageBuffer) {
+        if let Some(record) = self.prepare(direction, msg) {
+            self.push(record);
+        }
+    }
+
+    // Record of the message, if enabled. For the messages that are to be recorded only after
+    // they are sent, since the buffer is returned by RMR after sending.
+    pub(crate) fn prepare(
+        &self,
+        direction: MessageDirection,
+        msg: &RMRMessageBuffer,
+    ) -> Option<MessageRecord> {
+        if self.is_enabled() {
+            Some(MessageRecord::new(direction, msg))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn push(&self, record: MessageRecord) {
+        let mut records = self.records.lock().expect("Corrupted MessageLog Mutex");
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        while !records.is_empty() && records.len() >= capacity {
+            let _ = records.pop_front();
+        }
+        if capacity > 0 {
+            records.push_back(record);
+        }
+    }
+
+    // Matching records, oldest first.
+    pub(crate) fn records(&self, filter: &MessageFilter) -> Vec<MessageRecord> {
+        self.records
+            .lock()
+            .expect("Corrupted MessageLog Mutex")
+            .iter()
+            .filter(|record| filter.matches(record))
+            .cloned()
+            .collect()
+    }
+}
+
+impl XApp {
+    /// Keep the most recent RMR Messages received and sent by the XApp
+    ///
+    /// Up to `capacity` messages are kept, the oldest messages are dropped first. A `capacity` of
+    /// zero disables it again and drops the messages kept so far.
+    pub fn enable_message_log(&mut self, capacity: usize) {
+        self.message_log.set_capacity(capacity);
+    }
+
+    /// Get the Recent RMR Messages matching the filter, oldest first
+    pub fn recent_messages(&self, filter: &MessageFilter) -> Vec<MessageRecord> {
+        self.message_log.records(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MessageDirection, MessageFilter};
+    use crate::xapp::test_utils::test_xapp;
+
+    #[test]
+    fn test_message_log() {
+        let mut xapp = test_xapp();
+
+        let sender = xapp.get_rmr_sender();
+        let mut msg = sender.alloc_msg();
+        msg.set_mtype(12050);
+        msg.set_payload(&[0xab; 100]);
+
+        // Disabled by default.
+        xapp.message_log.record(MessageDirection::Received, &msg);
+        assert!(xapp.recent_messages(&MessageFilter::default()).is_empty());
+
+        xapp.enable_message_log(2);
+        xapp.message_log.record(MessageDirection::Received, &msg);
+        msg.set_mtype(12010);
+        xapp.message_log.record(MessageDirection::Sent, &msg);
+        xapp.message_log.record(MessageDirection::Sent, &msg);
+
+        let records = xapp.recent_messages(&MessageFilter::default());
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|r| r.direction == MessageDirection::Sent && r.mtype == 12010));
+        assert_eq!(records[0].length, 100);
+        assert_eq!(records[0].payload, "ab".repeat(64));
+
+        let filter = MessageFilter {
+            mtype: Some(12050),
+            meid: None,
+        };
+        assert!(xapp.recent_messages(&filter).is_empty());
+        let filter = MessageFilter {
+            mtype: Some(12010),
+            meid: Some("gnb_001".to_string()),
+        };
+        assert!(xapp.recent_messages(&filter).is_empty());
+
+        xapp.enable_message_log(0);
+        assert!(xapp.recent_messages(&MessageFilter::default()).is_empty());
+        msg.free();
+    }
+}
@@ -0,0 +1,248 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Typed handles for the metrics registered by the XApp
+//!
+//! The handles are returned when a metric is registered. Updating a metric through its handle
+//! does not require a lookup of the metric in the registry. Label values are given in the same
+//! order as the label names used for registering the metric.
+//!
+//! ```ignore
+//!     let requests = xapp.register_counter("requests", "Number of requests", &["method", "code"])?;
+//!
+//!     requests.inc(&["GET", "200"])?;
+//! ```
+
+use std::fmt::Write;
+use std::sync::atomic::AtomicU64;
+
+use prometheus_client::encoding::{EncodeLabelValue, LabelValueEncoder};
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::{Family, MetricConstructor};
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::Histogram;
+
+use crate::XAppError;
+
+pub(crate) type LabelSet = Vec<(String, LabelValue)>;
+
+// Value of a label given by the XApp. The encoder writes the label values as they are, so the value
+// is escaped here as per the OpenMetrics text format.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct LabelValue(String);
+
+impl EncodeLabelValue for LabelValue {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        for c in self.0.chars() {
+            match c {
+                '\\' => encoder.write_str("\\\\")?,
+                '"' => encoder.write_str("\\\"")?,
+                '\n' => encoder.write_str("\\n")?,
+                c => encoder.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Label names of a metric, used for validating the labels while updating a metric.
+#[derive(Clone, Debug)]
+pub(crate) struct Labels {
+    metric: String,
+    names: Vec<String>,
+}
+
+impl Labels {
+    pub(crate) fn new(metric: &str, names: &[&str]) -> Result<Self, XAppError> {
+        for (idx, name) in names.iter().enumerate() {
+            if !super::is_valid_name(name) || name.starts_with("__") {
+                return Err(XAppError(format!(
+                    "Metric '{}': Invalid label name '{}'.",
+                    metric, name
+                )));
+            }
+            if names[..idx].contains(name) {
+                return Err(XAppError(format!(
+                    "Metric '{}': Duplicate label name '{}'.",
+                    metric, name
+                )));
+            }
+        }
+
+        Ok(Self {
+            metric: metric.to_string(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+        })
+    }
+
+    // Label Set from the values given in the order of label names.
+    pub(crate) fn label_set(&self, values: &[&str]) -> Result<LabelSet, XAppError> {
+        if values.len() != self.names.len() {
+            return Err(XAppError(format!(
+                "Metric '{}': Expected {} label values, got {}.",
+                self.metric,
+                self.names.len(),
+                values.len()
+            )));
+        }
+
+        Ok(self
+            .names
+            .iter()
+            .zip(values)
+            .map(|(n, v)| (n.clone(), LabelValue(v.to_string())))
+            .collect())
+    }
+
+    // Label Set from the `(name, value)` pairs given in any order.
+    pub(crate) fn label_set_from_pairs(
+        &self,
+        pairs: &[(&str, &str)],
+    ) -> Result<LabelSet, XAppError> {
+        if pairs.len() != self.names.len() {
+            return Err(XAppError(format!(
+                "Metric '{}': Expected labels {:?}, got {} labels.",
+                self.metric,
+                self.names,
+                pairs.len()
+            )));
+        }
+
+        self.names
+            .iter()
+            .map(|name| {
+                pairs
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| (name.clone(), LabelValue(v.to_string())))
+                    .ok_or_else(|| {
+                        XAppError(format!(
+                            "Metric '{}': Label '{}' not given.",
+                            self.metric, name
+                        ))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Handle for a Counter registered using `XApp::register_counter`
+#[derive(Clone, Debug)]
+pub struct CounterHandle {
+    pub(crate) family: Family<LabelSet, Counter>,
+    pub(crate) labels: Labels,
+}
+
+impl CounterHandle {
+    /// Increment the Counter for the given label values by one.
+    pub fn inc(&self, label_values: &[&str]) -> Result<(), XAppError> {
+        self.inc_by(label_values, 1)
+    }
+
+    /// Increment the Counter for the given label values by `value`.
+    pub fn inc_by(&self, label_values: &[&str], value: u64) -> Result<(), XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        let _ = self.family.get_or_create(&labels).inc_by(value);
+        Ok(())
+    }
+
+    /// Get the value of the Counter for the given label values.
+    pub fn get(&self, label_values: &[&str]) -> Result<u64, XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        Ok(self.family.get_or_create(&labels).get())
+    }
+}
+
+/// Handle for a Gauge registered using `XApp::register_gauge`
+#[derive(Clone, Debug)]
+pub struct GaugeHandle {
+    pub(crate) family: Family<LabelSet, Gauge<f64, AtomicU64>>,
+    pub(crate) labels: Labels,
+}
+
+impl GaugeHandle {
+    /// Increment the Gauge for the given label values by `value`.
+    pub fn inc_by(&self, label_values: &[&str], value: f64) -> Result<(), XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        let _ = self.family.get_or_create(&labels).inc_by(value);
+        Ok(())
+    }
+
+    /// Decrement the Gauge for the given label values by `value`.
+    pub fn dec_by(&self, label_values: &[&str], value: f64) -> Result<(), XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        let _ = self.family.get_or_create(&labels).dec_by(value);
+        Ok(())
+    }
+
+    /// Set the Gauge for the given label values to `value`.
+    pub fn set(&self, label_values: &[&str], value: f64) -> Result<(), XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        let _ = self.family.get_or_create(&labels).set(value);
+        Ok(())
+    }
+
+    /// Get the value of the Gauge for the given label values.
+    pub fn get(&self, label_values: &[&str]) -> Result<f64, XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        Ok(self.family.get_or_create(&labels).get())
+    }
+}
+
+// Creates the Histograms of a family with the buckets used for registering the Histogram.
+#[derive(Clone, Debug)]
+pub(crate) struct HistogramBuckets(pub(crate) Vec<f64>);
+
+impl MetricConstructor<Histogram> for HistogramBuckets {
+    fn new_metric(&self) -> Histogram {
+        Histogram::new(self.0.iter().cloned())
+    }
+}
+
+impl HistogramBuckets {
+    pub(crate) fn new(metric: &str, buckets: &[f64]) -> Result<Self, XAppError> {
+        if buckets.is_empty() {
+            return Err(XAppError(format!(
+                "Histogram '{}': No buckets given.",
+                metric
+            )));
+        }
+        if buckets.iter().any(|b| !b.is_finite()) || buckets.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(XAppError(format!(
+                "Histogram '{}': Buckets should be finite and in increasing order.",
+                metric
+            )));
+        }
+
+        Ok(Self(buckets.to_vec()))
+    }
+}
+
+/// Handle for a Histogram registered using `XApp::register_histogram`
+#[derive(Clone, Debug)]
+pub struct HistogramHandle {
+    pub(crate) family: Family<LabelSet, Histogram, HistogramBuckets>,
+    pub(crate) labels: Labels,
+}
+
+impl HistogramHandle {
+    /// Observe the `value` in the Histogram for the given label values.
+    pub fn observe(&self, label_values: &[&str], value: f64) -> Result<(), XAppError> {
+        let labels = self.labels.label_set(label_values)?;
+        self.family.get_or_create(&labels).observe(value);
+        Ok(())
+    }
+}
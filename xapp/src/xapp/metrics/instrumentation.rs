@@ -0,0 +1,388 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Metrics recorded automatically by the framework
+//!
+//! When the metrics are enabled for the XApp, the framework records following metrics without
+//! any code in the XApp -
+//!
+//! - `rmr_messages_rx` and `rmr_messages_tx`: RMR Messages received and sent (using `RMRSender`)
+//!   per message type.
+//! - `rmr_handler_latency_seconds` and `rmr_handler_errors`: Time taken by the handlers
+//!   registered using `register_handler` and the errors returned by them, per message type.
+//! - `rmr_receive_queue_depth`: Received RMR Messages waiting to be processed.
+//! - `sdl_operation_latency_seconds` and `sdl_operation_errors`: Time taken by and errors in the
+//!   SDL operations per namespace and operation.
+//! - `http_client_requests`: Results of the HTTP requests to the App Manager, Subscription Manager
+//!   and Alarm Manager.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+
+use reqwest::blocking::{Client as ReqwestClient, RequestBuilder, Response};
+
+use sdl::{DataMap, KeySet, SdlError, SdlStorageApi, ValueType};
+
+use super::handles::{CounterHandle, GaugeHandle, HistogramHandle};
+use super::{MetricsRegistry, RMRMessage};
+use crate::xapp::subscription::registry::SdlHandle;
+use crate::xapp::telemetry;
+use crate::XAppError;
+
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0,
+];
+
+/// Platform Service names used as label values of `http_client_requests`.
+pub(crate) const APPMGR_SERVICE: &str = "appmgr";
+pub(crate) const SUBMGR_SERVICE: &str = "submgr";
+pub(crate) const ALARMMGR_SERVICE: &str = "alarmmgr";
+
+struct FrameworkMetrics {
+    rmr_messages_rx: Family<RMRMessage, Counter>,
+    rmr_messages_tx: Family<RMRMessage, Counter>,
+    handler_latency: HistogramHandle,
+    handler_errors: CounterHandle,
+    receive_queue_depth: GaugeHandle,
+    sdl_latency: HistogramHandle,
+    sdl_errors: CounterHandle,
+    http_requests: CounterHandle,
+}
+
+/// Instrumentation of the framework
+///
+/// A cheap to clone handle to the framework metrics. All the functions are no-op when the metrics
+/// are not enabled for the XApp.
+#[derive(Clone, Default)]
+pub(crate) struct Instrumentation(Option<Arc<FrameworkMetrics>>);
+
+impl Instrumentation {
+    /// Register the framework metrics in the given registry.
+    pub(crate) fn register(registry: &mut MetricsRegistry) -> Result<Self, XAppError> {
+        let handler_latency = registry.register_histogram(
+            "rmr_handler_latency_seconds",
+            "Time taken by the RMR Message handlers",
+            &["message_type"],
+            &LATENCY_BUCKETS,
+        )?;
+        let handler_errors = registry.register_counter(
+            "rmr_handler_errors",
+            "Number of errors returned by the RMR Message handlers",
+            &["message_type"],
+        )?;
+        let receive_queue_depth = registry.register_gauge(
+            "rmr_receive_queue_depth",
+            "Number of received RMR Messages waiting to be processed",
+            &[],
+        )?;
+        let sdl_latency = registry.register_histogram(
+            "sdl_operation_latency_seconds",
+            "Time taken by the SDL operations",
+            &["namespace", "operation"],
+            &LATENCY_BUCKETS,
+        )?;
+        let sdl_errors = registry.register_counter(
+            "sdl_operation_errors",
+            "Number of failed SDL operations",
+            &["namespace", "operation"],
+        )?;
+        let http_requests = registry.register_counter(
+            "http_client_requests",
+            "Number of HTTP requests to the RIC Platform services by the result",
+            &["service", "result"],
+        )?;
+
+        Ok(Self(Some(Arc::new(FrameworkMetrics {
+            rmr_messages_rx: registry.rmr_messages_rx.clone(),
+            rmr_messages_tx: registry.rmr_messages_tx.clone(),
+            handler_latency,
+            handler_errors,
+            receive_queue_depth,
+            sdl_latency,
+            sdl_errors,
+            http_requests,
+        }))))
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub(crate) fn rmr_message_received(&self, message_type: i32) {
+        if let Some(ref metrics) = self.0 {
+            metrics
+                .rmr_messages_rx
+                .get_or_create(&RMRMessage { message_type })
+                .inc();
+        }
+    }
+
+    pub(crate) fn rmr_message_sent(&self, message_type: i32) {
+        if let Some(ref metrics) = self.0 {
+            metrics
+                .rmr_messages_tx
+                .get_or_create(&RMRMessage { message_type })
+                .inc();
+        }
+    }
+
+    pub(crate) fn rmr_message_handled(&self, message_type: i32, elapsed: Duration, ok: bool) {
+        if let Some(ref metrics) = self.0 {
+            let message_type = message_type.to_string();
+            let _ = metrics
+                .handler_latency
+                .observe(&[&message_type], elapsed.as_secs_f64());
+            if !ok {
+                let _ = metrics.handler_errors.inc(&[&message_type]);
+            }
+        }
+    }
+
+    pub(crate) fn set_receive_queue_depth(&self, depth: usize) {
+        if let Some(ref metrics) = self.0 {
+            let _ = metrics.receive_queue_depth.set(&[], depth as f64);
+        }
+    }
+
+    pub(crate) fn sdl_operation(
+        &self,
+        namespace: &str,
+        operation: &str,
+        elapsed: Duration,
+        ok: bool,
+    ) {
+        if let Some(ref metrics) = self.0 {
+            let labels = [namespace, operation];
+            let _ = metrics.sdl_latency.observe(&labels, elapsed.as_secs_f64());
+            if !ok {
+                let _ = metrics.sdl_errors.inc(&labels);
+            }
+        }
+    }
+
+    /// Send a HTTP request to a RIC Platform service.
+    ///
+    /// The request is sent inside a `http_request` span. The result of the request is recorded
+    /// as the HTTP status code of the response or `error` if no response was received.
+    pub(crate) fn send_request(
+        &self,
+        service: &str,
+        client: &ReqwestClient,
+        request: RequestBuilder,
+    ) -> reqwest::Result<Response> {
+        let response = request.build().and_then(|mut request| {
+            let span = telemetry::http_request_span(
+                service,
+                request.method().as_str(),
+                request.url().as_str(),
+            );
+            span.inject_context(request.headers_mut());
+
+            let response = client.execute(request);
+            match response {
+                Ok(ref response) => span.record_status(response.status().as_u16()),
+                Err(ref e) => span.record_error(&e.to_string()),
+            }
+            response
+        });
+
+        if let Some(ref metrics) = self.0 {
+            let result = match response {
+                Ok(ref response) => response.status().as_u16().to_string(),
+                Err(_) => "error".to_string(),
+            };
+            let _ = metrics.http_requests.inc(&[service, &result]);
+        }
+
+        response
+    }
+}
+
+/// SDL Storage that records the latency and the errors of the SDL operations.
+pub(crate) struct InstrumentedStorage {
+    sdl: SdlHandle,
+    instrumentation: Instrumentation,
+}
+
+impl InstrumentedStorage {
+    pub(crate) fn new(sdl: SdlHandle, instrumentation: Instrumentation) -> Self {
+        Self {
+            sdl,
+            instrumentation,
+        }
+    }
+
+    fn observe<T>(
+        &self,
+        namespace: &str,
+        operation: &str,
+        f: impl FnOnce(&mut (dyn SdlStorageApi + Send)) -> Result<T, SdlError>,
+    ) -> Result<T, SdlError> {
+        let start = Instant::now();
+        let result = f(&mut *self.sdl.lock().expect("Corrupted SDL Client Mutex"));
+        self.instrumentation
+            .sdl_operation(namespace, operation, start.elapsed(), result.is_ok());
+        result
+    }
+}
+
+impl SdlStorageApi for InstrumentedStorage {
+    fn is_ready(&mut self, namespace: &str) -> bool {
+        self.sdl
+            .lock()
+            .expect("Corrupted SDL Client Mutex")
+            .is_ready(namespace)
+    }
+
+    fn set(&mut self, namespace: &str, data: &DataMap) -> Result<(), SdlError> {
+        self.observe(namespace, "set", |sdl| sdl.set(namespace, data))
+    }
+
+    fn set_if_not_exists(
+        &mut self,
+        namespace: &str,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), SdlError> {
+        self.observe(namespace, "set_if_not_exists", |sdl| {
+            sdl.set_if_not_exists(namespace, key, value)
+        })
+    }
+
+    fn get(&mut self, namespace: &str, keys: &KeySet) -> Result<DataMap, SdlError> {
+        self.observe(namespace, "get", |sdl| sdl.get(namespace, keys))
+    }
+
+    fn delete(&mut self, namespace: &str, keys: &KeySet) -> Result<(), SdlError> {
+        self.observe(namespace, "delete", |sdl| sdl.delete(namespace, keys))
+    }
+
+    fn delete_if(&mut self, namespace: &str, key: &str, value: &[u8]) -> Result<bool, SdlError> {
+        self.observe(namespace, "delete_if", |sdl| {
+            sdl.delete_if(namespace, key, value)
+        })
+    }
+
+    fn list_keys(&mut self, namespace: &str, pattern: &str) -> Result<KeySet, SdlError> {
+        self.observe(namespace, "list_keys", |sdl| {
+            sdl.list_keys(namespace, pattern)
+        })
+    }
+
+    fn delete_all(&mut self, namespace: &str) -> Result<(), SdlError> {
+        self.observe(namespace, "delete_all", |sdl| sdl.delete_all(namespace))
+    }
+
+    fn add_member(
+        &mut self,
+        namespace: &str,
+        group: &str,
+        value: &ValueType,
+    ) -> Result<(), SdlError> {
+        self.observe(namespace, "add_member", |sdl| {
+            sdl.add_member(namespace, group, value)
+        })
+    }
+
+    fn delete_member(
+        &mut self,
+        namespace: &str,
+        group: &str,
+        value: &ValueType,
+    ) -> Result<(), SdlError> {
+        self.observe(namespace, "delete_member", |sdl| {
+            sdl.delete_member(namespace, group, value)
+        })
+    }
+
+    fn get_members(&mut self, namespace: &str, group: &str) -> Result<Vec<Vec<u8>>, SdlError> {
+        self.observe(namespace, "get_members", |sdl| {
+            sdl.get_members(namespace, group)
+        })
+    }
+
+    fn del_group(&mut self, namespace: &str, group: &str) -> Result<(), SdlError> {
+        self.observe(namespace, "del_group", |sdl| {
+            sdl.del_group(namespace, group)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use sdl::{DataMap, KeySet, SdlStorageApi};
+
+    use super::{Instrumentation, InstrumentedStorage};
+    use crate::xapp::subscription::registry::SdlHandle;
+    use crate::xapp::test_utils::MemoryStorage;
+
+    #[test]
+    fn test_instrumentation() {
+        let metrics = crate::xapp::metrics::registry_for_ns_app("ricxapp", "test-instrumentation");
+        assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
+        let mut metrics = metrics.unwrap();
+
+        let instrumentation = Instrumentation::register(&mut metrics);
+        assert!(
+            instrumentation.is_ok(),
+            "{}",
+            instrumentation.err().unwrap()
+        );
+        let instrumentation = instrumentation.unwrap();
+        assert!(instrumentation.is_enabled());
+
+        instrumentation.rmr_message_received(12010);
+        instrumentation.rmr_message_handled(12010, Duration::from_millis(2), false);
+        instrumentation.set_receive_queue_depth(3);
+
+        let sdl: SdlHandle = Arc::new(Mutex::new(MemoryStorage::default()));
+        let mut sdl = InstrumentedStorage::new(sdl, instrumentation.clone());
+        let mut data = DataMap::new();
+        let _ = data.insert("key".to_string(), b"value".to_vec());
+        assert!(sdl.set("test-ns", &data).is_ok());
+        assert!(sdl.get("test-ns", &KeySet::new()).is_ok());
+
+        let s = metrics.encode();
+        for expected in [
+            r#"ricxapp_test_instrumentation_rmr_messages_rx_total{message_type="12010"} 1"#,
+            r#"ricxapp_test_instrumentation_rmr_handler_latency_seconds_count{message_type="12010"} 1"#,
+            r#"ricxapp_test_instrumentation_rmr_handler_errors_total{message_type="12010"} 1"#,
+            r#"ricxapp_test_instrumentation_rmr_receive_queue_depth{} 3.0"#,
+            r#"ricxapp_test_instrumentation_sdl_operation_latency_seconds_count{namespace="test-ns",operation="set"} 1"#,
+            r#"ricxapp_test_instrumentation_sdl_operation_latency_seconds_count{namespace="test-ns",operation="get"} 1"#,
+        ] {
+            assert!(s.contains(expected), "{} not found in {}", expected, s);
+        }
+
+        // Not registered twice in a registry.
+        assert!(Instrumentation::register(&mut metrics).is_err());
+    }
+
+    #[test]
+    fn test_instrumentation_disabled() {
+        let instrumentation = Instrumentation::default();
+        assert!(!instrumentation.is_enabled());
+
+        instrumentation.rmr_message_received(12010);
+        instrumentation.set_receive_queue_depth(3);
+    }
+}
@@ -33,36 +33,49 @@
 //! `ricplt_hw_rust_rmr_messages_rx` and `ricplt_hw_rust_rmr_messages_tx`. Optionally other metrics
 //! can be added.
 //!
-//! There are two types of metrics supported currently - Counters and Gauges. Counters are used for
-//! monotonically increasing integer values. Gauges are to be used for values within a given range
-//! (eg. CPU utlization would be a Gauge, interrupts will be a counter.).
+//! These and other metrics for the RMR, SDL and HTTP calls made by the framework are recorded
+//! automatically (see `instrumentation`).
 //!
-//! XApp can register custom Counters and Gauges using `register_counter` and `register_gauge`
-//! methods respectively. XApp can then use the public APIs on the XApp class to increment the
-//! counters or update (increment or decrement the guages). In addition it's possible to `set` the
-//! values of the Counters and Gauges.
+//! Optionally, the metrics can also be reported to a VES Collector (see `ves`).
 //!
-//! All the registered counters and guages along with default `rmr_messages_rx` and
-//! `rmr_messages_tx` counters can be scraped through registry. This is available via
-//! `/ric/v1/metrics` end point of the XApp.
+//! There are three types of metrics supported currently - Counters, Gauges and Histograms.
+//! Counters are used for monotonically increasing integer values. Gauges are to be used for values
+//! within a given range (eg. CPU utlization would be a Gauge, interrupts will be a counter.).
+//! Histograms are used for observing distribution of values (eg. latency of processing a
+//! message) in configurable buckets.
+//!
+//! XApp can register custom metrics using `register_counter`, `register_gauge` and
+//! `register_histogram` methods, along with the names of the labels for the metric. The
+//! registration returns a typed handle (see `handles`) that can be used for updating the metric.
+//! Alternatively, the metrics can be updated using their names through the public APIs on the
+//! XApp class (eg. `increment_counter`). Registering a metric with a name that is already
+//! registered, or updating a metric that is not registered returns an error.
+//!
+//! All the registered metrics along with default `rmr_messages_rx` and `rmr_messages_tx` counters
+//! can be scraped through registry. This is available via `/ric/v1/metrics` end point of the
+//! XApp. The registry is encoded when the end point is scraped, in the OpenMetrics text format if
+//! the scraper accepts it (`Accept: application/openmetrics-text`) or in the Prometheus text
+//! format otherwise.
+
+pub mod handles;
+pub(crate) mod instrumentation;
+pub(crate) mod ves;
 
-use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::text::{encode_eof, encode_registry};
 use prometheus_client::encoding::EncodeLabelSet;
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
-use prometheus_client::metrics::gauge::Gauge;
-use prometheus_client::registry::Registry;
+use prometheus_client::metrics::MetricType;
+use prometheus_client::registry::{Metric, Registry};
 
-use tokio::sync::mpsc::Sender as TokioSyncSender;
-
-use std::collections::HashMap;
-use std::sync::{
-    atomic::{AtomicBool, AtomicU64, Ordering},
-    Arc, Mutex,
-};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
 
 use crate::{XApp, XAppError};
 
+use self::handles::{CounterHandle, GaugeHandle, HistogramBuckets, HistogramHandle, Labels};
+
 // RMR Messages
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 struct RMRMessage {
@@ -71,6 +84,12 @@ struct RMRMessage {
 
 impl XApp {
     /// Increment the internal RMR Received Messages Counter for a given message type.
+    ///
+    /// The received messages are counted by the framework, this is kept for compatibility.
+    #[deprecated(
+        since = "0.3.0-dev",
+        note = "received messages are counted by the framework."
+    )]
     pub fn increment_rmr_rx_messages(&self, message_type: i32) {
         if let Some(ref metrics) = self.metrics {
             let metrics = metrics.lock().unwrap();
@@ -79,6 +98,9 @@ impl XApp {
     }
 
     /// Increment the internal RMR Sent Messages Counter for a given message type.
+    ///
+    /// Messages sent using the `RMRSender` are counted by the framework. This is required only for
+    /// the messages sent directly using the `RMRClient`.
     pub fn increment_rmr_tx_messages(&self, message_type: i32) {
         if let Some(ref metrics) = self.metrics {
             let metrics = metrics.lock().unwrap();
@@ -86,150 +108,328 @@ impl XApp {
         }
     }
 
-    /// Increment the counter that was dynamically registered with `register_counter`
-    pub fn increment_counter<S: Into<String>>(&self, counter_name: &str, labels: (S, S)) {
-        if let Some(ref metrics) = self.metrics {
-            let metrics = metrics.lock().unwrap();
-            if let Some(counter_family) = metrics.counters.get(counter_name) {
-                counter_family
-                    .get_or_create(&vec![(labels.0.into(), labels.1.into())])
-                    .inc();
-            }
-        }
+    /// Increment the Counter registered with `register_counter` by one
+    ///
+    /// `labels` are the `(name, value)` pairs for all the labels of the Counter.
+    pub fn increment_counter(
+        &self,
+        counter_name: &str,
+        labels: &[(&str, &str)],
+    ) -> Result<(), XAppError> {
+        let metrics = self.metrics_registry()?.lock().unwrap();
+        let counter = metrics.counter(counter_name)?;
+        let labels = counter.labels.label_set_from_pairs(labels)?;
+        let _ = counter.family.get_or_create(&labels).inc();
+        Ok(())
     }
 
     /// Increment a Gauge value by given value.
-    pub fn increment_gauge<S: Into<String>>(&self, gauge_name: &str, labels: (S, S), value: f64) {
-        if let Some(ref metrics) = self.metrics {
-            let metrics = metrics.lock().unwrap();
-            if let Some(gauge_family) = metrics.gauges.get(gauge_name) {
-                gauge_family
-                    .get_or_create(&vec![(labels.0.into(), labels.1.into())])
-                    .inc_by(value);
-            }
-        }
+    pub fn increment_gauge(
+        &self,
+        gauge_name: &str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) -> Result<(), XAppError> {
+        let metrics = self.metrics_registry()?.lock().unwrap();
+        let gauge = metrics.gauge(gauge_name)?;
+        let labels = gauge.labels.label_set_from_pairs(labels)?;
+        let _ = gauge.family.get_or_create(&labels).inc_by(value);
+        Ok(())
     }
 
     /// Decrement a Gauge value by given value.
-    pub fn decrement_gauge<S: Into<String>>(&self, gauge_name: &str, labels: (S, S), value: f64) {
-        if let Some(ref metrics) = self.metrics {
-            let metrics = metrics.lock().unwrap();
-            if let Some(gauge_family) = metrics.gauges.get(gauge_name) {
-                gauge_family
-                    .get_or_create(&vec![(labels.0.into(), labels.1.into())])
-                    .dec_by(value);
-            }
-        }
+    pub fn decrement_gauge(
+        &self,
+        gauge_name: &str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) -> Result<(), XAppError> {
+        let metrics = self.metrics_registry()?.lock().unwrap();
+        let gauge = metrics.gauge(gauge_name)?;
+        let labels = gauge.labels.label_set_from_pairs(labels)?;
+        let _ = gauge.family.get_or_create(&labels).dec_by(value);
+        Ok(())
     }
 
     /// Sets the value of the Gauge to the given value.
-    pub fn set_gauge<S: Into<String>>(&self, gauge_name: &str, labels: (S, S), value: f64) {
-        if let Some(ref metrics) = self.metrics {
-            let metrics = metrics.lock().unwrap();
-            if let Some(gauge_family) = metrics.gauges.get(gauge_name) {
-                gauge_family
-                    .get_or_create(&vec![(labels.0.into(), labels.1.into())])
-                    .set(value);
-            }
-        }
+    pub fn set_gauge(
+        &self,
+        gauge_name: &str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) -> Result<(), XAppError> {
+        let metrics = self.metrics_registry()?.lock().unwrap();
+        let gauge = metrics.gauge(gauge_name)?;
+        let labels = gauge.labels.label_set_from_pairs(labels)?;
+        let _ = gauge.family.get_or_create(&labels).set(value);
+        Ok(())
     }
 
-    /// Register a Counter with a given name and help message
-    pub fn register_counter(&mut self, counter_name: &str, counter_help: &str) {
-        if let Some(ref mut metrics) = self.metrics {
-            let mut metrics = metrics.lock().unwrap();
-            metrics.register_counter(counter_name, counter_help)
-        }
+    /// Observe the value in the Histogram registered with `register_histogram`
+    pub fn observe_histogram(
+        &self,
+        histogram_name: &str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) -> Result<(), XAppError> {
+        let metrics = self.metrics_registry()?.lock().unwrap();
+        let histogram = metrics.histogram(histogram_name)?;
+        let labels = histogram.labels.label_set_from_pairs(labels)?;
+        histogram.family.get_or_create(&labels).observe(value);
+        Ok(())
     }
 
-    /// Register a Gauge with a given name and help message
+    /// Register a Counter with a given name, help message and label names
+    pub fn register_counter(
+        &mut self,
+        counter_name: &str,
+        counter_help: &str,
+        label_names: &[&str],
+    ) -> Result<CounterHandle, XAppError> {
+        let mut metrics = self.metrics_registry()?.lock().unwrap();
+        metrics.register_counter(counter_name, counter_help, label_names)
+    }
+
+    /// Register a Gauge with a given name, help message and label names
     ///
     /// Currently float gauges are supported.
-    pub fn register_gauge(&mut self, counter_name: &str, counter_help: &str) {
-        if let Some(ref mut metrics) = self.metrics {
-            let mut metrics = metrics.lock().unwrap();
-            metrics.register_gauge(counter_name, counter_help)
-        }
+    pub fn register_gauge(
+        &mut self,
+        gauge_name: &str,
+        gauge_help: &str,
+        label_names: &[&str],
+    ) -> Result<GaugeHandle, XAppError> {
+        let mut metrics = self.metrics_registry()?.lock().unwrap();
+        metrics.register_gauge(gauge_name, gauge_help, label_names)
+    }
+
+    /// Register a Histogram with a given name, help message, label names and buckets
+    ///
+    /// `buckets` are the upper bounds of the buckets in increasing order.
+    pub fn register_histogram(
+        &mut self,
+        histogram_name: &str,
+        histogram_help: &str,
+        label_names: &[&str],
+        buckets: &[f64],
+    ) -> Result<HistogramHandle, XAppError> {
+        let mut metrics = self.metrics_registry()?.lock().unwrap();
+        metrics.register_histogram(histogram_name, histogram_help, label_names, buckets)
+    }
+
+    fn metrics_registry(&self) -> Result<&Arc<Mutex<MetricsRegistry>>, XAppError> {
+        self.metrics
+            .as_ref()
+            .ok_or_else(|| XAppError("Metrics are not enabled for the XApp.".to_string()))
     }
 }
 
 pub(crate) struct MetricsRegistry {
-    registry: Registry,
+    prefix: String,
+    metrics: Vec<RegisteredMetric>,
     rmr_messages_rx: Family<RMRMessage, Counter>,
     rmr_messages_tx: Family<RMRMessage, Counter>,
-    counters: HashMap<String, Family<Vec<(String, String)>, Counter>>,
-    gauges: HashMap<String, Family<Vec<(String, String)>, Gauge<f64, AtomicU64>>>,
+    names: HashSet<String>,
+    counters: HashMap<String, CounterHandle>,
+    gauges: HashMap<String, GaugeHandle>,
+    histograms: HashMap<String, HistogramHandle>,
 }
 
-pub(crate) fn run_metrics_server(
-    metrics: Arc<Mutex<MetricsRegistry>>,
-    data_tx: TokioSyncSender<String>,
-    app_is_running: Arc<AtomicBool>,
-) -> Result<(), XAppError> {
-    loop {
-        let metrics = metrics.lock().unwrap();
-        let metrics_data = metrics.encode();
-        drop(metrics);
-
-        let _ = data_tx.blocking_send(format!("metrics: {metrics_data}"));
-        std::thread::sleep(std::time::Duration::from_secs(1));
-        if !app_is_running.load(Ordering::SeqCst) {
-            break;
+// Each metric is kept in a registry of its own, so that the metric is encoded (in the OpenMetrics
+// text format) separately from the others for the Prometheus text format and the samples.
+struct RegisteredMetric {
+    name: String,
+    metric_type: MetricType,
+    registry: Registry,
+}
+
+impl RegisteredMetric {
+    // Encode the metric, returns the help text and the samples of the metric. The descriptor of
+    // the metric is the `HELP` line followed by the `TYPE` line. If the metric is not encoded with
+    // this descriptor, there's no help text and the encoding is returned as is.
+    fn encode(&self) -> (Option<String>, String) {
+        let mut encoded = String::new();
+        encode_registry(&mut encoded, &self.registry).unwrap();
+
+        let help_line = format!("# HELP {} ", self.name);
+        let type_line = format!("\n# TYPE {} {}\n", self.name, self.metric_type.as_str());
+        match encoded
+            .strip_prefix(&help_line)
+            .and_then(|rest| rest.split_once(&type_line))
+        {
+            Some((help, samples)) => (Some(help.to_string()), samples.to_string()),
+            None => (None, encoded),
         }
     }
-    Ok(())
 }
 
+/// A sample of a registered metric, as in the OpenMetrics text format.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Sample {
+    pub(crate) name: String,
+    pub(crate) labels: Vec<(String, String)>,
+    pub(crate) value: String,
+}
+
+/// Content Type of the metrics encoded in the OpenMetrics text format.
+pub(crate) const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Content Type of the metrics encoded in the Prometheus text format.
+pub(crate) const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
 pub(crate) fn registry_for_ns_app(ns: &str, app_name: &str) -> Result<MetricsRegistry, XAppError> {
     let app_name = app_name.replace('-', "_");
     let prefix = format!("{ns}_{app_name}");
-    let mut registry = Registry::with_prefix(prefix);
 
     let rmr_messages_rx = Family::<RMRMessage, Counter>::default();
-    registry.register(
+    let rmr_messages_tx = Family::<RMRMessage, Counter>::default();
+    let mut registry = MetricsRegistry {
+        prefix,
+        metrics: vec![],
+        rmr_messages_rx: rmr_messages_rx.clone(),
+        rmr_messages_tx: rmr_messages_tx.clone(),
+        names: HashSet::new(),
+        counters: HashMap::new(),
+        gauges: HashMap::new(),
+        histograms: HashMap::new(),
+    };
+
+    registry.register_metric(
         // With the metric name.
         "rmr_messages_rx",
         // And the metric help text.
         "Number of RMR messages received",
-        rmr_messages_rx.clone(),
-    );
-
-    let rmr_messages_tx = Family::<RMRMessage, Counter>::default();
-    registry.register(
+        rmr_messages_rx,
+    )?;
+    registry.register_metric(
         // With the metric name.
         "rmr_messages_tx",
         // And the metric help text.
         "Number of RMR messages transmitted",
-        rmr_messages_tx.clone(),
-    );
-
-    Ok(MetricsRegistry {
-        registry,
-        rmr_messages_rx,
         rmr_messages_tx,
-        counters: HashMap::new(),
-        gauges: HashMap::new(),
-    })
+    )?;
+
+    Ok(registry)
 }
 
 impl MetricsRegistry {
-    pub(crate) fn register_counter(&mut self, counter_name: &str, counter_help: &str) {
-        let counter_family = Family::<Vec<(String, String)>, Counter>::default();
+    pub(crate) fn register_counter(
+        &mut self,
+        counter_name: &str,
+        counter_help: &str,
+        label_names: &[&str],
+    ) -> Result<CounterHandle, XAppError> {
+        let labels = Labels::new(counter_name, label_names)?;
+        self.reserve_name(counter_name)?;
+
+        let counter = CounterHandle {
+            family: Family::default(),
+            labels,
+        };
+        self.register(counter_name, counter_help, counter.family.clone());
+        let _ = self
+            .counters
+            .insert(counter_name.to_owned(), counter.clone());
+
+        Ok(counter)
+    }
 
-        self.registry
-            .register(counter_name, counter_help, counter_family.clone());
+    pub(crate) fn register_gauge(
+        &mut self,
+        gauge_name: &str,
+        gauge_help: &str,
+        label_names: &[&str],
+    ) -> Result<GaugeHandle, XAppError> {
+        let labels = Labels::new(gauge_name, label_names)?;
+        self.reserve_name(gauge_name)?;
+
+        let gauge = GaugeHandle {
+            family: Family::default(),
+            labels,
+        };
+        self.register(gauge_name, gauge_help, gauge.family.clone());
+        let _ = self.gauges.insert(gauge_name.to_owned(), gauge.clone());
+
+        Ok(gauge)
+    }
+
+    pub(crate) fn register_histogram(
+        &mut self,
+        histogram_name: &str,
+        histogram_help: &str,
+        label_names: &[&str],
+        buckets: &[f64],
+    ) -> Result<HistogramHandle, XAppError> {
+        let labels = Labels::new(histogram_name, label_names)?;
+        let buckets = HistogramBuckets::new(histogram_name, buckets)?;
+        self.reserve_name(histogram_name)?;
+
+        let histogram = HistogramHandle {
+            family: Family::new_with_constructor(buckets),
+            labels,
+        };
+        self.register(histogram_name, histogram_help, histogram.family.clone());
+        let _ = self
+            .histograms
+            .insert(histogram_name.to_owned(), histogram.clone());
+
+        Ok(histogram)
+    }
+
+    /// Register a metric maintained by the framework itself.
+    pub(crate) fn register_metric(
+        &mut self,
+        name: &str,
+        help: &str,
+        metric: impl Metric,
+    ) -> Result<(), XAppError> {
+        self.reserve_name(name)?;
+        self.register(name, help, metric);
+        Ok(())
+    }
 
+    fn register(&mut self, name: &str, help: &str, metric: impl Metric) {
+        let metric_type = metric.metric_type();
+        let mut registry = Registry::with_prefix(self.prefix.clone());
+        registry.register(name, help, metric);
+        self.metrics.push(RegisteredMetric {
+            name: format!("{}_{}", self.prefix, name),
+            metric_type,
+            registry,
+        });
+    }
+
+    pub(crate) fn counter(&self, counter_name: &str) -> Result<&CounterHandle, XAppError> {
         self.counters
-            .insert(counter_name.to_owned(), counter_family);
+            .get(counter_name)
+            .ok_or_else(|| XAppError(format!("Counter '{}' is not registered.", counter_name)))
     }
 
-    pub(crate) fn register_gauge(&mut self, gauge_name: &str, gauge_help: &str) {
-        let gauge_family = Family::<Vec<(String, String)>, Gauge<f64, AtomicU64>>::default();
+    pub(crate) fn gauge(&self, gauge_name: &str) -> Result<&GaugeHandle, XAppError> {
+        self.gauges
+            .get(gauge_name)
+            .ok_or_else(|| XAppError(format!("Gauge '{}' is not registered.", gauge_name)))
+    }
 
-        self.registry
-            .register(gauge_name, gauge_help, gauge_family.clone());
+    pub(crate) fn histogram(&self, histogram_name: &str) -> Result<&HistogramHandle, XAppError> {
+        self.histograms
+            .get(histogram_name)
+            .ok_or_else(|| XAppError(format!("Histogram '{}' is not registered.", histogram_name)))
+    }
 
-        self.gauges.insert(gauge_name.to_owned(), gauge_family);
+    // Names are unique across all the types of metrics.
+    fn reserve_name(&mut self, name: &str) -> Result<(), XAppError> {
+        if !is_valid_name(name) {
+            return Err(XAppError(format!("Invalid metric name '{}'.", name)));
+        }
+        if !self.names.insert(name.to_string()) {
+            return Err(XAppError(format!(
+                "Metric '{}' is already registered.",
+                name
+            )));
+        }
+        Ok(())
     }
 
     pub(crate) fn increment_rmr_rx_messages(&self, message_type: i32) {
@@ -246,9 +446,127 @@ impl MetricsRegistry {
 
     pub(crate) fn encode(&self) -> String {
         let mut buffer = String::new();
-        encode(&mut buffer, &self.registry).unwrap();
+        for metric in &self.metrics {
+            encode_registry(&mut buffer, &metric.registry).unwrap();
+        }
+        encode_eof(&mut buffer).unwrap();
         buffer
     }
+
+    /// Encode the registry in the format negotiated using the value of the `Accept` header.
+    ///
+    /// Returns the Content Type of the encoded metrics along with the encoded metrics.
+    pub(crate) fn encode_for_accept(&self, accept: Option<&str>) -> (&'static str, String) {
+        let openmetrics = accept
+            .map(|accept| accept.contains("application/openmetrics-text"))
+            .unwrap_or_default();
+        if openmetrics {
+            (OPENMETRICS_CONTENT_TYPE, self.encode())
+        } else {
+            (PROMETHEUS_CONTENT_TYPE, self.encode_prometheus_text())
+        }
+    }
+
+    // The Prometheus text format differs from the OpenMetrics text format in the descriptors of
+    // the metrics: the name of the counters in `HELP` and `TYPE` lines includes the `_total`
+    // suffix and `unknown` metrics are `untyped`. The samples are same and there's no `# EOF`.
+    fn encode_prometheus_text(&self) -> String {
+        let mut buffer = String::new();
+
+        for metric in &self.metrics {
+            let (help, samples) = metric.encode();
+            // Without the descriptor, the metric is left in the OpenMetrics text format.
+            if let Some(help) = help {
+                let (name, metric_type) = match metric.metric_type {
+                    MetricType::Counter => (format!("{}_total", metric.name), "counter"),
+                    MetricType::Unknown => (metric.name.clone(), "untyped"),
+                    _ => (metric.name.clone(), metric.metric_type.as_str()),
+                };
+                let _ = writeln!(buffer, "# HELP {} {}", name, help);
+                let _ = writeln!(buffer, "# TYPE {} {}", name, metric_type);
+            }
+            buffer.push_str(&samples);
+        }
+
+        buffer
+    }
+
+    /// Samples of all the registered metrics.
+    ///
+    /// `prometheus_client` does not provide a way to read the metrics other than encoding them,
+    /// so the samples are read from the encoding of each of the metrics.
+    pub(crate) fn samples(&self) -> Vec<Sample> {
+        self.metrics
+            .iter()
+            .flat_map(|metric| {
+                let (_, samples) = metric.encode();
+                samples
+                    .lines()
+                    .filter(|line| !line.starts_with('#'))
+                    .filter_map(parse_sample)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+// Parse a sample line of a metric encoded in the OpenMetrics text format.
+fn parse_sample(line: &str) -> Option<Sample> {
+    let name_end = line.find(['{', ' '])?;
+    let name = &line[..name_end];
+
+    let mut labels = vec![];
+    let mut rest = &line[name_end..];
+    if let Some(label_str) = rest.strip_prefix('{') {
+        let mut chars = label_str.char_indices();
+        let mut label_name = String::new();
+        let end = loop {
+            let (idx, c) = chars.next()?;
+            match c {
+                '}' => break idx,
+                ',' => continue,
+                '=' => {
+                    if chars.next()?.1 != '"' {
+                        return None;
+                    }
+                    let mut value = String::new();
+                    loop {
+                        match chars.next()?.1 {
+                            '"' => break,
+                            '\\' => match chars.next()?.1 {
+                                'n' => value.push('\n'),
+                                c => value.push(c),
+                            },
+                            c => value.push(c),
+                        }
+                    }
+                    labels.push((std::mem::take(&mut label_name), value));
+                }
+                c => label_name.push(c),
+            }
+        };
+        rest = &label_str[end + 1..];
+    }
+
+    // Value may be followed by a timestamp or an exemplar.
+    let value = rest.split_whitespace().next()?;
+
+    Some(Sample {
+        name: name.to_string(),
+        labels,
+        value: value.to_string(),
+    })
+}
+
+// Metric and Label names as per the Prometheus data model.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
 }
 
 #[cfg(test)]
@@ -265,9 +583,25 @@ mod tests {
 
         assert!(xapp.metrics.is_some());
 
-        xapp.register_counter("xapp-test-counter", "Test counter for XApp API");
-
-        // TODO : When full integration tests are supported we should be able to get the metrics.
+        let result = xapp.register_counter(
+            "xapp_test_counter",
+            "Test counter for XApp API",
+            &["method"],
+        );
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let counter = result.unwrap();
+
+        let result = xapp.increment_counter("xapp_test_counter", &[("method", "GET")]);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert_eq!(counter.get(&["GET"]).unwrap(), 1);
+
+        assert!(xapp
+            .increment_counter("xapp_test_counter", &[("code", "200")])
+            .is_err());
+        assert!(xapp
+            .increment_counter("xapp_unknown_counter", &[("method", "GET")])
+            .is_err());
+        assert!(xapp.set_gauge("xapp_test_counter", &[], 1.0).is_err());
     }
 
     #[test]
@@ -276,15 +610,35 @@ mod tests {
         assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
         let mut metrics = metrics.unwrap();
 
-        metrics.register_counter("test_counter", "This is a Test Counter");
+        let result =
+            metrics.register_counter("test_counter", "This is a Test Counter", &["method"]);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let counter = result.unwrap();
 
         assert!(metrics.counters.len() == 1);
-        assert!(metrics.counters.get("test_counter").is_some());
-
-        let labels = vec![("method".to_owned(), "GET".to_owned())];
-        let tc_family = metrics.counters.get("test_counter").unwrap();
-        let value = tc_family.get_or_create(&labels);
-        assert!(value.get() == 0, "{:}", value.get());
+        assert!(metrics.counters.contains_key("test_counter"));
+
+        assert!(counter.get(&["GET"]).unwrap() == 0);
+        assert!(counter.inc_by(&["GET"], 2).is_ok());
+        assert!(counter.get(&["GET"]).unwrap() == 2);
+        assert!(counter.inc(&["GET", "200"]).is_err());
+
+        // Duplicate and Invalid names.
+        assert!(metrics
+            .register_counter("test_counter", "Duplicate Counter", &[])
+            .is_err());
+        assert!(metrics
+            .register_gauge("test_counter", "Duplicate Gauge", &[])
+            .is_err());
+        assert!(metrics
+            .register_counter("rmr_messages_rx", "Duplicate Counter", &[])
+            .is_err());
+        assert!(metrics
+            .register_counter("test-counter", "Invalid Counter", &[])
+            .is_err());
+        assert!(metrics
+            .register_counter("test_counter_2", "Invalid Labels", &["code", "code"])
+            .is_err());
     }
 
     #[test]
@@ -293,14 +647,61 @@ mod tests {
         assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
         let mut metrics = metrics.unwrap();
 
-        metrics.register_gauge("test_gauge", "This is a Test Gauge");
+        let result = metrics.register_gauge("test_gauge", "This is a Test Gauge", &["snr", "dir"]);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let gauge = result.unwrap();
 
         assert!(metrics.gauges.len() == 1);
-        assert!(metrics.gauges.get("test_gauge").is_some());
-        let labels = vec![("snr".to_owned(), "upstream".to_owned())];
-        let tc_family = metrics.gauges.get("test_gauge").unwrap();
-        let value = tc_family.get_or_create(&labels);
-        assert!(value.get() == 0.0, "{:}", value.get());
+        assert!(metrics.gauges.contains_key("test_gauge"));
+        assert!(gauge.get(&["upstream", "rx"]).unwrap() == 0.0);
+
+        assert!(gauge.set(&["upstream", "rx"], 5.0).is_ok());
+        assert!(gauge.dec_by(&["upstream", "rx"], 1.5).is_ok());
+        let value = gauge.get(&["upstream", "rx"]).unwrap();
+        assert!(value == 3.5, "{:}", value);
+
+        // Label pairs are matched by name.
+        let labels = metrics
+            .gauge("test_gauge")
+            .unwrap()
+            .labels
+            .label_set_from_pairs(&[("dir", "rx"), ("snr", "upstream")]);
+        assert!(labels.is_ok(), "{}", labels.err().unwrap());
+    }
+
+    #[test]
+    fn test_metrics_register_histogram() {
+        let metrics = super::registry_for_ns_app("ricxapp", "test-histograms-app");
+        assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
+        let mut metrics = metrics.unwrap();
+
+        assert!(metrics
+            .register_histogram("test_histogram", "No buckets", &[], &[])
+            .is_err());
+        assert!(metrics
+            .register_histogram("test_histogram", "Unordered buckets", &[], &[1.0, 0.5])
+            .is_err());
+
+        let result = metrics.register_histogram(
+            "test_histogram",
+            "This is a Test Histogram",
+            &["mtype"],
+            &[0.1, 1.0],
+        );
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let histogram = result.unwrap();
+
+        assert!(histogram.observe(&["12010"], 0.5).is_ok());
+        assert!(histogram.observe(&[], 0.5).is_err());
+
+        let s = metrics.encode();
+        assert!(
+            s.contains(
+                r#"ricxapp_test_histograms_app_test_histogram_bucket{le="1.0",mtype="12010"} 1"#
+            ),
+            "{}",
+            s
+        );
     }
 
     #[test]
@@ -332,7 +733,9 @@ mod tests {
         metrics.increment_rmr_tx_messages(1);
         metrics.increment_rmr_rx_messages(1);
 
-        metrics.register_counter("test_encode", "Counter for testing encode function");
+        let result =
+            metrics.register_counter("test_encode", "Counter for testing encode function", &[]);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
 
         let s = metrics.encode();
 
@@ -349,4 +752,134 @@ ricxapp_test_rmr_tx_rx_app_rmr_messages_tx_total{message_type="1"} 1
 
         assert_eq!(s, expected.to_owned(), "{:#?}", s);
     }
+
+    #[test]
+    fn test_encode_for_accept() {
+        let metrics = super::registry_for_ns_app("ricxapp", "test-accept-app");
+        assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
+        let metrics = metrics.unwrap();
+
+        metrics.increment_rmr_rx_messages(1);
+
+        let (content_type, s) = metrics.encode_for_accept(Some(
+            "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5",
+        ));
+        assert_eq!(content_type, super::OPENMETRICS_CONTENT_TYPE);
+        assert!(s.ends_with("# EOF\n"), "{}", s);
+
+        let (content_type, s) = metrics.encode_for_accept(None);
+        assert_eq!(content_type, super::PROMETHEUS_CONTENT_TYPE);
+
+        let expected = r#"# HELP ricxapp_test_accept_app_rmr_messages_rx_total Number of RMR messages received.
+# TYPE ricxapp_test_accept_app_rmr_messages_rx_total counter
+ricxapp_test_accept_app_rmr_messages_rx_total{message_type="1"} 1
+# HELP ricxapp_test_accept_app_rmr_messages_tx_total Number of RMR messages transmitted.
+# TYPE ricxapp_test_accept_app_rmr_messages_tx_total counter
+"#;
+        assert_eq!(s, expected.to_owned(), "{:#?}", s);
+    }
+
+    #[test]
+    fn test_encode_prometheus_text() {
+        let metrics = super::registry_for_ns_app("ricxapp", "test-prometheus-app");
+        assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
+        let mut metrics = metrics.unwrap();
+
+        let counter = metrics
+            .register_counter("requests", "Requests", &["path"])
+            .unwrap();
+        let gauge = metrics.register_gauge("queued", "Queued", &[]).unwrap();
+
+        // The label values are escaped and are not rewritten, even if they look like the
+        // descriptors.
+        assert!(counter.inc(&["/requests_total"]).is_ok());
+        assert!(counter
+            .inc(&["x\n# TYPE ricxapp_test_prometheus_app_queued counter"])
+            .is_ok());
+        assert!(gauge.set(&[], 2.0).is_ok());
+
+        let (content_type, s) = metrics.encode_for_accept(Some("text/plain"));
+        assert_eq!(content_type, super::PROMETHEUS_CONTENT_TYPE);
+
+        assert!(s.contains(
+            "# HELP ricxapp_test_prometheus_app_requests_total Requests.\n\
+             # TYPE ricxapp_test_prometheus_app_requests_total counter\n"
+        ));
+        assert!(
+            s.contains("ricxapp_test_prometheus_app_requests_total{path=\"/requests_total\"} 1\n"),
+            "{}",
+            s
+        );
+        assert!(s.contains("{path=\"x\\n# TYPE ricxapp_test_prometheus_app_queued counter\"} 1\n"));
+        assert!(
+            s.ends_with(
+                "# HELP ricxapp_test_prometheus_app_queued Queued.\n\
+             # TYPE ricxapp_test_prometheus_app_queued gauge\n\
+             ricxapp_test_prometheus_app_queued{} 2.0\n"
+            ),
+            "{}",
+            s
+        );
+        assert!(!s.contains("# EOF"));
+    }
+
+    #[test]
+    fn test_encode_without_descriptor() {
+        let metrics = super::registry_for_ns_app("ricxapp", "test-descriptor-app");
+        assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
+        let mut metrics = metrics.unwrap();
+        metrics.increment_rmr_rx_messages(1);
+
+        // The descriptor is not found when the name of the metric differs from the registered one.
+        metrics.metrics[0].name = "ricxapp_test_descriptor_app_other".to_string();
+        let (help, samples) = metrics.metrics[0].encode();
+        assert!(help.is_none());
+        assert!(samples.starts_with("# HELP ricxapp_test_descriptor_app_rmr_messages_rx "));
+
+        let (_, s) = metrics.encode_for_accept(None);
+        assert!(s.starts_with(&samples), "{}", s);
+        assert_eq!(
+            metrics.samples()[0].name,
+            "ricxapp_test_descriptor_app_rmr_messages_rx_total"
+        );
+    }
+
+    #[test]
+    fn test_samples() {
+        let metrics = super::registry_for_ns_app("ricxapp", "test-samples-app");
+        assert!(metrics.is_ok(), "{:?}", metrics.err().unwrap());
+        let mut metrics = metrics.unwrap();
+
+        metrics.increment_rmr_rx_messages(1);
+        let counter = metrics
+            .register_counter("requests", "Requests", &["method", "path"])
+            .unwrap();
+        assert!(counter.inc_by(&["GET", "/a \"b\"\n"], 3).is_ok());
+        let gauge = metrics.register_gauge("queued", "Queued", &[]).unwrap();
+        assert!(gauge.set(&[], 2.0).is_ok());
+
+        assert_eq!(
+            metrics.samples(),
+            vec![
+                super::Sample {
+                    name: "ricxapp_test_samples_app_rmr_messages_rx_total".to_string(),
+                    labels: vec![("message_type".to_string(), "1".to_string())],
+                    value: "1".to_string(),
+                },
+                super::Sample {
+                    name: "ricxapp_test_samples_app_requests_total".to_string(),
+                    labels: vec![
+                        ("method".to_string(), "GET".to_string()),
+                        ("path".to_string(), "/a \"b\"\n".to_string())
+                    ],
+                    value: "3".to_string(),
+                },
+                super::Sample {
+                    name: "ricxapp_test_samples_app_queued".to_string(),
+                    labels: vec![],
+                    value: "2.0".to_string(),
+                }
+            ]
+        );
+    }
 }
@@ -0,0 +1,440 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Reporting of the XApp metrics as VES Measurement Events
+//!
+//! When enabled using `XApp::enable_ves_reporting`, the contents of the `MetricsRegistry` are
+//! collected every `interval` and are converted to VES `measurement` domain events. Every sample
+//! of a metric is reported as an entry in the `additionalMeasurements` of the event, with the
+//! value of the sample (`value`) and the labels of the sample (`label.<name>`) as the `hashMap`.
+//!
+//! The events are POSTed in batches to the `eventBatch` resource of the VES Event Listener of the
+//! collector. When the collector is not available, the batch is retried with a backoff. Events
+//! waiting to be sent are bounded by `max_pending_events`, the oldest events are dropped first.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::blocking::Client as ReqwestClient;
+
+use serde_json::{json, Value};
+
+use crate::xapp::alarms::transport::SendError;
+use crate::xapp::backoff::Backoff;
+use crate::xapp::health::Heartbeat;
+use crate::{XApp, XAppError};
+
+use super::{MetricsRegistry, Sample};
+
+const EVENT_BATCH_PATH: &str = "eventBatch";
+const VES_EVENT_LISTENER_VERSION: &str = "7.2.1";
+const COMMON_EVENT_HEADER_VERSION: &str = "4.1";
+const MEASUREMENT_FIELDS_VERSION: &str = "4.0";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Configuration of the VES reporting of the XApp metrics
+#[derive(Clone, Debug, PartialEq)]
+pub struct VesConfig {
+    /// URL of the VES Event Listener (eg. `http://ves-collector:8080/eventListener/v7`).
+    pub collector_url: String,
+
+    /// Interval at which the metrics are reported.
+    pub interval: Duration,
+
+    /// Maximum number of measurements in a single VES Event.
+    pub max_measurements_per_event: usize,
+
+    /// Maximum number of VES Events sent in a single batch.
+    pub max_events_per_batch: usize,
+
+    /// Maximum number of VES Events waiting to be sent.
+    pub max_pending_events: usize,
+
+    /// Number of retries for sending a batch, after which the batch is dropped.
+    pub max_retries: u32,
+
+    /// User name and password for the Basic Authentication with the collector.
+    pub basic_auth: Option<(String, String)>,
+}
+
+impl VesConfig {
+    /// VES Configuration with defaults for the collector with given URL
+    pub fn new(collector_url: &str) -> Self {
+        Self {
+            collector_url: collector_url.trim_end_matches('/').to_string(),
+            interval: Duration::from_secs(60),
+            max_measurements_per_event: 100,
+            max_events_per_batch: 10,
+            max_pending_events: 100,
+            max_retries: 5,
+            basic_auth: None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), XAppError> {
+        let url = reqwest::Url::parse(&self.collector_url)
+            .map_err(|e| XAppError(format!("Invalid VES Collector URL: {}", e)))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(XAppError(format!(
+                "Invalid VES Collector URL: Unsupported scheme '{}'.",
+                url.scheme()
+            )));
+        }
+        if self.interval.is_zero()
+            || self.max_measurements_per_event == 0
+            || self.max_events_per_batch == 0
+            || self.max_pending_events == 0
+        {
+            return Err(XAppError(
+                "VES interval, measurements per event and the number of events should be non-zero."
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl XApp {
+    /// Report the metrics of the XApp to a VES Collector
+    ///
+    /// Metrics should be enabled for the XApp. This should be called before `start`.
+    pub fn enable_ves_reporting(&mut self, config: VesConfig) -> Result<(), XAppError> {
+        let _ = self.metrics_registry()?;
+        config.validate()?;
+
+        let _ = self.ves_config.replace(config);
+        Ok(())
+    }
+
+    // Starts the VES Exporter thread, if the VES reporting is enabled.
+    pub(crate) fn start_ves_exporter(&mut self) -> Result<(), XAppError> {
+        let config = match self.ves_config {
+            Some(ref config) => config.clone(),
+            None => return Ok(()),
+        };
+        let registry = Arc::clone(self.metrics_registry()?);
+
+        let xapp_name = self.config.current().metadata.xapp_name.clone();
+        let source_name = self
+            .app_instance_name
+            .clone()
+            .unwrap_or_else(|| xapp_name.clone());
+        let mut exporter = VesExporter::new(
+            config,
+            registry,
+            &xapp_name,
+            &source_name,
+            Arc::clone(&self.app_is_running),
+        )?;
+
+        exporter.heartbeat = self.heartbeat("ves_exporter");
+        let ves_exporter_thread = std::thread::spawn(move || exporter.run());
+        let _ = self.ves_exporter_thread.replace(ves_exporter_thread);
+
+        Ok(())
+    }
+}
+
+// VES Exporter: Runs in it's own thread, collects the metrics every interval and sends them to the
+// VES Collector.
+pub(crate) struct VesExporter {
+    config: VesConfig,
+    registry: Arc<Mutex<MetricsRegistry>>,
+    reporting_entity_name: String,
+    source_name: String,
+    http_client: ReqwestClient,
+    app_is_running: Arc<AtomicBool>,
+    pub(crate) heartbeat: Heartbeat,
+    sequence: u64,
+    last_collected: u64,
+    pending: VecDeque<Value>,
+}
+
+impl VesExporter {
+    pub(crate) fn new(
+        config: VesConfig,
+        registry: Arc<Mutex<MetricsRegistry>>,
+        reporting_entity_name: &str,
+        source_name: &str,
+        app_is_running: Arc<AtomicBool>,
+    ) -> Result<Self, XAppError> {
+        let http_client = ReqwestClient::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|e| XAppError(format!("Error creating HTTP client: {}", e)))?;
+
+        Ok(Self {
+            config,
+            registry,
+            reporting_entity_name: reporting_entity_name.to_string(),
+            source_name: source_name.to_string(),
+            http_client,
+            app_is_running,
+            heartbeat: Heartbeat::default(),
+            sequence: 0,
+            last_collected: epoch_micros(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    pub(crate) fn run(mut self) {
+        log::info!("Starting VES exporter thread!");
+
+        let mut backoff = Backoff::new(RETRY_INITIAL_DELAY, RETRY_MAX_DELAY);
+        let mut next_collection = Instant::now() + self.config.interval;
+        let mut next_attempt = Instant::now();
+        let mut retries = 0;
+
+        while self.app_is_running.load(Ordering::Relaxed) {
+            self.heartbeat.beat();
+            if Instant::now() >= next_collection {
+                self.collect();
+                // Collections missed (eg. while sending) are skipped, instead of collecting again
+                // immediately.
+                next_collection += self.config.interval;
+                if next_collection <= Instant::now() {
+                    next_collection = Instant::now() + self.config.interval;
+                }
+            }
+
+            if self.pending.is_empty() || Instant::now() < next_attempt {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+
+            let count = std::cmp::min(self.pending.len(), self.config.max_events_per_batch);
+            let batch = self.pending.iter().take(count).cloned().collect::<Vec<_>>();
+            match self.send(batch) {
+                Ok(()) => {
+                    let _ = self.pending.drain(..count);
+                    backoff.reset();
+                    retries = 0;
+                }
+                Err(SendError::Rejected(e)) => {
+                    log::error!(
+                        "VES Events rejected by the collector: {}, dropping them.",
+                        e
+                    );
+                    let _ = self.pending.drain(..count);
+                }
+                Err(SendError::Failed(e)) if retries >= self.config.max_retries => {
+                    log::error!(
+                        "Sending VES Events failed: {}, dropping them after {} retries.",
+                        e,
+                        retries
+                    );
+                    let _ = self.pending.drain(..count);
+                    backoff.reset();
+                    retries = 0;
+                }
+                Err(SendError::Failed(e)) => {
+                    let delay = backoff.next_delay();
+                    log::warn!(
+                        "Sending VES Events failed: {}, retrying after {:?}.",
+                        e,
+                        delay
+                    );
+                    retries += 1;
+                    next_attempt = Instant::now() + delay;
+                }
+            }
+        }
+
+        log::info!(
+            "VES exporter thread stopped, {} VES Event(s) not sent.",
+            self.pending.len()
+        );
+    }
+
+    // Collect the metrics from the registry as VES Events to be sent.
+    fn collect(&mut self) {
+        let samples = self.registry.lock().unwrap().samples();
+
+        let start = self.last_collected;
+        let end = epoch_micros();
+        self.last_collected = end;
+
+        for measurements in samples.chunks(self.config.max_measurements_per_event) {
+            let event = self.measurement_event(measurements, start, end);
+            if self.pending.len() >= self.config.max_pending_events {
+                log::warn!("Too many VES Events waiting to be sent, dropping the oldest.");
+                let _ = self.pending.pop_front();
+            }
+            self.pending.push_back(event);
+        }
+    }
+
+    fn measurement_event(&mut self, samples: &[Sample], start: u64, end: u64) -> Value {
+        self.sequence += 1;
+
+        let additional_measurements = samples
+            .iter()
+            .map(|sample| {
+                let mut hash_map = serde_json::Map::new();
+                for (name, value) in &sample.labels {
+                    let _ =
+                        hash_map.insert(format!("label.{}", name), Value::String(value.clone()));
+                }
+                let _ = hash_map.insert("value".to_string(), Value::String(sample.value.clone()));
+                json!({ "name": sample.name, "hashMap": hash_map })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "commonEventHeader": {
+                "domain": "measurement",
+                "eventId": format!("{}-{}", self.source_name, self.sequence),
+                "eventName": format!("measurement_{}", self.reporting_entity_name),
+                "eventType": "xapp-metrics",
+                "priority": "Normal",
+                "reportingEntityName": self.reporting_entity_name,
+                "sourceName": self.source_name,
+                "sequence": self.sequence,
+                "startEpochMicrosec": start,
+                "lastEpochMicrosec": end,
+                "version": COMMON_EVENT_HEADER_VERSION,
+                "vesEventListenerVersion": VES_EVENT_LISTENER_VERSION,
+            },
+            "measurementFields": {
+                "measurementFieldsVersion": MEASUREMENT_FIELDS_VERSION,
+                "measurementInterval": self.config.interval.as_secs_f64(),
+                "additionalMeasurements": additional_measurements,
+            }
+        })
+    }
+
+    fn send(&self, events: Vec<Value>) -> Result<(), SendError> {
+        let path = format!("{}/{}", self.config.collector_url, EVENT_BATCH_PATH);
+        let body = json!({ "eventList": events });
+
+        log::debug!("Sending {} VES Events to '{}'", events_len(&body), path);
+        let mut request = self.http_client.post(path).json(&body);
+        if let Some((ref user, ref password)) = self.config.basic_auth {
+            request = request.basic_auth(user, Some(password));
+        }
+        let response = request
+            .send()
+            .map_err(|e| SendError::Failed(format!("Error sending request: {}", e)))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_client_error() {
+            Err(SendError::Rejected(format!("Server returned: {}", status)))
+        } else {
+            Err(SendError::Failed(format!("Server returned: {}", status)))
+        }
+    }
+}
+
+fn events_len(body: &Value) -> usize {
+    body["eventList"]
+        .as_array()
+        .map(Vec::len)
+        .unwrap_or_default()
+}
+
+fn epoch_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use crate::xapp::test_utils::http_stub;
+
+    use super::{VesConfig, VesExporter};
+
+    #[test]
+    fn test_config_validate() {
+        assert!(VesConfig::new("http://ves-collector:8080/eventListener/v7")
+            .validate()
+            .is_ok());
+        assert!(VesConfig::new("ves-collector:8080").validate().is_err());
+
+        let mut config = VesConfig::new("http://ves-collector:8080/eventListener/v7");
+        config.max_measurements_per_event = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_exporter_batches_and_retries() {
+        // Collector is unavailable for the first request.
+        let collector = http_stub(vec![503, 202]);
+
+        let registry = crate::xapp::metrics::registry_for_ns_app("ricxapp", "test-ves-app");
+        assert!(registry.is_ok(), "{:?}", registry.err().unwrap());
+        let registry = registry.unwrap();
+        registry.increment_rmr_rx_messages(12010);
+        let registry = Arc::new(Mutex::new(registry));
+
+        let mut config = VesConfig::new(&format!("{}/eventListener/v7", collector.url()));
+        config.interval = Duration::from_millis(100);
+        config.max_measurements_per_event = 1;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let exporter = VesExporter::new(
+            config,
+            registry,
+            "test-ves-app",
+            "test-ves-app-1",
+            Arc::clone(&running),
+        );
+        assert!(exporter.is_ok(), "{}", exporter.err().unwrap());
+        let exporter_thread = std::thread::spawn(move || exporter.unwrap().run());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while collector.requests().len() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        running.store(false, Ordering::Relaxed);
+        let _ = exporter_thread.join();
+
+        let requests = collector.requests();
+        assert!(requests.len() >= 2, "{:?}", requests);
+
+        // The failed batch is sent again, along with the Events collected while retrying.
+        let failed: serde_json::Value = requests[0].json().unwrap();
+        let body: serde_json::Value = requests[1].json().unwrap();
+        let events = body["eventList"].as_array().unwrap();
+        assert_eq!(failed["eventList"].as_array().unwrap().len(), 1);
+        assert_eq!(events[0], failed["eventList"][0]);
+        assert!(events.len() > 1 && events.len() <= 10, "{:#?}", events);
+
+        let event = &events[0];
+        assert_eq!(event["commonEventHeader"]["domain"], "measurement");
+        assert_eq!(event["commonEventHeader"]["sourceName"], "test-ves-app-1");
+        let measurement = &event["measurementFields"]["additionalMeasurements"][0];
+        assert_eq!(
+            measurement["name"],
+            "ricxapp_test_ves_app_rmr_messages_rx_total"
+        );
+        assert_eq!(measurement["hashMap"]["label.message_type"], "12010");
+        assert_eq!(measurement["hashMap"]["value"], "1");
+    }
+}
@@ -0,0 +1,299 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Processing of the received RMR Messages
+//!
+//! The RMR Messages received by the `RMRReceiver` are processed by the Message Processor thread
+//! of the XApp. If a handler is registered for the message type using `register_handler`, the
+//! handler is called for the message. Otherwise the message is sent on the channel given to the
+//! XApp when it was created.
+//!
+//! ```ignore
+//!     xapp.register_handler(RIC_HEALTH_CHECK_REQ, |msg, sender| {
+//!         msg.set_mtype(RIC_HEALTH_CHECK_RESP);
+//!         sender.rts_msg(msg)
+//!     })?;
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rmr::{RMRClient, RMRMessageBuffer};
+
+use super::health::Heartbeat;
+use super::logging::{mdc_put, mdc_remove, MDC_MEID, MDC_MTYPE};
+use super::message_log::{MessageDirection, MessageLog};
+use super::metrics::instrumentation::Instrumentation;
+use super::telemetry;
+use super::XApp;
+use crate::XAppError;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Handler for the RMR Messages of a given message type
+///
+/// The message is freed by the framework after the handler returns.
+pub type RMRHandler =
+    Box<dyn FnMut(&mut RMRMessageBuffer, &RMRSender) -> Result<(), XAppError> + Send>;
+
+/// Sending RMR Messages from the XApp
+///
+/// Messages sent using the `RMRSender` are counted in the `rmr_messages_tx` metric of the XApp
+/// and are kept in the recent messages, if enabled using `enable_message_log`.
+#[derive(Clone)]
+pub struct RMRSender {
+    client: Arc<Mutex<RMRClient>>,
+    instrumentation: Instrumentation,
+    message_log: Arc<MessageLog>,
+}
+
+impl RMRSender {
+    pub(crate) fn new(
+        client: Arc<Mutex<RMRClient>>,
+        instrumentation: Instrumentation,
+        message_log: Arc<MessageLog>,
+    ) -> Self {
+        Self {
+            client,
+            instrumentation,
+            message_log,
+        }
+    }
+
+    /// Allocate a new Message Buffer for sending
+    pub fn alloc_msg(&self) -> RMRMessageBuffer {
+        let client = self.client.lock().expect("Corrupted RMRClient Mutex");
+        RMRMessageBuffer::new(&client)
+    }
+
+    /// Send the Message using the RMR Routing Table
+    ///
+    /// On return, `msg` contains the buffer returned by RMR, which should be freed by the caller.
+    pub fn send_msg(&self, msg: &mut RMRMessageBuffer) -> Result<(), XAppError> {
+        let message_type = msg.get_msgtype();
+        // The buffer is returned by RMR after sending, hence the record is prepared before and
+        // kept only if the message is sent.
+        let record = self.message_log.prepare(MessageDirection::Sent, msg);
+        self.client
+            .lock()
+            .expect("Corrupted RMRClient Mutex")
+            .send_msg(msg)?;
+        self.instrumentation.rmr_message_sent(message_type);
+        if let Some(record) = record {
+            self.message_log.push(record);
+        }
+        Ok(())
+    }
+
+    /// Return the Message to Sender
+    pub fn rts_msg(&self, msg: &RMRMessageBuffer) -> Result<(), XAppError> {
+        self.client
+            .lock()
+            .expect("Corrupted RMRClient Mutex")
+            .rts_msg(msg)?;
+        self.instrumentation.rmr_message_sent(msg.get_msgtype());
+        self.message_log.record(MessageDirection::Sent, msg);
+        Ok(())
+    }
+
+    /// Is the underlying RMR Client ready?
+    pub fn is_ready(&self) -> bool {
+        self.client
+            .lock()
+            .expect("Corrupted RMRClient Mutex")
+            .is_ready()
+    }
+}
+
+impl XApp {
+    /// Register a handler for the RMR Messages of the given message type
+    ///
+    /// The handlers should be registered before `start`. Messages of the types without a handler
+    /// are sent on the channel given to the XApp when it was created.
+    pub fn register_handler<F>(&mut self, message_type: i32, handler: F) -> Result<(), XAppError>
+    where
+        F: FnMut(&mut RMRMessageBuffer, &RMRSender) -> Result<(), XAppError> + Send + 'static,
+    {
+        let processor = self
+            .processor
+            .get_mut()
+            .expect("Corrupted MessageProcessor Mutex")
+            .as_mut()
+            .ok_or_else(|| {
+                XAppError("Handlers should be registered before starting the XApp.".to_string())
+            })?;
+
+        if processor.handlers.contains_key(&message_type) {
+            return Err(XAppError(format!(
+                "Handler for the message type {} is already registered.",
+                message_type
+            )));
+        }
+        let _ = processor.handlers.insert(message_type, Box::new(handler));
+
+        Ok(())
+    }
+
+    /// Get an `RMRSender` for sending the RMR Messages
+    pub fn get_rmr_sender(&self) -> RMRSender {
+        RMRSender::new(
+            Arc::clone(&self.rmr_client),
+            self.instrumentation.clone(),
+            Arc::clone(&self.message_log),
+        )
+    }
+
+    // Starts the Message Processor thread.
+    pub(crate) fn start_processor(&mut self) {
+        let processor = self
+            .processor
+            .get_mut()
+            .expect("Corrupted MessageProcessor Mutex")
+            .take();
+
+        if let Some(mut processor) = processor {
+            processor.sender = self.get_rmr_sender();
+            processor.instrumentation = self.instrumentation.clone();
+            processor.heartbeat = self.heartbeat("message_processor");
+            processor.message_log = Arc::clone(&self.message_log);
+
+            let processor_thread = std::thread::spawn(move || processor.run());
+            let _ = self.processor_thread.replace(processor_thread);
+        }
+    }
+}
+
+// Message Processor: Runs in it's own thread and processes the received RMR Messages.
+pub(crate) struct MessageProcessor {
+    data_rx: Receiver<RMRMessageBuffer>,
+    app_tx: Sender<RMRMessageBuffer>,
+    handlers: HashMap<i32, RMRHandler>,
+    sender: RMRSender,
+    instrumentation: Instrumentation,
+    heartbeat: Heartbeat,
+    message_log: Arc<MessageLog>,
+    is_running: Arc<AtomicBool>,
+}
+
+impl MessageProcessor {
+    pub(crate) fn new(
+        data_rx: Receiver<RMRMessageBuffer>,
+        app_tx: Sender<RMRMessageBuffer>,
+        sender: RMRSender,
+        is_running: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            data_rx,
+            app_tx,
+            handlers: HashMap::new(),
+            sender,
+            instrumentation: Instrumentation::default(),
+            heartbeat: Heartbeat::default(),
+            message_log: Arc::default(),
+            is_running,
+        }
+    }
+
+    fn run(mut self) {
+        log::info!("Starting message processor thread!");
+
+        // Messages received, but not yet processed. Used for the receive queue depth.
+        let mut pending = VecDeque::new();
+
+        while self.is_running.load(Ordering::Relaxed) {
+            self.heartbeat.beat();
+            match self.data_rx.recv_timeout(POLL_INTERVAL) {
+                Ok(msg) => pending.push_back(msg),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            while self.is_running.load(Ordering::Relaxed) {
+                pending.extend(self.data_rx.try_iter());
+                self.instrumentation.set_receive_queue_depth(pending.len());
+
+                self.heartbeat.beat();
+                match pending.pop_front() {
+                    Some(msg) => self.process_msg(msg),
+                    None => break,
+                }
+            }
+        }
+
+        // Messages received, but not processed before stopping are freed.
+        pending.extend(self.data_rx.try_iter());
+        if !pending.is_empty() {
+            log::warn!(
+                "Dropping {} received message(s) that were not processed.",
+                pending.len()
+            );
+        }
+        for msg in pending.drain(..) {
+            msg.free();
+        }
+        self.instrumentation.set_receive_queue_depth(0);
+
+        log::info!("Message processor thread stopped!");
+    }
+
+    fn process_msg(&mut self, mut msg: RMRMessageBuffer) {
+        let message_type = msg.get_msgtype();
+        self.instrumentation.rmr_message_received(message_type);
+        self.message_log.record(MessageDirection::Received, &msg);
+
+        match self.handlers.get_mut(&message_type) {
+            Some(handler) => {
+                let meid = msg.get_meid();
+                mdc_put(MDC_MTYPE, &message_type.to_string());
+                if let Some(ref meid) = meid {
+                    mdc_put(MDC_MEID, meid);
+                }
+                let span = telemetry::rmr_message_span(message_type, meid.as_deref());
+
+                let start = Instant::now();
+                let result = handler(&mut msg, &self.sender);
+                self.instrumentation.rmr_message_handled(
+                    message_type,
+                    start.elapsed(),
+                    result.is_ok(),
+                );
+                if let Err(e) = result {
+                    span.record_error(&e.to_string());
+                    log::warn!(
+                        "Error: '{}' in handling the message of type {}",
+                        e,
+                        message_type
+                    );
+                }
+                mdc_remove(MDC_MTYPE);
+                mdc_remove(MDC_MEID);
+                msg.free();
+            }
+            None => {
+                if let Err(e) = self.app_tx.send(msg) {
+                    log::warn!(
+                        "No receiver for the message of type {}, dropping it.",
+                        message_type
+                    );
+                    e.0.free();
+                }
+            }
+        }
+    }
+}
@@ -17,17 +17,46 @@
 //! Management of Registration and Deregistration of XApps
 //!
 //! This module implements APIs for interacting with App Manager of the NearRT RIC Platform.
+//!
+//! An XApp can either register itself using `register_xapp` or let the framework manage the
+//! registration using `enable_registration`. When the registration is managed by the framework,
+//! the XApp is registered once the RMR is ready, failed attempts are retried with an exponential
+//! backoff and the XApp is registered again if the App Manager restarts. In either case the XApp
+//! is deregistered when the XApp is stopped.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
 
-use std::sync::atomic::Ordering;
+use reqwest::blocking::Client as ReqwestClient;
 
 use registration_api::models::{DeregisterRequest, RegisterRequest};
+use rmr::RMRReceiver;
 
+use super::backoff::Backoff;
+use super::discovery::XAPPS_URL;
+use super::health::Heartbeat;
+use super::logging::{global_mdc_put, MDC_XAPP_INSTANCE};
+use super::metrics::instrumentation::{Instrumentation, APPMGR_SERVICE};
+use super::metrics::MetricsRegistry;
 use super::{XApp, XAppError};
 
-const APP_MGR_HOST: &str = "http://service-ricplt-appmgr-http.ricplt:8080";
 const REGISTRATION_URL: &str = "ric/v1/register";
+const DEREGISTRATION_URL: &str = "ric/v1/deregister";
+const APP_MGR_ALIVE_URL: &str = "ric/v1/health/alive";
 const CONFIG_PATH: &str = "/ric/v1/config";
 
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+const APP_MGR_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 impl XApp {
     /// Register the XApp with the App Manager
     pub fn register_xapp(
@@ -37,14 +66,146 @@ impl XApp {
         config: &str,
         xapp_ns: Option<&str>,
     ) -> Result<(), XAppError> {
-        let ns = xapp_ns.unwrap_or(crate::xapp::DEFAULT_XAPP_NS);
-        let http_host = Self::get_from_env(ns, xapp_name, "http", "host");
-        let http_port = Self::get_from_env(ns, xapp_name, "http", "port");
-        let http_endpoint = format!("{}:{}", http_host, http_port);
+        let reg_request =
+            self.registration_request(xapp_name, xapp_instance_name, config, xapp_ns)?;
+
+        let req_client = http_client()?;
+        send_registration_request(
+            &req_client,
+            &self.endpoints.appmgr,
+            &reg_request,
+            &self.instrumentation,
+        )?;
+
+        self.app_is_registered.store(true, Ordering::SeqCst);
+        let _ = self.app_name.replace(xapp_name.to_string());
+        let _ = self
+            .app_instance_name
+            .replace(xapp_instance_name.to_string());
+        global_mdc_put(MDC_XAPP_INSTANCE, xapp_instance_name);
+
+        Ok(())
+    }
+
+    /// Let the framework manage the Registration of the XApp with the App Manager
+    ///
+    /// The XApp is registered using the name from the config metadata and the given
+    /// `xapp_instance_name`, once the RMR is ready after `start`. Failed attempts are retried with
+    /// an exponential backoff. The App Manager is checked periodically and if it restarts or no
+    /// longer has the registration of the XApp instance, the XApp is registered again. The XApp is
+    /// deregistered on `stop`.
+    ///
+    /// Returns an error if the endpoints of the XApp are not known (see `set_service_endpoints`).
+    pub fn enable_registration(
+        &mut self,
+        xapp_instance_name: &str,
+        xapp_ns: Option<&str>,
+    ) -> Result<(), XAppError> {
+        let current = self.config.current();
+        let xapp_name = current.metadata.xapp_name.clone();
+        let config = serde_json::to_string(&current.config)
+            .map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+
+        let reg_request =
+            self.registration_request(&xapp_name, xapp_instance_name, &config, xapp_ns)?;
+
+        let _ = self.app_name.replace(xapp_name);
+        let _ = self
+            .app_instance_name
+            .replace(xapp_instance_name.to_string());
+        global_mdc_put(MDC_XAPP_INSTANCE, xapp_instance_name);
+        let _ = self.registration_request.replace(reg_request);
+
+        Ok(())
+    }
+
+    /// Set the HTTP and RMR Endpoints (`host:port`) of the XApp
+    ///
+    /// The endpoints are used for the registration with the App Manager and as the client
+    /// endpoint for the Subscriptions. By default, the hosts are taken from the
+    /// `SERVICE_<NS>_<XAPP>_HTTP_SERVICE_HOST` and `SERVICE_<NS>_<XAPP>_RMR_SERVICE_HOST`
+    /// environment variables and the ports from the corresponding `_SERVICE_PORT` variables, or
+    /// else from the XApp config. This should be called before registering the XApp.
+    pub fn set_service_endpoints(&mut self, http_endpoint: &str, rmr_endpoint: &str) {
+        let _ = self
+            .service_endpoints
+            .replace((http_endpoint.to_string(), rmr_endpoint.to_string()));
+    }
+
+    // HTTP and RMR Endpoints of the XApp, for the Subscriptions and the App Manager events: the
+    // ones used for the registration managed by the framework, if enabled.
+    pub(crate) fn client_endpoints(&self) -> Result<(String, String), XAppError> {
+        match self.registration_request {
+            Some(ref request) => Ok((request.http_endpoint.clone(), request.rmr_endpoint.clone())),
+            None => {
+                let xapp_name = self.config.current().metadata.xapp_name.clone();
+                self.service_endpoints(&xapp_name, None)
+            }
+        }
+    }
 
-        let rmr_host = Self::get_from_env(ns, xapp_name, "rmr", "host");
-        let rmr_port = Self::get_from_env(ns, xapp_name, "rmr", "port");
-        let rmr_endpoint = format!("{}:{}", rmr_host, rmr_port);
+    /// Is the XApp currently registered with the App Manager?
+    pub fn is_registered(&self) -> bool {
+        self.app_is_registered.load(Ordering::SeqCst)
+    }
+
+    /// Deregister XApp
+    pub fn deregister_xapp(&self) -> Result<(), XAppError> {
+        let (app_name, app_instance_name) = match (&self.app_name, &self.app_instance_name) {
+            (Some(app_name), Some(app_instance_name)) => {
+                (app_name.clone(), app_instance_name.clone())
+            }
+            _ => return Err(XAppError("XApp is not registered!".to_string())),
+        };
+
+        let deregister_request = DeregisterRequest {
+            app_name,
+            app_instance_name,
+        };
+
+        let result = http_client().and_then(|req_client| {
+            send_deregistration_request(
+                &req_client,
+                &self.endpoints.appmgr,
+                &deregister_request,
+                &self.instrumentation,
+            )
+        });
+
+        self.app_is_registered.store(false, Ordering::SeqCst);
+        result
+    }
+
+    // Starts the Registration Manager thread, if the registration is managed by the framework.
+    pub(crate) fn start_registration_manager(&mut self) -> Result<(), XAppError> {
+        if let Some(ref reg_request) = self.registration_request {
+            let receiver = Arc::clone(&self.receiver);
+            let mut manager = RegistrationManager::new(
+                reg_request.clone(),
+                self.endpoints.appmgr.clone(),
+                Box::new(move || RMRReceiver::is_ready(Arc::clone(&receiver))),
+                Arc::clone(&self.app_is_running),
+                Arc::clone(&self.app_is_registered),
+                self.metrics.as_ref(),
+                self.instrumentation.clone(),
+            )?;
+
+            manager.heartbeat = self.heartbeat("registration_manager");
+            let registration_thread = std::thread::spawn(move || manager.run());
+            let _ = self.registration_thread.replace(registration_thread);
+        }
+
+        Ok(())
+    }
+
+    fn registration_request(
+        &self,
+        xapp_name: &str,
+        xapp_instance_name: &str,
+        config: &str,
+        xapp_ns: Option<&str>,
+    ) -> Result<RegisterRequest, XAppError> {
+        let (http_endpoint, rmr_endpoint) = self.service_endpoints(xapp_name, xapp_ns)?;
 
         log::info!(
             "HTTP Endpoint: {}, RMR Endpoint: {}",
@@ -52,7 +213,7 @@ impl XApp {
             rmr_endpoint
         );
 
-        let reg_request = RegisterRequest {
+        Ok(RegisterRequest {
             app_name: xapp_name.to_string(),
             app_instance_name: xapp_instance_name.to_string(),
             app_version: None,
@@ -60,94 +221,432 @@ impl XApp {
             config: Some(config.to_string()),
             http_endpoint,
             rmr_endpoint,
+        })
+    }
+
+    // Endpoints set using `set_service_endpoints`, or else from the environment and the config.
+    fn service_endpoints(
+        &self,
+        xapp_name: &str,
+        xapp_ns: Option<&str>,
+    ) -> Result<(String, String), XAppError> {
+        if let Some(ref endpoints) = self.service_endpoints {
+            return Ok(endpoints.clone());
+        }
+
+        let ns = xapp_ns.unwrap_or(crate::xapp::DEFAULT_XAPP_NS);
+        let config = self.config.current();
+        let endpoint = |service: &str, port_name: &str| -> Result<String, XAppError> {
+            let host = Self::get_from_env(ns, xapp_name, service, "host")?;
+            let port = match Self::get_from_env(ns, xapp_name, service, "port") {
+                Ok(port) => port,
+                Err(_) => Self::port_from_config(&config, port_name)?.to_string(),
+            };
+            Ok(format!("{}:{}", host, port))
         };
 
-        let json = serde_json::to_string(&reg_request)
-            .map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+        Ok((endpoint("http", "http")?, endpoint("rmr", "rmrdata")?))
+    }
 
-        let req_client = reqwest::blocking::Client::new();
-        let path = format!("{}/{}", APP_MGR_HOST, REGISTRATION_URL);
-
-        log::debug!("Sending Registration Request: '{}' to '{}'", json, path);
-        let response = req_client
-            .post(path)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(json)
-            .send()
-            .map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
-
-        if response.status().is_success() {
-            log::info!(
-                "Registration Response Code: {}, Body: {}",
-                response.status(),
-                response.text().unwrap()
-            );
+    #[inline(always)]
+    fn get_from_env(ns: &str, xapp: &str, service: &str, typ: &str) -> Result<String, XAppError> {
+        let env_name = format!("SERVICE_{}_{}_{}_SERVICE_{}", ns, xapp, service, typ,);
+        let env_name = env_name.replace(['-'], "_").to_uppercase();
+        std::env::var(&env_name).map_err(|_| XAppError(format!("Env '{env_name}' Not Set!")))
+    }
+}
 
-            self.app_is_registered.store(true, Ordering::SeqCst);
-            let _ = self.app_name.replace(xapp_name.to_string());
-            let _ = self
-                .app_instance_name
-                .replace(xapp_instance_name.to_string());
+// Labels for the registration attempts counter
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RegistrationAttempt {
+    result: String,
+}
+
+// Is the RMR ready? The XApp is registered only after the RMR is ready.
+type RmrReady = Box<dyn Fn() -> bool + Send>;
+
+// Registration Manager: Runs in it's own thread and keeps the XApp registered with the App Manager.
+pub(crate) struct RegistrationManager {
+    request: RegisterRequest,
+    appmgr: String,
+    http_client: ReqwestClient,
+    rmr_ready: RmrReady,
+    check_interval: Duration,
+    app_is_running: Arc<AtomicBool>,
+    app_is_registered: Arc<AtomicBool>,
+    registration_status: Gauge,
+    registration_attempts: Family<RegistrationAttempt, Counter>,
+    instrumentation: Instrumentation,
+    heartbeat: Heartbeat,
+}
+
+impl RegistrationManager {
+    fn new(
+        request: RegisterRequest,
+        appmgr: String,
+        rmr_ready: RmrReady,
+        app_is_running: Arc<AtomicBool>,
+        app_is_registered: Arc<AtomicBool>,
+        metrics: Option<&Arc<Mutex<MetricsRegistry>>>,
+        instrumentation: Instrumentation,
+    ) -> Result<Self, XAppError> {
+        let registration_status = Gauge::default();
+        let registration_attempts = Family::<RegistrationAttempt, Counter>::default();
 
-            Ok(())
-        } else {
-            Err(XAppError(format!("Error : {}", response.status())))
+        if let Some(metrics) = metrics {
+            let mut metrics = metrics.lock().unwrap();
+            metrics.register_metric(
+                "registration_status",
+                "Registration status of the XApp with the App Manager (1: Registered)",
+                registration_status.clone(),
+            )?;
+            metrics.register_metric(
+                "registration_attempts",
+                "Number of registration attempts with the App Manager",
+                registration_attempts.clone(),
+            )?;
         }
+
+        Ok(Self {
+            request,
+            appmgr,
+            http_client: http_client()?,
+            rmr_ready,
+            check_interval: APP_MGR_CHECK_INTERVAL,
+            app_is_running,
+            app_is_registered,
+            registration_status,
+            registration_attempts,
+            instrumentation,
+            heartbeat: Heartbeat::default(),
+        })
     }
 
-    /// Deregister XApp
-    pub fn deregister_xapp(&self) -> Result<(), XAppError> {
-        let deregister_request = DeregisterRequest {
-            app_name: self.app_name.clone().unwrap().clone(),
-            app_instance_name: self.app_instance_name.clone().unwrap().clone(),
-        };
+    fn run(self) {
+        log::info!("Starting registration manager thread!");
 
-        let json = serde_json::to_string(&deregister_request)
-            .map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+        let mut backoff = Backoff::new(RETRY_INITIAL_DELAY, RETRY_MAX_DELAY);
+        let mut next_attempt = Instant::now();
+        let mut appmgr_unavailable = false;
 
-        let req_client = reqwest::blocking::Client::new();
-        let path = format!("{}/{}", APP_MGR_HOST, REGISTRATION_URL);
-
-        log::debug!("Sending Deregistration Request: '{}' to '{}'", json, path);
-        let response = req_client
-            .post(path)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(json)
-            .send()
-            .map_err(|e| XAppError(format!("Error sending request: {}", e)));
-
-        match response {
-            Ok(response) => {
-                if response.status().is_success() {
-                    log::info!(
-                        "Deregistration for XApp {} Successful",
-                        self.app_name.clone().unwrap()
-                    );
-                } else {
-                    log::error!(
-                        "DeRegistration Response Code: {} for XApp {}",
-                        response.status(),
-                        self.app_name.clone().unwrap()
-                    );
-                }
+        while self.app_is_running.load(Ordering::Relaxed) {
+            self.heartbeat.beat();
+            if Instant::now() < next_attempt {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
             }
-            Err(e) => {
-                log::error!(
-                    "Error: '{}' during Dergeistering XApp: {}",
-                    e,
-                    self.app_name.clone().unwrap()
-                );
+
+            if !self.app_is_registered.load(Ordering::SeqCst) {
+                if !(self.rmr_ready)() {
+                    log::debug!("Waiting for RMR to be ready before registering the XApp!");
+                    next_attempt = Instant::now() + RETRY_INITIAL_DELAY;
+                    continue;
+                }
+
+                match send_registration_request(
+                    &self.http_client,
+                    &self.appmgr,
+                    &self.request,
+                    &self.instrumentation,
+                ) {
+                    Ok(()) => {
+                        self.set_registered(true);
+                        self.record_attempt("success");
+                        backoff.reset();
+                        appmgr_unavailable = false;
+                        next_attempt = Instant::now() + self.check_interval;
+                    }
+                    Err(e) => {
+                        let delay = backoff.next_delay();
+                        log::warn!("Registration failed: {}, retrying after {:?}.", e, delay);
+                        self.record_attempt("failure");
+                        next_attempt = Instant::now() + delay;
+                    }
+                }
+            } else {
+                let result =
+                    check_appmgr_alive(&self.http_client, &self.appmgr, &self.instrumentation)
+                        .and_then(|()| {
+                            check_registration(
+                                &self.http_client,
+                                &self.appmgr,
+                                &self.request,
+                                &self.instrumentation,
+                            )
+                        });
+                match result {
+                    Ok(true) => {
+                        if appmgr_unavailable {
+                            // App Manager might have restarted and lost our registration.
+                            log::info!("App Manager available again, registering the XApp again!");
+                            self.set_registered(false);
+                            appmgr_unavailable = false;
+                            next_attempt = Instant::now();
+                        } else {
+                            next_attempt = Instant::now() + self.check_interval;
+                        }
+                    }
+                    Ok(false) => {
+                        // App Manager restarted (without being noticed) and lost our registration.
+                        log::info!("XApp not registered with App Manager, registering it again!");
+                        self.set_registered(false);
+                        appmgr_unavailable = false;
+                        next_attempt = Instant::now();
+                    }
+                    Err(e) => {
+                        if !appmgr_unavailable {
+                            log::warn!("App Manager not available: {}", e);
+                        }
+                        appmgr_unavailable = true;
+                        next_attempt = Instant::now() + self.check_interval;
+                    }
+                }
             }
         }
 
-        self.app_is_registered.store(false, Ordering::SeqCst);
+        log::info!("Registration manager thread stopped!");
+    }
+
+    fn set_registered(&self, registered: bool) {
+        self.app_is_registered.store(registered, Ordering::SeqCst);
+        self.registration_status.set(registered as i64);
+    }
+
+    fn record_attempt(&self, result: &str) {
+        self.registration_attempts
+            .get_or_create(&RegistrationAttempt {
+                result: result.to_string(),
+            })
+            .inc();
+    }
+}
+
+pub(crate) fn http_client() -> Result<ReqwestClient, XAppError> {
+    ReqwestClient::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| XAppError(format!("Error creating HTTP client: {}", e)))
+}
+
+fn send_registration_request(
+    req_client: &ReqwestClient,
+    appmgr: &str,
+    reg_request: &RegisterRequest,
+    instrumentation: &Instrumentation,
+) -> Result<(), XAppError> {
+    let json =
+        serde_json::to_string(reg_request).map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+
+    let path = format!("{}/{}", appmgr, REGISTRATION_URL);
+
+    log::debug!("Sending Registration Request: '{}' to '{}'", json, path);
+    let request = req_client
+        .post(path)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(json);
+    let response = instrumentation.send_request(APPMGR_SERVICE, req_client, request);
+    let response = response.map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Registration Response Code: {}, Body: {}",
+            response.status(),
+            response.text().unwrap_or_default()
+        );
         Ok(())
+    } else {
+        Err(XAppError(format!("Error : {}", response.status())))
     }
+}
 
-    #[inline(always)]
-    fn get_from_env(ns: &str, xapp: &str, service: &str, typ: &str) -> String {
-        let env_name = format!("SERVICE_{}_{}_{}_SERVICE_{}", ns, xapp, service, typ,);
-        let env_name = env_name.replace(['-'], "_").to_uppercase();
-        std::env::var(env_name.clone()).expect(&format!("Env '{env_name}' Not Set!"))
+fn send_deregistration_request(
+    req_client: &ReqwestClient,
+    appmgr: &str,
+    deregister_request: &DeregisterRequest,
+    instrumentation: &Instrumentation,
+) -> Result<(), XAppError> {
+    let json = serde_json::to_string(deregister_request)
+        .map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+
+    let path = format!("{}/{}", appmgr, DEREGISTRATION_URL);
+
+    log::debug!("Sending Deregistration Request: '{}' to '{}'", json, path);
+    let request = req_client
+        .post(path)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(json);
+    let response = instrumentation.send_request(APPMGR_SERVICE, req_client, request);
+    let response = response.map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+
+    if response.status().is_success() {
+        log::info!(
+            "Deregistration for XApp {} Successful",
+            deregister_request.app_name
+        );
+        Ok(())
+    } else {
+        log::error!(
+            "DeRegistration Response Code: {} for XApp {}",
+            response.status(),
+            deregister_request.app_name
+        );
+        Err(XAppError(format!("Error : {}", response.status())))
+    }
+}
+
+fn check_appmgr_alive(
+    req_client: &ReqwestClient,
+    appmgr: &str,
+    instrumentation: &Instrumentation,
+) -> Result<(), XAppError> {
+    let path = format!("{}/{}", appmgr, APP_MGR_ALIVE_URL);
+
+    let response = instrumentation.send_request(APPMGR_SERVICE, req_client, req_client.get(path));
+    let response = response.map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(XAppError(format!("Error : {}", response.status())))
+    }
+}
+
+// Is the XApp instance of the `request` still registered with the App Manager?
+fn check_registration(
+    req_client: &ReqwestClient,
+    appmgr: &str,
+    request: &RegisterRequest,
+    instrumentation: &Instrumentation,
+) -> Result<bool, XAppError> {
+    let path = format!(
+        "{}/{}/{}/instances/{}",
+        appmgr, XAPPS_URL, request.app_name, request.app_instance_name
+    );
+
+    let response = instrumentation.send_request(APPMGR_SERVICE, req_client, req_client.get(path));
+    let response = response.map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+
+    match response.status() {
+        status if status.is_success() => Ok(true),
+        reqwest::StatusCode::NOT_FOUND => Ok(false),
+        status => Err(XAppError(format!("Error : {}", status))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use registration_api::models::{DeregisterRequest, RegisterRequest};
+    use xapp_testkit::appmgr::{DEREGISTER_PATH, REGISTER_PATH};
+    use xapp_testkit::FakeAppMgr;
+
+    use super::RegistrationManager;
+    use crate::xapp::metrics::instrumentation::Instrumentation;
+
+    fn wait_until<F: Fn() -> bool>(condition: F) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_registration_manager_registers_again() {
+        let appmgr = FakeAppMgr::start().unwrap();
+        appmgr.fail_next_on(REGISTER_PATH, 1, 503);
+
+        let request = RegisterRequest::new(
+            "tests".to_string(),
+            "tests-0".to_string(),
+            "127.0.0.1:8080".to_string(),
+            "127.0.0.1:4560".to_string(),
+        );
+        let app_is_running = Arc::new(AtomicBool::new(true));
+        let app_is_registered = Arc::new(AtomicBool::new(false));
+        let mut manager = RegistrationManager::new(
+            request,
+            appmgr.url().to_string(),
+            Box::new(|| true),
+            Arc::clone(&app_is_running),
+            Arc::clone(&app_is_registered),
+            None,
+            Instrumentation::default(),
+        )
+        .unwrap();
+        manager.check_interval = Duration::from_millis(50);
+        let manager_thread = std::thread::spawn(move || manager.run());
+
+        // The failed registration is retried. The App Manager has the registration before the
+        // manager marks the XApp registered.
+        assert!(wait_until(|| appmgr.is_registered("tests-0")));
+        assert!(wait_until(|| app_is_registered.load(Ordering::SeqCst)));
+        assert_eq!(appmgr.requests_to("POST", REGISTER_PATH).len(), 2);
+
+        // App Manager loses the registration, while it is alive.
+        let deregister = DeregisterRequest::new("tests".to_string(), "tests-0".to_string());
+        let response = reqwest::blocking::Client::new()
+            .post(format!("{}{}", appmgr.url(), DEREGISTER_PATH))
+            .json(&deregister)
+            .send();
+        assert!(response.unwrap().status().is_success());
+        assert!(wait_until(|| appmgr.is_registered("tests-0")));
+        assert!(wait_until(|| app_is_registered.load(Ordering::SeqCst)));
+        assert_eq!(appmgr.requests_to("POST", REGISTER_PATH).len(), 3);
+
+        app_is_running.store(false, Ordering::Relaxed);
+        assert!(manager_thread.join().is_ok());
+    }
+
+    #[test]
+    fn test_registration_request_env_not_set() {
+        let (app_tx, _) = std::sync::mpsc::channel();
+        let xapp = crate::XApp::from_config(crate::xapp::tests::get_config_data(5690), app_tx);
+        let xapp = xapp.unwrap();
+
+        let result = xapp.registration_request("no-such-xapp", "no-such-xapp-instance", "{}", None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_registration_request_from_env() {
+        for (service, port) in [("HTTP", Some("8080")), ("RMR", None)] {
+            std::env::set_var(
+                format!("SERVICE_RICXAPP_REG_TEST_{}_SERVICE_HOST", service),
+                "10.0.0.1",
+            );
+            if let Some(port) = port {
+                std::env::set_var(
+                    format!("SERVICE_RICXAPP_REG_TEST_{}_SERVICE_PORT", service),
+                    port,
+                );
+            }
+        }
+
+        let (app_tx, _) = std::sync::mpsc::channel();
+        let xapp = crate::XApp::from_config(crate::xapp::tests::get_config_data(5691), app_tx);
+        let mut xapp = xapp.unwrap();
+
+        let result = xapp.registration_request("reg-test", "reg-test-instance", "{}", None);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+
+        // RMR port is not in the env, should be taken from the config.
+        let request = result.unwrap();
+        assert_eq!(request.http_endpoint, "10.0.0.1:8080");
+        assert_eq!(request.rmr_endpoint, "10.0.0.1:5691");
+        assert_eq!(request.config_path.as_deref(), Some("/ric/v1/config"));
+
+        xapp.set_service_endpoints("127.0.0.1:8081", "127.0.0.1:4561");
+        let request = xapp
+            .registration_request("no-such-xapp", "no-such-xapp-instance", "{}", None)
+            .unwrap();
+        assert_eq!(request.http_endpoint, "127.0.0.1:8081");
+        assert_eq!(xapp.client_endpoints().unwrap().1, "127.0.0.1:4561");
     }
 }
@@ -0,0 +1,215 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Graceful shutdown of the XApp
+//!
+//! When the XApp is stopped, either using `stop` or by a `SIGTERM` / `SIGINT` (when enabled
+//! using `enable_signal_handling`), the framework -
+//!
+//! - stops all the threads of the framework, including the Web Server,
+//! - deletes the Subscriptions of the XApp and deregisters the XApp with the App Manager,
+//! - sends the Alarms that are queued but not yet sent.
+//!
+//! `join` waits for the XApp to be stopped and then for all the threads to stop within the
+//! shutdown timeout. It returns a `ShutdownSummary` of the threads that did not stop cleanly.
+//!
+//! ```ignore
+//!     xapp.enable_signal_handling();
+//!     xapp.start();
+//!
+//!     // Process the messages on the channel, till the XApp is stopped.
+//!     while let Ok(msg) = app_rx.recv() {
+//!         ...
+//!     }
+//!
+//!     let summary = xapp.join();
+//!     if !summary.is_clean() {
+//!         log::warn!("XApp not stopped cleanly: {:?}", summary);
+//!     }
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+
+use super::XApp;
+
+pub(crate) const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+const JOIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Summary of the shutdown of the XApp
+#[derive(Debug, Default)]
+pub struct ShutdownSummary {
+    /// Threads that did not stop within the shutdown timeout.
+    pub timed_out: Vec<String>,
+
+    /// Threads and shutdown steps that failed, with the errors.
+    pub failed: Vec<(String, String)>,
+}
+
+impl ShutdownSummary {
+    /// Did everything stop cleanly?
+    pub fn is_clean(&self) -> bool {
+        self.timed_out.is_empty() && self.failed.is_empty()
+    }
+
+    // Waits for the thread to finish till the `deadline`. A thread that is still running after
+    // the deadline is left detached.
+    pub(crate) fn join_thread<T>(
+        &mut self,
+        name: &str,
+        thread: Option<JoinHandle<T>>,
+        deadline: Instant,
+        result: impl FnOnce(T) -> Result<(), String>,
+    ) {
+        let thread = match thread {
+            Some(thread) => thread,
+            None => return,
+        };
+
+        log::debug!("Waiting for {} thread to join!", name);
+        while !thread.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(JOIN_POLL_INTERVAL);
+        }
+        if !thread.is_finished() {
+            log::warn!("{} thread did not stop in time!", name);
+            self.timed_out.push(name.to_string());
+            return;
+        }
+
+        match thread.join() {
+            Ok(value) => {
+                if let Err(e) = result(value) {
+                    self.failed.push((name.to_string(), e));
+                }
+            }
+            Err(_) => self
+                .failed
+                .push((name.to_string(), "Thread panicked".to_string())),
+        }
+        log::debug!("{} thread joined!", name);
+    }
+}
+
+impl XApp {
+    /// Set the time to wait for the threads of the XApp to stop in `join`
+    ///
+    /// Default is 10 seconds.
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// Stop the XApp on `SIGTERM` or `SIGINT`
+    ///
+    /// This should be called before `start`. After a signal, the XApp is stopped as if `stop` was
+    /// called. The threads of the framework stop and the channel given to the XApp when it was
+    /// created is closed, the XApp should then call `join` to complete the shutdown.
+    pub fn enable_signal_handling(&mut self) {
+        self.handle_signals = true;
+    }
+
+    /// Is the XApp running?
+    ///
+    /// The XApp is running after `start`, till it is stopped.
+    pub fn is_running(&self) -> bool {
+        self.app_is_running.load(Ordering::Relaxed)
+    }
+
+    // Starts the Signal Handler thread, if the signal handling is enabled.
+    pub(crate) fn start_signal_handler(&mut self) {
+        if !self.handle_signals {
+            return;
+        }
+
+        let app_is_running = Arc::clone(&self.app_is_running);
+        let shutdown = self.shutdown_receiver();
+        let signal_thread = std::thread::spawn(move || wait_for_signal(app_is_running, shutdown));
+        let _ = self.signal_thread.replace(signal_thread);
+    }
+
+    // Notified when the XApp is stopped.
+    pub(crate) fn shutdown_receiver(&self) -> watch::Receiver<bool> {
+        self.shutdown_tx.subscribe()
+    }
+
+    // Waits for the XApp to be stopped, by `stop` or by a signal.
+    pub(crate) fn wait_for_stop(&self) {
+        while self.app_is_running.load(Ordering::Relaxed) {
+            std::thread::sleep(STOP_POLL_INTERVAL);
+        }
+    }
+}
+
+// Resolves when the XApp is stopped.
+pub(crate) async fn stopped(mut shutdown: watch::Receiver<bool>) {
+    let _ = shutdown.wait_for(|stopped| *stopped).await;
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn wait_for_signal(
+    app_is_running: Arc<AtomicBool>,
+    shutdown: watch::Receiver<bool>,
+) -> Result<(), String> {
+    let mut sigterm = signal(SignalKind::terminate()).map_err(|e| e.to_string())?;
+    let mut sigint = signal(SignalKind::interrupt()).map_err(|e| e.to_string())?;
+
+    tokio::select! {
+        _ = sigterm.recv() => log::info!("Received SIGTERM, stopping XApp!"),
+        _ = sigint.recv() => log::info!("Received SIGINT, stopping XApp!"),
+        _ = stopped(shutdown) => return Ok(()),
+    }
+    app_is_running.store(false, Ordering::Relaxed);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::ShutdownSummary;
+
+    #[test]
+    fn test_join_threads() {
+        let ok = std::thread::spawn(|| Ok(()));
+        let failed = std::thread::spawn(|| Err("Failed".to_string()));
+        let stuck = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(())
+        });
+
+        let mut summary = ShutdownSummary::default();
+        let deadline = Instant::now() + Duration::from_millis(200);
+        summary.join_thread("ok", Some(ok), deadline, |r| r);
+        summary.join_thread("failed", Some(failed), deadline, |r| r);
+        summary.join_thread("stuck", Some(stuck), deadline, |r| r);
+        summary.join_thread::<()>("not_started", None, deadline, Ok);
+
+        assert!(Instant::now() < deadline + Duration::from_millis(100));
+        assert!(!summary.is_clean());
+        assert_eq!(summary.timed_out, vec!["stuck".to_string()]);
+        assert_eq!(
+            summary.failed,
+            vec![("failed".to_string(), "Failed".to_string())]
+        );
+    }
+}
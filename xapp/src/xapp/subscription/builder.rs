@@ -0,0 +1,546 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Builder for the `SubscriptionParams`
+//!
+//! `SubscriptionParams` are made up of several nested models from the `subscription-api` crate.
+//! The builders in this module construct them and validate them locally, so that mistakes are
+//! reported before a request is sent to the Subscription Manager.
+//!
+//! ```ignore
+//!     let builder = SubscriptionParamsBuilder::new("gnb_734_733_b5c67788", 2).subscription_detail(
+//!         SubscriptionDetailBuilder::new(1, event_triggers).action(
+//!             ActionBuilder::new(1, ActionType::Report)
+//!                 .definition(action_definition)
+//!                 .subsequent_action(SubsequentActionType::Continue, Duration::from_millis(10)),
+//!         ),
+//!     );
+//!
+//!     let params = xapp.build_subscription_params(builder)?;
+//!     let response = xapp.subscribe(params)?;
+//! ```
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use ric_subscriptions::models::{
+    action_to_be_setup::ActionType, subsequent_action::SubsequentActionType,
+    subsequent_action::TimeToWait, ActionToBeSetup, SubscriptionDetail, SubscriptionParams,
+    SubscriptionParamsClientEndpoint, SubscriptionParamsE2SubscriptionDirectives, SubsequentAction,
+};
+
+use crate::XAppError;
+
+/// Error returned when validating the `SubscriptionParams`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionParamsError {
+    /// MEID is empty
+    EmptyMeid,
+
+    /// No Subscription Details are given
+    NoSubscriptionDetails,
+
+    /// XApp Event Instance ID is used by more than one Subscription Details
+    DuplicateEventInstanceId(u32),
+
+    /// Event Triggers are empty for the XApp Event Instance ID
+    EmptyEventTriggers(u32),
+
+    /// No Actions are given for the XApp Event Instance ID
+    NoActions(u32),
+
+    /// Action ID is used more than once for the XApp Event Instance ID: (instance ID, action ID)
+    DuplicateActionId(u32, u32),
+
+    /// Action Definition is empty: (instance ID, action ID)
+    EmptyActionDefinition(u32, u32),
+
+    /// Time To Wait of the Subsequent Action is not one of the E2AP values:
+    /// (instance ID, action ID, time to wait)
+    InvalidTimeToWait(u32, u32, Duration),
+
+    /// Client Endpoint cannot be determined
+    ClientEndpoint(String),
+
+    /// MEID is not found in RNIB
+    UnknownMeid(String),
+
+    /// Error in looking up the MEID in RNIB
+    Rnib(String),
+}
+
+impl std::fmt::Display for SubscriptionParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyMeid => write!(f, "SubscriptionParams: MEID is empty"),
+            Self::NoSubscriptionDetails => {
+                write!(f, "SubscriptionParams: No Subscription Details")
+            }
+            Self::DuplicateEventInstanceId(i) => write!(
+                f,
+                "SubscriptionParams: Duplicate XApp Event Instance ID: {}",
+                i
+            ),
+            Self::EmptyEventTriggers(i) => write!(
+                f,
+                "SubscriptionParams: Empty Event Triggers for XApp Event Instance ID: {}",
+                i
+            ),
+            Self::NoActions(i) => write!(
+                f,
+                "SubscriptionParams: No Actions for XApp Event Instance ID: {}",
+                i
+            ),
+            Self::DuplicateActionId(i, a) => write!(
+                f,
+                "SubscriptionParams: Duplicate Action ID {} for XApp Event Instance ID: {}",
+                a, i
+            ),
+            Self::EmptyActionDefinition(i, a) => write!(
+                f,
+                "SubscriptionParams: Empty Action Definition for Action ID {} of XApp Event Instance ID: {}",
+                a, i
+            ),
+            Self::InvalidTimeToWait(i, a, t) => write!(
+                f,
+                "SubscriptionParams: Invalid Time To Wait {:?} for Action ID {} of XApp Event Instance ID: {}",
+                t, a, i
+            ),
+            Self::ClientEndpoint(e) => write!(f, "SubscriptionParams: Client Endpoint: {}", e),
+            Self::UnknownMeid(m) => write!(f, "SubscriptionParams: MEID '{}' not found in RNIB", m),
+            Self::Rnib(e) => write!(f, "SubscriptionParams: RNIB Error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SubscriptionParamsError {}
+
+impl From<SubscriptionParamsError> for XAppError {
+    fn from(e: SubscriptionParamsError) -> Self {
+        XAppError(e.to_string())
+    }
+}
+
+/// Builder for an `ActionToBeSetup`
+#[derive(Debug, Clone)]
+pub struct ActionBuilder {
+    action_id: u32,
+    action_type: ActionType,
+    definition: Vec<i32>,
+    subsequent_action: Option<(SubsequentActionType, Duration)>,
+}
+
+impl ActionBuilder {
+    /// Create a new builder for the Action with the given ID and type.
+    pub fn new(action_id: u32, action_type: ActionType) -> Self {
+        Self {
+            action_id,
+            action_type,
+            definition: vec![],
+            subsequent_action: None,
+        }
+    }
+
+    /// E2SM encoded Action Definition.
+    pub fn definition(mut self, definition: Vec<i32>) -> Self {
+        self.definition = definition;
+        self
+    }
+
+    /// Subsequent Action. `time_to_wait` should be one of the values defined by E2AP (0, 1ms,
+    /// 2ms, 5ms, 10ms, 20ms, 30ms, 40ms, 50ms, 100ms, 200ms, 500ms, 1s, 2s, 5s, 10s, 20s, 60s).
+    pub fn subsequent_action(
+        mut self,
+        subsequent_action_type: SubsequentActionType,
+        time_to_wait: Duration,
+    ) -> Self {
+        self.subsequent_action = Some((subsequent_action_type, time_to_wait));
+        self
+    }
+
+    fn build(self, instance_id: u32) -> Result<ActionToBeSetup, SubscriptionParamsError> {
+        if self.definition.is_empty() {
+            return Err(SubscriptionParamsError::EmptyActionDefinition(
+                instance_id,
+                self.action_id,
+            ));
+        }
+
+        let subsequent_action = match self.subsequent_action {
+            Some((subsequent_action_type, time_to_wait)) => {
+                let ttw = time_to_wait_from_duration(time_to_wait).ok_or(
+                    SubscriptionParamsError::InvalidTimeToWait(
+                        instance_id,
+                        self.action_id,
+                        time_to_wait,
+                    ),
+                )?;
+                Some(Box::new(SubsequentAction::new(subsequent_action_type, ttw)))
+            }
+            None => None,
+        };
+
+        Ok(ActionToBeSetup {
+            action_id: self.action_id,
+            action_type: self.action_type,
+            action_definition: Some(self.definition),
+            subsequent_action,
+        })
+    }
+}
+
+/// Builder for a `SubscriptionDetail`
+#[derive(Debug, Clone)]
+pub struct SubscriptionDetailBuilder {
+    xapp_event_instance_id: u32,
+    event_triggers: Vec<i32>,
+    actions: Vec<ActionBuilder>,
+}
+
+impl SubscriptionDetailBuilder {
+    /// Create a new builder with XApp Event Instance ID and E2SM encoded Event Triggers.
+    pub fn new(xapp_event_instance_id: u32, event_triggers: Vec<i32>) -> Self {
+        Self {
+            xapp_event_instance_id,
+            event_triggers,
+            actions: vec![],
+        }
+    }
+
+    /// Add an Action to be setup.
+    pub fn action(mut self, action: ActionBuilder) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    fn build(self) -> Result<SubscriptionDetail, SubscriptionParamsError> {
+        let instance_id = self.xapp_event_instance_id;
+        if self.event_triggers.is_empty() {
+            return Err(SubscriptionParamsError::EmptyEventTriggers(instance_id));
+        }
+        if self.actions.is_empty() {
+            return Err(SubscriptionParamsError::NoActions(instance_id));
+        }
+
+        let mut action_ids = HashSet::new();
+        let mut actions = vec![];
+        for action in self.actions {
+            if !action_ids.insert(action.action_id) {
+                return Err(SubscriptionParamsError::DuplicateActionId(
+                    instance_id,
+                    action.action_id,
+                ));
+            }
+            actions.push(action.build(instance_id)?);
+        }
+
+        Ok(SubscriptionDetail::new(
+            instance_id,
+            self.event_triggers,
+            actions,
+        ))
+    }
+}
+
+/// Builder for the `SubscriptionParams`
+///
+/// The `client_endpoint` is filled in from the XApp's registration endpoints by
+/// `XApp::build_subscription_params`, unless it is explicitly set.
+#[derive(Debug, Clone)]
+pub struct SubscriptionParamsBuilder {
+    meid: String,
+    ran_function_id: u32,
+    subscription_id: Option<String>,
+    client_endpoint: Option<SubscriptionParamsClientEndpoint>,
+    directives: Option<SubscriptionParamsE2SubscriptionDirectives>,
+    details: Vec<SubscriptionDetailBuilder>,
+}
+
+impl SubscriptionParamsBuilder {
+    /// Create a new builder for Subscription to the RAN Function of the E2 Node (`meid`).
+    pub fn new(meid: &str, ran_function_id: u32) -> Self {
+        Self {
+            meid: meid.to_string(),
+            ran_function_id,
+            subscription_id: None,
+            client_endpoint: None,
+            directives: None,
+            details: vec![],
+        }
+    }
+
+    /// Subscription ID to use (Subscription Manager allocates one if not given).
+    pub fn subscription_id(mut self, subscription_id: &str) -> Self {
+        self.subscription_id = Some(subscription_id.to_string());
+        self
+    }
+
+    /// Explicit Client Endpoint of the XApp.
+    pub fn client_endpoint(mut self, client_endpoint: SubscriptionParamsClientEndpoint) -> Self {
+        self.client_endpoint = Some(client_endpoint);
+        self
+    }
+
+    /// Timeout (in seconds) for the response from the E2 Node.
+    pub fn e2_timeout(mut self, timeout: u32) -> Self {
+        self.directives_mut().e2_timeout_timer_value = Some(timeout);
+        self
+    }
+
+    /// Number of times E2 Subscription Request is retried.
+    pub fn e2_retry_count(mut self, count: u32) -> Self {
+        self.directives_mut().e2_retry_count = Some(count);
+        self
+    }
+
+    /// Whether RMR route from E2 Termination to the XApp is needed.
+    pub fn rmr_routing_needed(mut self, needed: bool) -> Self {
+        self.directives_mut().rmr_routing_needed = Some(needed);
+        self
+    }
+
+    /// Add a Subscription Detail.
+    pub fn subscription_detail(mut self, detail: SubscriptionDetailBuilder) -> Self {
+        self.details.push(detail);
+        self
+    }
+
+    /// Build the `SubscriptionParams` after validating them.
+    ///
+    /// Checks that do not require access to the RIC Platform are performed here. The MEID
+    /// is checked in RNIB by `XApp::build_subscription_params`.
+    pub fn build(self) -> Result<SubscriptionParams, SubscriptionParamsError> {
+        if self.meid.is_empty() {
+            return Err(SubscriptionParamsError::EmptyMeid);
+        }
+
+        let client_endpoint = self.client_endpoint.ok_or_else(|| {
+            SubscriptionParamsError::ClientEndpoint("Client Endpoint not set.".to_string())
+        })?;
+
+        if self.details.is_empty() {
+            return Err(SubscriptionParamsError::NoSubscriptionDetails);
+        }
+
+        let mut instance_ids = HashSet::new();
+        let mut details = vec![];
+        for detail in self.details {
+            if !instance_ids.insert(detail.xapp_event_instance_id) {
+                return Err(SubscriptionParamsError::DuplicateEventInstanceId(
+                    detail.xapp_event_instance_id,
+                ));
+            }
+            details.push(detail.build()?);
+        }
+
+        let mut params =
+            SubscriptionParams::new(client_endpoint, self.meid, self.ran_function_id, details);
+        params.subscription_id = self.subscription_id;
+        params.e2_subscription_directives = self.directives.map(Box::new);
+
+        Ok(params)
+    }
+
+    pub(crate) fn has_client_endpoint(&self) -> bool {
+        self.client_endpoint.is_some()
+    }
+
+    fn directives_mut(&mut self) -> &mut SubscriptionParamsE2SubscriptionDirectives {
+        self.directives
+            .get_or_insert_with(SubscriptionParamsE2SubscriptionDirectives::new)
+    }
+}
+
+// Client Endpoint from the `host:port` HTTP and RMR endpoints used for the registration.
+pub(crate) fn client_endpoint_from(
+    http_endpoint: &str,
+    rmr_endpoint: &str,
+) -> Result<SubscriptionParamsClientEndpoint, SubscriptionParamsError> {
+    let split = |endpoint: &str| -> Result<(String, u32), SubscriptionParamsError> {
+        let (host, port) = endpoint.rsplit_once(':').ok_or_else(|| {
+            SubscriptionParamsError::ClientEndpoint(format!("Invalid Endpoint '{}'", endpoint))
+        })?;
+        let port = port.parse::<u32>().map_err(|e| {
+            SubscriptionParamsError::ClientEndpoint(format!(
+                "Invalid Port in Endpoint '{}': {}",
+                endpoint, e
+            ))
+        })?;
+        Ok((host.to_string(), port))
+    };
+
+    let (host, http_port) = split(http_endpoint)?;
+    let (_, rmr_port) = split(rmr_endpoint)?;
+
+    Ok(SubscriptionParamsClientEndpoint {
+        host: Some(host),
+        http_port: Some(http_port),
+        rmr_port: Some(rmr_port),
+    })
+}
+
+// E2AP allows only a fixed set of values for the Time To Wait.
+fn time_to_wait_from_duration(duration: Duration) -> Option<TimeToWait> {
+    let ttw = match duration.as_micros() {
+        0 => TimeToWait::Zero,
+        1_000 => TimeToWait::W1ms,
+        2_000 => TimeToWait::W2ms,
+        5_000 => TimeToWait::W5ms,
+        10_000 => TimeToWait::W10ms,
+        20_000 => TimeToWait::W20ms,
+        30_000 => TimeToWait::W30ms,
+        40_000 => TimeToWait::W40ms,
+        50_000 => TimeToWait::W50ms,
+        100_000 => TimeToWait::W100ms,
+        200_000 => TimeToWait::W200ms,
+        500_000 => TimeToWait::W500ms,
+        1_000_000 => TimeToWait::W1s,
+        2_000_000 => TimeToWait::W2s,
+        5_000_000 => TimeToWait::W5s,
+        10_000_000 => TimeToWait::W10s,
+        20_000_000 => TimeToWait::W20s,
+        60_000_000 => TimeToWait::W60s,
+        _ => return None,
+    };
+    Some(ttw)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ric_subscriptions::models::{
+        action_to_be_setup::ActionType, subsequent_action::SubsequentActionType,
+        subsequent_action::TimeToWait, SubscriptionParamsClientEndpoint,
+    };
+
+    use super::*;
+
+    fn builder() -> SubscriptionParamsBuilder {
+        SubscriptionParamsBuilder::new("gnb_734_733_b5c67788", 2)
+            .client_endpoint(SubscriptionParamsClientEndpoint::new())
+    }
+
+    #[test]
+    fn test_build_valid_params() {
+        let result = builder()
+            .e2_retry_count(2)
+            .subscription_detail(
+                SubscriptionDetailBuilder::new(1, vec![1, 2, 3])
+                    .action(
+                        ActionBuilder::new(1, ActionType::Report)
+                            .definition(vec![4, 5])
+                            .subsequent_action(
+                                SubsequentActionType::Wait,
+                                Duration::from_millis(10),
+                            ),
+                    )
+                    .action(ActionBuilder::new(2, ActionType::Insert).definition(vec![6])),
+            )
+            .build();
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+
+        let params = result.unwrap();
+        assert_eq!(params.meid, "gnb_734_733_b5c67788");
+        assert_eq!(params.subscription_details.len(), 1);
+        let actions = &params.subscription_details[0].action_to_be_setup_list;
+        assert_eq!(actions.len(), 2);
+        assert_eq!(
+            actions[0].subsequent_action.as_ref().unwrap().time_to_wait,
+            TimeToWait::W10ms
+        );
+        assert_eq!(
+            params.e2_subscription_directives.unwrap().e2_retry_count,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_build_invalid_params() {
+        let action = || ActionBuilder::new(1, ActionType::Report).definition(vec![1]);
+
+        let result = builder().build();
+        assert_eq!(result, Err(SubscriptionParamsError::NoSubscriptionDetails));
+
+        let result = builder()
+            .subscription_detail(SubscriptionDetailBuilder::new(1, vec![]).action(action()))
+            .build();
+        assert_eq!(result, Err(SubscriptionParamsError::EmptyEventTriggers(1)));
+
+        let result = builder()
+            .subscription_detail(
+                SubscriptionDetailBuilder::new(1, vec![1])
+                    .action(action())
+                    .action(action()),
+            )
+            .build();
+        assert_eq!(
+            result,
+            Err(SubscriptionParamsError::DuplicateActionId(1, 1))
+        );
+
+        let result = builder()
+            .subscription_detail(
+                SubscriptionDetailBuilder::new(1, vec![1])
+                    .action(ActionBuilder::new(3, ActionType::Report)),
+            )
+            .build();
+        assert_eq!(
+            result,
+            Err(SubscriptionParamsError::EmptyActionDefinition(1, 3))
+        );
+
+        let result = builder()
+            .subscription_detail(SubscriptionDetailBuilder::new(1, vec![1]).action(
+                action().subsequent_action(SubsequentActionType::Wait, Duration::from_millis(15)),
+            ))
+            .build();
+        assert_eq!(
+            result,
+            Err(SubscriptionParamsError::InvalidTimeToWait(
+                1,
+                1,
+                Duration::from_millis(15)
+            ))
+        );
+
+        let result = SubscriptionParamsBuilder::new("gnb", 2)
+            .subscription_detail(SubscriptionDetailBuilder::new(1, vec![1]).action(action()))
+            .build();
+        assert!(matches!(
+            result,
+            Err(SubscriptionParamsError::ClientEndpoint(_))
+        ));
+    }
+
+    #[test]
+    fn test_client_endpoint_from() {
+        let result = client_endpoint_from(
+            "service-ricxapp-hw-http.ricxapp:8080",
+            "service-ricxapp-hw-rmr.ricxapp:4560",
+        );
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let endpoint = result.unwrap();
+        assert_eq!(
+            endpoint.host.as_deref(),
+            Some("service-ricxapp-hw-http.ricxapp")
+        );
+        assert_eq!(endpoint.http_port, Some(8080));
+        assert_eq!(endpoint.rmr_port, Some(4560));
+
+        assert!(client_endpoint_from("hw-http", "hw-rmr:4560").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Client for the REST API of the Subscription Manager.
+
+use std::time::Duration;
+
+use reqwest::blocking::Client as ReqwestClient;
+
+use ric_subscriptions::models::{SubscriptionData, SubscriptionParams, SubscriptionResponse};
+
+use super::types::{GetAllSubscriptionsError, SubscribeError, UnsubscribeError};
+use crate::xapp::metrics::instrumentation::{Instrumentation, SUBMGR_SERVICE};
+use crate::XAppError;
+
+pub(crate) const SUBSCRIPTION_URL: &str = "ric/v1/subscriptions";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) struct SubscriptionClient {
+    pub(crate) http_client: ReqwestClient,
+    pub(crate) instrumentation: Instrumentation,
+}
+
+impl SubscriptionClient {
+    pub(crate) fn new() -> Result<Self, XAppError> {
+        let http_client = ReqwestClient::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|e| XAppError(format!("Error creating HTTP client: {}", e)))?;
+
+        Ok(Self {
+            http_client,
+            instrumentation: Instrumentation::default(),
+        })
+    }
+
+    pub(crate) fn subscribe(
+        &self,
+        submgr: &str,
+        params: &SubscriptionParams,
+    ) -> Result<SubscriptionResponse, SubscribeError> {
+        let path = format!("{}/{}", submgr, SUBSCRIPTION_URL);
+
+        let json =
+            serde_json::to_string(params).map_err(|e| SubscribeError::Request(e.to_string()))?;
+
+        log::debug!("Sending Subscription Request: '{}' to '{}'", json, path);
+        let request = self
+            .http_client
+            .post(path)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(json);
+        let response =
+            self.instrumentation
+                .send_request(SUBMGR_SERVICE, &self.http_client, request);
+        let response = response.map_err(|e| SubscribeError::Request(e.to_string()))?;
+
+        let status = response.status();
+        let content = response
+            .text()
+            .map_err(|e| SubscribeError::Request(e.to_string()))?;
+
+        log::info!("Subscription Response Code: {}, Body: {}", status, content);
+
+        if status.is_success() {
+            serde_json::from_str(&content).map_err(|e| SubscribeError::Request(e.to_string()))
+        } else {
+            Err(SubscribeError::from_status(status.as_u16(), content))
+        }
+    }
+
+    pub(crate) fn unsubscribe(
+        &self,
+        submgr: &str,
+        subscription_id: &str,
+    ) -> Result<(), UnsubscribeError> {
+        self.unsubscribe_with_timeout(submgr, subscription_id, HTTP_TIMEOUT)
+    }
+
+    pub(crate) fn unsubscribe_with_timeout(
+        &self,
+        submgr: &str,
+        subscription_id: &str,
+        timeout: Duration,
+    ) -> Result<(), UnsubscribeError> {
+        let path = format!("{}/{}/{}", submgr, SUBSCRIPTION_URL, subscription_id);
+
+        log::debug!("Sending Unsubscribe Request to '{}'", path);
+        let response = self.instrumentation.send_request(
+            SUBMGR_SERVICE,
+            &self.http_client,
+            self.http_client.delete(path).timeout(timeout),
+        );
+        let response = response.map_err(|e| UnsubscribeError::Request(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            log::info!(
+                "Unsubscribe for Subscription {} Successful",
+                subscription_id
+            );
+            Ok(())
+        } else {
+            let content = response.text().unwrap_or_default();
+            Err(UnsubscribeError::from_status(status.as_u16(), content))
+        }
+    }
+
+    pub(crate) fn get_all_subscriptions(
+        &self,
+        submgr: &str,
+    ) -> Result<Vec<SubscriptionData>, GetAllSubscriptionsError> {
+        let path = format!("{}/{}", submgr, SUBSCRIPTION_URL);
+
+        log::debug!("Sending Get Subscriptions Request to '{}'", path);
+        let response = self.instrumentation.send_request(
+            SUBMGR_SERVICE,
+            &self.http_client,
+            self.http_client.get(path),
+        );
+        let response = response.map_err(|e| GetAllSubscriptionsError::Request(e.to_string()))?;
+
+        let status = response.status();
+        let content = response
+            .text()
+            .map_err(|e| GetAllSubscriptionsError::Request(e.to_string()))?;
+
+        if status.is_success() {
+            serde_json::from_str(&content)
+                .map_err(|e| GetAllSubscriptionsError::Request(e.to_string()))
+        } else {
+            Err(GetAllSubscriptionsError::from_status(
+                status.as_u16(),
+                content,
+            ))
+        }
+    }
+}
@@ -0,0 +1,427 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! E2 Subscriptions management for XApp
+//!
+//! This module implements APIs for creating and deleting E2 Subscriptions through the
+//! Subscription Manager of the NearRT RIC Platform. The APIs use the models from the
+//! `subscription-api` crate.
+//!
+//! The Subscriptions created through these APIs are tracked by the framework and are deleted when
+//! the XApp stops (unless marked using `keep_subscription_on_stop`). Optionally, they can be
+//! persisted in SDL (`enable_persistent_subscriptions`), so that they can be reconciled with the
+//! Subscription Manager when the XApp restarts.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ric_subscriptions::models::{SubscriptionData, SubscriptionParams, SubscriptionResponse};
+
+use rnib::RnibApi;
+
+use super::{XApp, XAppError};
+
+use self::builder::{client_endpoint_from, SubscriptionParamsBuilder, SubscriptionParamsError};
+use self::notification::PendingSubscription;
+use self::registry::{ReconcileSummary, SubscriptionRecord};
+use self::types::{GetAllSubscriptionsError, SubscribeError, UnsubscribeError};
+
+pub mod builder;
+pub mod notification;
+pub mod registry;
+pub mod types;
+
+pub(crate) mod client;
+
+// Subscriptions of an XApp are persisted in the SDL namespace `<xapp_name>-subscriptions`.
+const SUBSCRIPTIONS_NS_SUFFIX: &str = "subscriptions";
+
+impl XApp {
+    /// Build the `SubscriptionParams` from the `SubscriptionParamsBuilder`
+    ///
+    /// The `client_endpoint` (if not set) is filled in from the HTTP and RMR endpoints of the XApp
+    /// used for the registration (see `enable_registration` and `set_service_endpoints`). In
+    /// addition to the validations performed by `SubscriptionParamsBuilder::build`, the MEID is
+    /// checked to be present in RNIB.
+    pub fn build_subscription_params(
+        &self,
+        builder: SubscriptionParamsBuilder,
+    ) -> Result<SubscriptionParams, SubscriptionParamsError> {
+        let builder = if builder.has_client_endpoint() {
+            builder
+        } else {
+            let (http_endpoint, rmr_endpoint) = self
+                .client_endpoints()
+                .map_err(|e| SubscriptionParamsError::ClientEndpoint(e.to_string()))?;
+            let endpoint = client_endpoint_from(&http_endpoint, &rmr_endpoint)?;
+            builder.client_endpoint(endpoint)
+        };
+
+        let params = builder.build()?;
+
+        let nodeb_ids = self
+            .sdl_client
+            .lock()
+            .expect("Corrupted SDL Client Mutex")
+            .get_nodeb_ids()
+            .map_err(|e| SubscriptionParamsError::Rnib(e.to_string()))?;
+        if !nodeb_ids.iter().any(|id| id.inventory_name == params.meid) {
+            return Err(SubscriptionParamsError::UnknownMeid(params.meid));
+        }
+
+        Ok(params)
+    }
+
+    /// Subscribe to E2 Events with the given `SubscriptionParams`
+    ///
+    /// Returns the `SubscriptionResponse` from the Subscription Manager. The `subscription_id`
+    /// from the response is to be used for `unsubscribe`.
+    ///
+    /// ```ignore
+    ///     let sub_params = SubscriptionParams {
+    ///         ...
+    ///     };
+    ///
+    ///     let response = xapp.subscribe(sub_params)?;
+    ///
+    ///     ...
+    ///
+    ///     xapp.unsubscribe(&response.subscription_id)?;
+    /// ```
+    pub fn subscribe(
+        &self,
+        params: SubscriptionParams,
+    ) -> Result<SubscriptionResponse, SubscribeError> {
+        let client = self
+            .subscription_client
+            .lock()
+            .expect("Corrupted SubscriptionClient Mutex");
+
+        let response = (*client).subscribe(&self.endpoints.submgr, &params)?;
+
+        let mut registry = self
+            .subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex");
+        registry.add(SubscriptionRecord::new(params, response.clone()));
+
+        Ok(response)
+    }
+
+    /// Subscribe to E2 Events and get notified about the final result of the Subscription
+    ///
+    /// Subscription Manager notifies the final result for each of the E2 Event Instances of the
+    /// Subscription asynchronously to the `/ric/v1/subscriptions` end point of the XApp. The
+    /// returned `PendingSubscription` completes, when the results for all the instances in the
+    /// `subscription_details` of the `params` are received. Note: `client_endpoint` in the
+    /// `params` should point to the HTTP end point of the XApp.
+    ///
+    /// ```ignore
+    ///     let pending = xapp.subscribe_with_notification(sub_params)?;
+    ///
+    ///     let result = pending.wait(Duration::from_secs(10))?;
+    ///     if !result.is_success() {
+    ///         ...
+    ///     }
+    /// ```
+    pub fn subscribe_with_notification(
+        &self,
+        params: SubscriptionParams,
+    ) -> Result<PendingSubscription, SubscribeError> {
+        let expected = params
+            .subscription_details
+            .iter()
+            .map(|d| d.xapp_event_instance_id)
+            .collect::<BTreeSet<u32>>();
+
+        self.pending_subscriptions
+            .lock()
+            .expect("Corrupted PendingSubscriptions Mutex")
+            .begin_request();
+
+        let result = self.subscribe(params);
+
+        let mut pending_subscriptions = self
+            .pending_subscriptions
+            .lock()
+            .expect("Corrupted PendingSubscriptions Mutex");
+
+        match result {
+            Ok(response) => Ok(pending_subscriptions.add(response, expected)),
+            Err(e) => {
+                pending_subscriptions.end_request();
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete the Subscription with the given Subscription ID
+    pub fn unsubscribe(&self, subscription_id: &str) -> Result<(), UnsubscribeError> {
+        self.pending_subscriptions
+            .lock()
+            .expect("Corrupted PendingSubscriptions Mutex")
+            .remove(subscription_id);
+
+        let client = self
+            .subscription_client
+            .lock()
+            .expect("Corrupted SubscriptionClient Mutex");
+
+        (*client).unsubscribe(&self.endpoints.submgr, subscription_id)?;
+
+        let _ = self
+            .subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .remove(subscription_id);
+
+        Ok(())
+    }
+
+    /// Get all the Subscriptions from the Subscription Manager
+    pub fn list_subscriptions(&self) -> Result<Vec<SubscriptionData>, GetAllSubscriptionsError> {
+        let client = self
+            .subscription_client
+            .lock()
+            .expect("Corrupted SubscriptionClient Mutex");
+
+        (*client).get_all_subscriptions(&self.endpoints.submgr)
+    }
+
+    /// Keep the Subscription active in the Subscription Manager after the XApp stops
+    ///
+    /// By default, all the Subscriptions created through the framework are deleted when the XApp
+    /// is stopped. Subscriptions that are meant to outlive the XApp process should be marked using
+    /// this function.
+    pub fn keep_subscription_on_stop(&self, subscription_id: &str) -> Result<(), XAppError> {
+        self.subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .set_keep_on_stop(subscription_id, true)
+    }
+
+    /// Set the timeout for each of the Unsubscribe requests sent when the XApp stops.
+    pub fn set_unsubscribe_timeout(&mut self, timeout: Duration) {
+        self.unsubscribe_timeout = timeout;
+    }
+
+    /// Get the Subscriptions created by the XApp through the framework.
+    pub fn tracked_subscriptions(&self) -> Vec<SubscriptionRecord> {
+        self.subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .records()
+    }
+
+    /// Persist the Subscriptions created by the XApp in SDL
+    ///
+    /// The Subscriptions are stored in the SDL namespace `<xapp_name>-subscriptions`. Any
+    /// Subscriptions recorded earlier (eg. before the XApp restarted) are loaded from the SDL and
+    /// are reconciled with the Subscription Manager when the XApp is started. This should be
+    /// called before `start`.
+    pub fn enable_persistent_subscriptions(&mut self) -> Result<(), XAppError> {
+        let namespace = format!(
+            "{}-{}",
+            self.config.current().metadata.xapp_name,
+            SUBSCRIPTIONS_NS_SUFFIX
+        );
+        let sdl = self.sdl_handle();
+
+        self.subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .enable_persistence(&namespace, sdl)
+    }
+
+    /// Reconcile the tracked Subscriptions with the Subscriptions in the Subscription Manager
+    ///
+    /// Tracked Subscriptions that are not known to the Subscription Manager are created again.
+    /// Subscriptions that the Subscription Manager rejects are removed from the tracked
+    /// Subscriptions.
+    pub fn reconcile_subscriptions(&self) -> Result<ReconcileSummary, XAppError> {
+        registry::reconcile(
+            &self.subscription_registry,
+            &self.subscription_client,
+            &self.endpoints.submgr,
+        )
+    }
+
+    // Delete the tracked Subscriptions (except the ones to be kept) when the XApp stops.
+    pub(crate) fn unsubscribe_all_on_stop(&self) -> Result<(), XAppError> {
+        let mut failed = vec![];
+        let subscription_ids = self
+            .subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .records_to_unsubscribe();
+
+        for subscription_id in subscription_ids {
+            self.pending_subscriptions
+                .lock()
+                .expect("Corrupted PendingSubscriptions Mutex")
+                .remove(&subscription_id);
+
+            let result = self
+                .subscription_client
+                .lock()
+                .expect("Corrupted SubscriptionClient Mutex")
+                .unsubscribe_with_timeout(
+                    &self.endpoints.submgr,
+                    &subscription_id,
+                    self.unsubscribe_timeout,
+                );
+
+            match result {
+                Ok(()) => {
+                    let _ = self
+                        .subscription_registry
+                        .lock()
+                        .expect("Corrupted SubscriptionRegistry Mutex")
+                        .remove(&subscription_id);
+                }
+                Err(e) => {
+                    log::error!(
+                        "Error deleting Subscription {} during stop: {}",
+                        subscription_id,
+                        e
+                    );
+                    failed.push(subscription_id);
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(XAppError(format!(
+                "Subscriptions not deleted: {}",
+                failed.join(", ")
+            )))
+        }
+    }
+
+    // Reconcile the persisted Subscriptions in the background when the XApp starts.
+    pub(crate) fn start_subscriptions_reconcile(&mut self) {
+        let persistent = self
+            .subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .is_persistent();
+        if !persistent {
+            return;
+        }
+
+        let registry = Arc::clone(&self.subscription_registry);
+        let client = Arc::clone(&self.subscription_client);
+        let submgr = self.endpoints.submgr.clone();
+        let reconcile_thread =
+            std::thread::spawn(
+                move || match registry::reconcile(&registry, &client, &submgr) {
+                    Ok(summary) => log::info!("Subscriptions reconciled: {:?}", summary),
+                    Err(e) => log::error!("Error reconciling Subscriptions: {}", e),
+                },
+            );
+        let _ = self.reconcile_thread.replace(reconcile_thread);
+    }
+
+    /// Send the Subscription
+    ///
+    /// Deprecated! Use `subscribe` API instead.
+    ///
+    /// The subscription information is to be generated by the XApp and the encoded JSON payload is
+    /// sent to this API. For Example an XApp can call this API as follows
+    ///
+    /// ```ignore
+    ///     let client = SubscriptionParamsClientEndpoint {
+    ///         ...
+    ///     };
+    ///
+    ///     let action = ActionToBeSetup {
+    ///         ...
+    ///     };
+    ///
+    ///     let subscription_detail = SubscriptionDetail {
+    ///         ...
+    ///     };
+    ///
+    ///     let sub_params = SubscriptionParams {
+    ///         ...
+    ///     };
+    ///
+    ///     let json = serde_json::to_string(&sub_params)?;
+    ///
+    ///     let result = self.xapp.xapp_send_subscription(json);
+    ///
+    ///     ...
+    ///
+    /// ```
+    #[deprecated(since = "0.3.0-dev", note = "please use `subscribe` instead.")]
+    pub fn xapp_send_subscription(&self, subscription_json: &str) -> Result<(), XAppError> {
+        let path = format!("{}/{}", self.endpoints.submgr, client::SUBSCRIPTION_URL);
+
+        let req_client = reqwest::blocking::Client::new();
+
+        log::debug!(
+            "Sending Subscription Request: '{}' to '{}'",
+            subscription_json,
+            path
+        );
+        let response = req_client
+            .post(path)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(subscription_json.to_string())
+            .send()
+            .map_err(|e| XAppError(format!("Error sending request: {}", e)))?;
+
+        if response.status().is_success() {
+            log::info!(
+                "Subscription Response Code: {}, Body: {}",
+                response.status(),
+                response.text().unwrap()
+            );
+            Ok(())
+        } else {
+            Err(XAppError(format!("Error : {}", response.status())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::types::{SubscribeError, UnsubscribeError};
+
+    #[test]
+    fn test_subscribe_error_from_status() {
+        let err = SubscribeError::from_status(400, "bad".to_string());
+        assert_eq!(err, SubscribeError::Status400("bad".to_string()));
+
+        let err = SubscribeError::from_status(503, "".to_string());
+        assert_eq!(err, SubscribeError::Status503("".to_string()));
+
+        let err = SubscribeError::from_status(409, "".to_string());
+        assert_eq!(err, SubscribeError::UnknownValue(409, "".to_string()));
+    }
+
+    #[test]
+    fn test_unsubscribe_error_from_status() {
+        let err = UnsubscribeError::from_status(400, "".to_string());
+        assert_eq!(err, UnsubscribeError::Status400("".to_string()));
+
+        let err = UnsubscribeError::from_status(404, "".to_string());
+        assert_eq!(err, UnsubscribeError::UnknownValue(404, "".to_string()));
+    }
+}
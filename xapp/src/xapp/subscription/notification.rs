@@ -0,0 +1,382 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Handling of the Subscription Notifications from the Subscription Manager
+//!
+//! Subscription Manager responds to the REST Subscription requests asynchronously. The final
+//! result for each of the E2 Event Instances of the Subscription is `POST`ed as a
+//! `SubscriptionResponse` to the `/ric/v1/subscriptions` end point of the XApp. These
+//! notifications are correlated with the pending subscriptions using the Subscription ID and once
+//! results for all the instances of a Subscription are received, the `PendingSubscription` for the
+//! Subscription is completed.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+
+use ric_subscriptions::models::{SubscriptionInstance, SubscriptionResponse};
+
+use crate::XAppError;
+
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+const UNMATCHED_TTL: Duration = Duration::from_secs(30);
+const UNMATCHED_CAPACITY: usize = 64;
+
+/// Final result of a Subscription, as notified by the Subscription Manager
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionResult {
+    /// Subscription ID allocated by the Subscription Manager
+    pub subscription_id: String,
+
+    /// Final result for each of the E2 Event Instances of the Subscription
+    pub instances: Vec<SubscriptionInstance>,
+}
+
+impl SubscriptionResult {
+    /// Returns `true` if all the E2 Event Instances of the Subscription were successful.
+    pub fn is_success(&self) -> bool {
+        self.failed_instances().next().is_none()
+    }
+
+    /// Iterator over the E2 Event Instances that failed.
+    pub fn failed_instances(&self) -> impl Iterator<Item = &SubscriptionInstance> {
+        self.instances.iter().filter(|i| instance_failed(i))
+    }
+}
+
+// An instance has failed, if the Subscription Manager reports a non empty error cause for it.
+fn instance_failed(instance: &SubscriptionInstance) -> bool {
+    instance
+        .error_cause
+        .as_ref()
+        .map(|c| !c.is_empty())
+        .unwrap_or(false)
+}
+
+/// A Subscription waiting for the Notifications from the Subscription Manager
+///
+/// `PendingSubscription` is a `Future` that completes with the final `SubscriptionResult`. An XApp
+/// not using `async` code, can use `wait` to wait for the result.
+#[derive(Debug)]
+pub struct PendingSubscription {
+    response: SubscriptionResponse,
+    result_rx: oneshot::Receiver<SubscriptionResult>,
+}
+
+impl PendingSubscription {
+    /// Subscription ID allocated by the Subscription Manager
+    pub fn subscription_id(&self) -> &str {
+        &self.response.subscription_id
+    }
+
+    /// The (initial) response from the Subscription Manager for the Subscription request.
+    pub fn response(&self) -> &SubscriptionResponse {
+        &self.response
+    }
+
+    /// Wait for the Subscription to complete, for at the most `timeout`.
+    ///
+    /// This function blocks and hence should not be called from `async` code.
+    pub fn wait(mut self, timeout: Duration) -> Result<SubscriptionResult, XAppError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.result_rx.try_recv() {
+                Ok(result) => return Ok(result),
+                Err(oneshot::error::TryRecvError::Empty) => {
+                    if Instant::now() >= deadline {
+                        return Err(XAppError(format!(
+                            "Timeout waiting for Subscription {} notification.",
+                            self.response.subscription_id
+                        )));
+                    }
+                    std::thread::sleep(WAIT_POLL_INTERVAL);
+                }
+                Err(oneshot::error::TryRecvError::Closed) => {
+                    return Err(XAppError(format!(
+                        "Subscription {} is no longer pending.",
+                        self.response.subscription_id
+                    )))
+                }
+            }
+        }
+    }
+}
+
+impl Future for PendingSubscription {
+    type Output = Result<SubscriptionResult, XAppError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let subscription_id = self.response.subscription_id.clone();
+        Pin::new(&mut self.result_rx).poll(cx).map(|r| {
+            r.map_err(|_| {
+                XAppError(format!(
+                    "Subscription {} is no longer pending.",
+                    subscription_id
+                ))
+            })
+        })
+    }
+}
+
+// A Subscription for which not all the notifications are received yet.
+struct Pending {
+    expected: BTreeSet<u32>,
+    instances: BTreeMap<u32, SubscriptionInstance>,
+    result_tx: oneshot::Sender<SubscriptionResult>,
+}
+
+// Notifications received before the Subscription request has returned.
+struct Unmatched {
+    received: Instant,
+    instances: Vec<SubscriptionInstance>,
+}
+
+/// Subscriptions waiting for the Notifications from the Subscription Manager.
+#[derive(Default)]
+pub(crate) struct PendingSubscriptions {
+    pending: HashMap<String, Pending>,
+
+    // Subscription requests (with notification) for which the response is not received yet.
+    in_flight: usize,
+
+    // Notifications for unknown Subscriptions are kept only while a Subscription request is in
+    // flight, for at most `UNMATCHED_TTL` and for at most `UNMATCHED_CAPACITY` Subscriptions.
+    unmatched: HashMap<String, Unmatched>,
+}
+
+impl PendingSubscriptions {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begin a Subscription request, whose notifications may arrive before its response.
+    ///
+    /// The request is ended by `add` on success or by `end_request` on failure.
+    pub(crate) fn begin_request(&mut self) {
+        self.in_flight += 1;
+    }
+
+    /// End a Subscription request begun with `begin_request`.
+    pub(crate) fn end_request(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        if self.in_flight == 0 {
+            self.unmatched.clear();
+        }
+    }
+
+    /// Add a Subscription expecting notifications for the given XApp Event Instance IDs.
+    ///
+    /// This ends the Subscription request begun with `begin_request`.
+    pub(crate) fn add(
+        &mut self,
+        response: SubscriptionResponse,
+        expected: BTreeSet<u32>,
+    ) -> PendingSubscription {
+        // Forget about the Subscriptions that no one is waiting for any longer.
+        self.pending.retain(|_, p| !p.result_tx.is_closed());
+
+        let (result_tx, result_rx) = oneshot::channel();
+        let subscription_id = response.subscription_id.clone();
+
+        let pending = Pending {
+            expected,
+            instances: BTreeMap::new(),
+            result_tx,
+        };
+        let _ = self.pending.insert(subscription_id.clone(), pending);
+
+        if let Some(unmatched) = self.unmatched.remove(&subscription_id) {
+            self.update(&subscription_id, unmatched.instances);
+        }
+        self.end_request();
+
+        PendingSubscription {
+            response,
+            result_rx,
+        }
+    }
+
+    /// Process the Notification received from the Subscription Manager.
+    pub(crate) fn notify(&mut self, notification: SubscriptionResponse) {
+        let subscription_id = notification.subscription_id;
+        if self.pending.contains_key(&subscription_id) {
+            self.update(&subscription_id, notification.subscription_instances);
+        } else if self.in_flight > 0 {
+            log::debug!(
+                "Notification for Subscription {} received before the Subscription request returned.",
+                subscription_id
+            );
+            self.unmatched
+                .retain(|_, u| u.received.elapsed() < UNMATCHED_TTL);
+            if !self.unmatched.contains_key(&subscription_id)
+                && self.unmatched.len() >= UNMATCHED_CAPACITY
+            {
+                let oldest = self
+                    .unmatched
+                    .iter()
+                    .min_by_key(|(_, u)| u.received)
+                    .map(|(id, _)| id.clone());
+                if let Some(oldest) = oldest {
+                    let _ = self.unmatched.remove(&oldest);
+                }
+            }
+            self.unmatched
+                .entry(subscription_id)
+                .or_insert_with(|| Unmatched {
+                    received: Instant::now(),
+                    instances: vec![],
+                })
+                .instances
+                .extend(notification.subscription_instances);
+        } else {
+            log::debug!(
+                "Notification for Subscription {} is not awaited, ignoring.",
+                subscription_id
+            );
+        }
+    }
+
+    /// Remove the Subscription (if pending) and any unmatched notifications for it.
+    pub(crate) fn remove(&mut self, subscription_id: &str) {
+        let _ = self.pending.remove(subscription_id);
+        let _ = self.unmatched.remove(subscription_id);
+    }
+
+    fn update(&mut self, subscription_id: &str, instances: Vec<SubscriptionInstance>) {
+        let pending = self.pending.get_mut(subscription_id).unwrap();
+
+        for instance in instances {
+            if instance_failed(&instance) {
+                log::warn!(
+                    "Subscription {} failed for XApp Event Instance {}: {:?}, Timeout: {:?}",
+                    subscription_id,
+                    instance.xapp_event_instance_id,
+                    instance.error_cause,
+                    instance.timeout_type
+                );
+            }
+            let _ = pending
+                .instances
+                .insert(instance.xapp_event_instance_id, instance);
+        }
+
+        let complete = pending
+            .expected
+            .iter()
+            .all(|id| pending.instances.contains_key(id));
+        if complete {
+            let pending = self.pending.remove(subscription_id).unwrap();
+            let result = SubscriptionResult {
+                subscription_id: subscription_id.to_string(),
+                instances: pending.instances.into_values().collect(),
+            };
+            let _ = pending.result_tx.send(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    use ric_subscriptions::models::{SubscriptionInstance, SubscriptionResponse};
+
+    fn notification(id: &str, instances: Vec<SubscriptionInstance>) -> SubscriptionResponse {
+        SubscriptionResponse::new(id.to_string(), instances)
+    }
+
+    #[test]
+    fn test_pending_subscription_completes_on_all_instances() {
+        let mut subscriptions = super::PendingSubscriptions::new();
+        let pending = subscriptions.add(
+            notification("sub-1", vec![]),
+            BTreeSet::from([1_u32, 2_u32]),
+        );
+
+        subscriptions.notify(notification(
+            "sub-1",
+            vec![SubscriptionInstance::new(1, 100)],
+        ));
+        assert!(subscriptions.pending.contains_key("sub-1"));
+
+        let mut failed = SubscriptionInstance::new(2, 0);
+        failed.error_cause = Some("E2 Node rejected".to_string());
+        subscriptions.notify(notification("sub-1", vec![failed]));
+        assert!(subscriptions.pending.is_empty());
+
+        let result = pending.wait(Duration::from_millis(100));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let result = result.unwrap();
+        assert!(!result.is_success());
+        assert_eq!(result.failed_instances().count(), 1);
+    }
+
+    #[test]
+    fn test_notification_before_pending_subscription() {
+        let mut subscriptions = super::PendingSubscriptions::new();
+        subscriptions.begin_request();
+
+        subscriptions.notify(notification(
+            "sub-2",
+            vec![SubscriptionInstance::new(1, 100)],
+        ));
+
+        let pending = subscriptions.add(notification("sub-2", vec![]), BTreeSet::from([1_u32]));
+        assert!(subscriptions.unmatched.is_empty());
+
+        let result = pending.wait(Duration::from_millis(100));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        assert!(result.unwrap().is_success());
+    }
+
+    #[test]
+    fn test_unmatched_notifications_are_bounded() {
+        let mut subscriptions = super::PendingSubscriptions::new();
+
+        // Not buffered when no Subscription request is in flight.
+        subscriptions.notify(notification(
+            "unknown",
+            vec![SubscriptionInstance::new(1, 100)],
+        ));
+        assert!(subscriptions.unmatched.is_empty());
+
+        subscriptions.begin_request();
+        for i in 0..(super::UNMATCHED_CAPACITY + 10) {
+            subscriptions.notify(notification(
+                &format!("unknown-{}", i),
+                vec![SubscriptionInstance::new(1, 100)],
+            ));
+        }
+        assert_eq!(subscriptions.unmatched.len(), super::UNMATCHED_CAPACITY);
+
+        subscriptions.end_request();
+        assert!(subscriptions.unmatched.is_empty());
+    }
+
+    #[test]
+    fn test_pending_subscription_timeout() {
+        let mut subscriptions = super::PendingSubscriptions::new();
+        let pending = subscriptions.add(notification("sub-3", vec![]), BTreeSet::from([1_u32]));
+
+        let result = pending.wait(Duration::from_millis(20));
+        assert!(result.is_err());
+    }
+}
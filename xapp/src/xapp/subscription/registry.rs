@@ -0,0 +1,495 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Registry of the Subscriptions created through the framework
+//!
+//! The framework keeps track of the Subscriptions created by the XApp (`SubscriptionParams`,
+//! Subscription ID and the E2 Event Instances). Optionally, the registry is persisted in SDL, so
+//! that after a restart of the XApp, the recorded Subscriptions can be reconciled with the
+//! Subscriptions known to the Subscription Manager. The recorded Subscriptions that are not known
+//! to the Subscription Manager are created again and the records of the Subscriptions that cannot
+//! be created again are removed.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use ric_subscriptions::models::{SubscriptionInstance, SubscriptionParams, SubscriptionResponse};
+use sdl::{DataMap, SdlStorageApi};
+
+use crate::XAppError;
+
+use super::client::SubscriptionClient;
+use super::types::SubscribeError;
+
+// SDL group containing the IDs of all the recorded Subscriptions.
+const SUBSCRIPTION_IDS_GROUP: &str = "subscription_ids";
+
+/// A Subscription created through the framework
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionRecord {
+    /// Subscription ID allocated by the Subscription Manager
+    pub subscription_id: String,
+
+    /// Parameters used for creating the Subscription
+    pub params: SubscriptionParams,
+
+    /// E2 Event Instances of the Subscription
+    pub instances: Vec<SubscriptionInstance>,
+
+    /// Subscription is not deleted when the XApp stops
+    #[serde(default)]
+    pub keep_on_stop: bool,
+}
+
+impl SubscriptionRecord {
+    pub(crate) fn new(params: SubscriptionParams, response: SubscriptionResponse) -> Self {
+        Self {
+            subscription_id: response.subscription_id,
+            params,
+            instances: response.subscription_instances,
+            keep_on_stop: false,
+        }
+    }
+}
+
+/// Summary of reconciling the recorded Subscriptions with the Subscription Manager
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconcileSummary {
+    /// Subscriptions that are active in the Subscription Manager
+    pub active: Vec<String>,
+
+    /// Subscriptions that were created again (Old Subscription ID, New Subscription ID)
+    pub resubscribed: Vec<(String, String)>,
+
+    /// Stale Subscriptions that were removed from the registry
+    pub removed: Vec<String>,
+}
+
+pub(crate) type SdlHandle = Arc<Mutex<dyn SdlStorageApi + Send>>;
+
+// SDL Namespace and Handle used for persisting the Subscriptions
+struct SdlStore {
+    namespace: String,
+    sdl: SdlHandle,
+}
+
+impl SdlStore {
+    fn load(&self) -> Result<Vec<SubscriptionRecord>, XAppError> {
+        let mut sdl = self.sdl.lock().expect("Corrupted SDL Mutex");
+
+        let ids = sdl
+            .get_members(&self.namespace, SUBSCRIPTION_IDS_GROUP)
+            .map_err(|e| XAppError(e.to_string()))?
+            .into_iter()
+            .map(|id| String::from_utf8_lossy(&id).to_string())
+            .collect::<BTreeSet<String>>();
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let values = sdl
+            .get(&self.namespace, &ids)
+            .map_err(|e| XAppError(e.to_string()))?;
+
+        let mut records = vec![];
+        for id in ids {
+            match values.get(&id).map(|v| serde_json::from_slice(v)) {
+                Some(Ok(record)) => records.push(record),
+                _ => {
+                    log::warn!("Removing invalid record for Subscription {} from SDL.", id);
+                    let _ = sdl.delete_member(&self.namespace, SUBSCRIPTION_IDS_GROUP, &id.into());
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    fn save(&self, record: &SubscriptionRecord) -> Result<(), XAppError> {
+        let json = serde_json::to_vec(record).map_err(|e| XAppError(e.to_string()))?;
+        let mut data = DataMap::new();
+        let _ = data.insert(record.subscription_id.clone(), json);
+
+        let mut sdl = self.sdl.lock().expect("Corrupted SDL Mutex");
+        sdl.set(&self.namespace, &data)
+            .map_err(|e| XAppError(e.to_string()))?;
+        sdl.add_member(
+            &self.namespace,
+            SUBSCRIPTION_IDS_GROUP,
+            &record.subscription_id.clone().into(),
+        )
+        .map_err(|e| XAppError(e.to_string()))
+    }
+
+    fn delete(&self, subscription_id: &str) -> Result<(), XAppError> {
+        let mut sdl = self.sdl.lock().expect("Corrupted SDL Mutex");
+        sdl.delete_member(
+            &self.namespace,
+            SUBSCRIPTION_IDS_GROUP,
+            &subscription_id.to_string().into(),
+        )
+        .map_err(|e| XAppError(e.to_string()))?;
+        sdl.delete(
+            &self.namespace,
+            &BTreeSet::from([subscription_id.to_string()]),
+        )
+        .map_err(|e| XAppError(e.to_string()))
+    }
+}
+
+/// Registry of the Subscriptions created through the framework.
+#[derive(Default)]
+pub(crate) struct SubscriptionRegistry {
+    records: HashMap<String, SubscriptionRecord>,
+    store: Option<SdlStore>,
+}
+
+impl SubscriptionRegistry {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist the registry in the given SDL namespace, loading any existing records from it.
+    pub(crate) fn enable_persistence(
+        &mut self,
+        namespace: &str,
+        sdl: SdlHandle,
+    ) -> Result<(), XAppError> {
+        let store = SdlStore {
+            namespace: namespace.to_string(),
+            sdl,
+        };
+
+        for record in store.load()? {
+            log::debug!("Loaded Subscription {} from SDL.", record.subscription_id);
+            let _ = self.records.insert(record.subscription_id.clone(), record);
+        }
+        let _ = self.store.replace(store);
+
+        Ok(())
+    }
+
+    pub(crate) fn is_persistent(&self) -> bool {
+        self.store.is_some()
+    }
+
+    pub(crate) fn add(&mut self, record: SubscriptionRecord) {
+        if let Some(ref store) = self.store {
+            if let Err(e) = store.save(&record) {
+                log::warn!(
+                    "Error saving Subscription {} to SDL: {}",
+                    record.subscription_id,
+                    e
+                );
+            }
+        }
+        let _ = self.records.insert(record.subscription_id.clone(), record);
+    }
+
+    pub(crate) fn remove(&mut self, subscription_id: &str) -> Option<SubscriptionRecord> {
+        if let Some(ref store) = self.store {
+            if let Err(e) = store.delete(subscription_id) {
+                log::warn!(
+                    "Error deleting Subscription {} from SDL: {}",
+                    subscription_id,
+                    e
+                );
+            }
+        }
+        self.records.remove(subscription_id)
+    }
+
+    pub(crate) fn records(&self) -> Vec<SubscriptionRecord> {
+        self.records.values().cloned().collect()
+    }
+
+    /// Mark the Subscription to be kept (or not) when the XApp stops.
+    pub(crate) fn set_keep_on_stop(
+        &mut self,
+        subscription_id: &str,
+        keep_on_stop: bool,
+    ) -> Result<(), XAppError> {
+        let record = self.records.get_mut(subscription_id).ok_or_else(|| {
+            XAppError(format!("Subscription {} is not tracked.", subscription_id))
+        })?;
+        record.keep_on_stop = keep_on_stop;
+
+        if let Some(ref store) = self.store {
+            store.save(record)?;
+        }
+        Ok(())
+    }
+
+    /// Subscriptions that are to be deleted when the XApp stops.
+    pub(crate) fn records_to_unsubscribe(&self) -> Vec<String> {
+        self.records
+            .values()
+            .filter(|r| !r.keep_on_stop)
+            .map(|r| r.subscription_id.clone())
+            .collect()
+    }
+}
+
+/// Reconcile the Subscriptions in the `registry` with the Subscriptions in the Subscription
+/// Manager.
+///
+/// The Subscriptions not known to the Subscription Manager are created again. If the
+/// Subscription Manager rejects the parameters of a Subscription, the record for the Subscription
+/// is removed. The `registry` is not locked while the requests are made to the Subscription
+/// Manager.
+pub(crate) fn reconcile(
+    registry: &Mutex<SubscriptionRegistry>,
+    client: &Mutex<SubscriptionClient>,
+    submgr: &str,
+) -> Result<ReconcileSummary, XAppError> {
+    // `XApp::subscribe` holds the client lock till the Subscription is recorded. Holding it while
+    // getting the Subscriptions and the records makes sure that a Subscription created in between
+    // is not taken as missing in the Subscription Manager (and created again).
+    let (subscriptions, records) = {
+        let client = client.lock().expect("Corrupted SubscriptionClient Mutex");
+        let subscriptions = client.get_all_subscriptions(submgr)?;
+        let records = registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .records();
+        (subscriptions, records)
+    };
+
+    let active = subscriptions
+        .into_iter()
+        .filter_map(|s| s.subscription_id.map(|id| id.to_string()))
+        .collect::<BTreeSet<String>>();
+
+    let mut summary = ReconcileSummary::default();
+    for record in records {
+        if active.contains(&record.subscription_id) {
+            summary.active.push(record.subscription_id);
+            continue;
+        }
+
+        log::info!(
+            "Subscription {} not found in Subscription Manager, subscribing again.",
+            record.subscription_id
+        );
+        let result = client
+            .lock()
+            .expect("Corrupted SubscriptionClient Mutex")
+            .subscribe(submgr, &record.params);
+
+        let mut registry = registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex");
+        match result {
+            Ok(response) => {
+                let _ = registry.remove(&record.subscription_id);
+                let mut new_record = SubscriptionRecord::new(record.params, response);
+                new_record.keep_on_stop = record.keep_on_stop;
+                summary
+                    .resubscribed
+                    .push((record.subscription_id, new_record.subscription_id.clone()));
+                registry.add(new_record);
+            }
+            Err(SubscribeError::Status400(e)) => {
+                log::warn!(
+                    "Subscription {} rejected by Subscription Manager: {}, removing it.",
+                    record.subscription_id,
+                    e
+                );
+                let _ = registry.remove(&record.subscription_id);
+                summary.removed.push(record.subscription_id);
+            }
+            Err(e) => {
+                // Keep the record, we will try again next time.
+                log::error!(
+                    "Error subscribing again Subscription {}: {}",
+                    record.subscription_id,
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use ric_subscriptions::models::{
+        SubscriptionParams, SubscriptionParamsClientEndpoint, SubscriptionResponse,
+    };
+
+    use sdl::SdlStorageApi;
+
+    use xapp_testkit::FakeServer;
+
+    use crate::xapp::test_utils::MemoryStorage;
+
+    fn record(id: &str) -> super::SubscriptionRecord {
+        let params = SubscriptionParams::new(
+            SubscriptionParamsClientEndpoint::new(),
+            "gnb_1".to_string(),
+            1,
+            vec![],
+        );
+        super::SubscriptionRecord::new(params, SubscriptionResponse::new(id.to_string(), vec![]))
+    }
+
+    fn xapp_record(id: &str, meid: &str) -> super::SubscriptionRecord {
+        let mut record = record(id);
+        record.params.client_endpoint.host = Some("xapp-host".to_string());
+        record.params.meid = meid.to_string();
+        record
+    }
+
+    // A Subscription Manager that has the Subscriptions "1" (of the XApp), "5" (on the same host,
+    // eg. of another replica) and "9" (of another XApp). Rejects the Subscriptions for "gnb_bad".
+    fn submgr_stub() -> FakeServer {
+        use axum::http::StatusCode;
+        use axum::routing::{delete, get};
+        use axum::{Json, Router};
+
+        let router = Router::new()
+            .route(
+                "/ric/v1/subscriptions",
+                get(|| async {
+                    Json(serde_json::json!([
+                        {"SubscriptionId": 1, "ClientEndpoint": ["xapp-host:8080"]},
+                        {"SubscriptionId": 5, "ClientEndpoint": ["xapp-host:4560"]},
+                        {"SubscriptionId": 9, "ClientEndpoint": ["other-host:8080"]},
+                    ]))
+                })
+                .post(|Json(params): Json<SubscriptionParams>| async move {
+                    if params.meid == "gnb_bad" {
+                        return Err(StatusCode::BAD_REQUEST);
+                    }
+                    Ok((
+                        StatusCode::CREATED,
+                        Json(SubscriptionResponse::new("7".to_string(), vec![])),
+                    ))
+                }),
+            )
+            .route(
+                "/ric/v1/subscriptions/:id",
+                delete(|| async { StatusCode::NO_CONTENT }),
+            );
+
+        FakeServer::start(router).unwrap()
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let submgr = submgr_stub();
+
+        let mut registry = super::SubscriptionRegistry::new();
+        registry.add(xapp_record("1", "gnb_1"));
+        registry.add(xapp_record("2", "gnb_1"));
+        registry.add(xapp_record("3", "gnb_bad"));
+        let registry = Mutex::new(registry);
+        let client = Mutex::new(super::SubscriptionClient::new().unwrap());
+
+        let summary = super::reconcile(&registry, &client, submgr.url());
+        assert!(summary.is_ok(), "{}", summary.err().unwrap());
+        let summary = summary.unwrap();
+
+        assert_eq!(summary.active, vec!["1".to_string()]);
+        assert_eq!(
+            summary.resubscribed,
+            vec![("2".to_string(), "7".to_string())]
+        );
+        assert_eq!(summary.removed, vec!["3".to_string()]);
+
+        // Subscriptions that are not recorded are left alone.
+        assert!(!submgr.requests().iter().any(|r| r.method == "DELETE"));
+
+        let mut ids = registry
+            .lock()
+            .unwrap()
+            .records()
+            .into_iter()
+            .map(|r| r.subscription_id)
+            .collect::<Vec<String>>();
+        ids.sort();
+        assert_eq!(ids, vec!["1".to_string(), "7".to_string()]);
+    }
+
+    #[test]
+    fn test_registry_persisted_in_sdl() {
+        let sdl: super::SdlHandle = Arc::new(Mutex::new(MemoryStorage::default()));
+
+        let mut registry = super::SubscriptionRegistry::new();
+        let result = registry.enable_persistence("tests-subscriptions", Arc::clone(&sdl));
+        assert!(result.is_ok());
+
+        registry.add(record("1"));
+        registry.add(record("2"));
+        let _ = registry.remove("1");
+
+        // A new registry (eg. after restart) should load the records from the SDL.
+        let mut registry = super::SubscriptionRegistry::new();
+        let result = registry.enable_persistence("tests-subscriptions", sdl);
+        assert!(result.is_ok());
+
+        let records = registry.records();
+        assert_eq!(records, vec![record("2")]);
+    }
+
+    #[test]
+    fn test_registry_keep_on_stop() {
+        let sdl: super::SdlHandle = Arc::new(Mutex::new(MemoryStorage::default()));
+
+        let mut registry = super::SubscriptionRegistry::new();
+        let result = registry.enable_persistence("tests-subscriptions", Arc::clone(&sdl));
+        assert!(result.is_ok());
+
+        registry.add(record("1"));
+        registry.add(record("2"));
+        assert!(registry.set_keep_on_stop("2", true).is_ok());
+        assert!(registry.set_keep_on_stop("3", true).is_err());
+
+        assert_eq!(registry.records_to_unsubscribe(), vec!["1".to_string()]);
+
+        // Should be persisted as well.
+        let mut registry = super::SubscriptionRegistry::new();
+        let result = registry.enable_persistence("tests-subscriptions", sdl);
+        assert!(result.is_ok());
+        assert_eq!(registry.records_to_unsubscribe(), vec!["1".to_string()]);
+    }
+
+    #[test]
+    fn test_registry_invalid_records_removed() {
+        let mut storage = MemoryStorage::default();
+        let _ = storage.add_member("ns", super::SUBSCRIPTION_IDS_GROUP, &b"3".to_vec());
+        let _ = storage
+            .data
+            .insert("3".to_string(), b"not a subscription".to_vec());
+        let sdl: super::SdlHandle = Arc::new(Mutex::new(storage));
+
+        let mut registry = super::SubscriptionRegistry::new();
+        let result = registry.enable_persistence("ns", Arc::clone(&sdl));
+        assert!(result.is_ok());
+        assert!(registry.records().is_empty());
+
+        let members = sdl
+            .lock()
+            .unwrap()
+            .get_members("ns", super::SUBSCRIPTION_IDS_GROUP);
+        assert!(members.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Errors returned by the Subscription APIs of the XApp
+//!
+//! These mirror the typed errors (`SubscribeError`, `UnsubscribeError` and
+//! `GetAllSubscriptionsError`) of the Subscription Manager REST API. In addition, errors in
+//! sending the request or decoding the response are reported as `Request` errors.
+
+use crate::XAppError;
+
+/// Error returned by `XApp::subscribe`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscribeError {
+    /// Invalid Subscription Parameters
+    Status400(String),
+
+    /// Subscription Manager returned Not Found
+    Status404(String),
+
+    /// Internal Error in the Subscription Manager
+    Status500(String),
+
+    /// Subscription Manager is unavailable
+    Status503(String),
+
+    /// Any other Error Status returned by the Subscription Manager
+    UnknownValue(u16, String),
+
+    /// Error in sending the Request or decoding the Response
+    Request(String),
+}
+
+impl SubscribeError {
+    pub(crate) fn from_status(status: u16, content: String) -> Self {
+        match status {
+            400 => Self::Status400(content),
+            404 => Self::Status404(content),
+            500 => Self::Status500(content),
+            503 => Self::Status503(content),
+            _ => Self::UnknownValue(status, content),
+        }
+    }
+}
+
+impl std::fmt::Display for SubscribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status400(c) => write!(f, "Subscribe: Invalid Input (400): {}", c),
+            Self::Status404(c) => write!(f, "Subscribe: Not Found (404): {}", c),
+            Self::Status500(c) => write!(f, "Subscribe: Internal Error (500): {}", c),
+            Self::Status503(c) => write!(f, "Subscribe: Service Unavailable (503): {}", c),
+            Self::UnknownValue(s, c) => write!(f, "Subscribe: Error ({}): {}", s, c),
+            Self::Request(e) => write!(f, "Subscribe: Request Error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SubscribeError {}
+
+impl From<SubscribeError> for XAppError {
+    fn from(e: SubscribeError) -> Self {
+        XAppError(e.to_string())
+    }
+}
+
+/// Error returned by `XApp::unsubscribe`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnsubscribeError {
+    /// Invalid Subscription ID
+    Status400(String),
+
+    /// Internal Error in the Subscription Manager
+    Status500(String),
+
+    /// Any other Error Status returned by the Subscription Manager
+    UnknownValue(u16, String),
+
+    /// Error in sending the Request
+    Request(String),
+}
+
+impl UnsubscribeError {
+    pub(crate) fn from_status(status: u16, content: String) -> Self {
+        match status {
+            400 => Self::Status400(content),
+            500 => Self::Status500(content),
+            _ => Self::UnknownValue(status, content),
+        }
+    }
+}
+
+impl std::fmt::Display for UnsubscribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status400(c) => write!(f, "Unsubscribe: Invalid Subscription ID (400): {}", c),
+            Self::Status500(c) => write!(f, "Unsubscribe: Internal Error (500): {}", c),
+            Self::UnknownValue(s, c) => write!(f, "Unsubscribe: Error ({}): {}", s, c),
+            Self::Request(e) => write!(f, "Unsubscribe: Request Error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for UnsubscribeError {}
+
+impl From<UnsubscribeError> for XAppError {
+    fn from(e: UnsubscribeError) -> Self {
+        XAppError(e.to_string())
+    }
+}
+
+/// Error returned by `XApp::list_subscriptions`
+#[derive(Debug, Clone, PartialEq)]
+pub enum GetAllSubscriptionsError {
+    /// Internal Error in the Subscription Manager
+    Status500(String),
+
+    /// Any other Error Status returned by the Subscription Manager
+    UnknownValue(u16, String),
+
+    /// Error in sending the Request or decoding the Response
+    Request(String),
+}
+
+impl GetAllSubscriptionsError {
+    pub(crate) fn from_status(status: u16, content: String) -> Self {
+        match status {
+            500 => Self::Status500(content),
+            _ => Self::UnknownValue(status, content),
+        }
+    }
+}
+
+impl std::fmt::Display for GetAllSubscriptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status500(c) => write!(f, "Get Subscriptions: Internal Error (500): {}", c),
+            Self::UnknownValue(s, c) => write!(f, "Get Subscriptions: Error ({}): {}", s, c),
+            Self::Request(e) => write!(f, "Get Subscriptions: Request Error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GetAllSubscriptionsError {}
+
+impl From<GetAllSubscriptionsError> for XAppError {
+    fn from(e: GetAllSubscriptionsError) -> Self {
+        XAppError(e.to_string())
+    }
+}
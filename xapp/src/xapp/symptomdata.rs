@@ -0,0 +1,329 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Symptom Data of the XApp
+//!
+//! `/ric/v1/symptomdata` returns a `tar` archive with the diagnostics of the XApp, for
+//! troubleshooting. The archive contains -
+//!
+//! - `config.json`: The current configuration of the XApp.
+//! - `metrics.txt`: Snapshot of the metrics (if enabled), in the OpenMetrics text format.
+//! - `logs.txt`: The recent log lines (if the RIC logging is enabled).
+//! - `alarms.json`: The alarms raised by the XApp, that are not yet cleared.
+//! - `registration.json`: Registration of the XApp with the App Manager.
+//! - `subscriptions.json`: The Subscriptions created by the XApp through the framework.
+//! - `health.json`: The results of the health checks and the heartbeats of the threads.
+//! - `app/<name>`: The files added by the XApp using `add_symptomdata_hook`.
+//!
+//! If a hook of the XApp fails, its error is listed in `errors.txt` instead.
+//!
+//! ```ignore
+//!     xapp.add_symptomdata_hook("cells.json", move || {
+//!         serde_json::to_vec_pretty(&cells.lock().unwrap().list()).map_err(|e| e.to_string())
+//!     })?;
+//! ```
+
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use registration_api::models::RegisterRequest;
+
+use super::alarms::client::AlarmClient;
+use super::config::store::ConfigStore;
+use super::health::HealthMonitor;
+use super::logging;
+use super::metrics::MetricsRegistry;
+use super::subscription::registry::SubscriptionRegistry;
+use super::XApp;
+use crate::XAppError;
+
+// All the files are under this directory in the archive.
+const ARCHIVE_ROOT: &str = "symptomdata";
+
+/// A Symptom Data Hook added by the XApp
+///
+/// Returns the contents of the file added to the symptom data, or an error describing why they
+/// could not be collected.
+pub type SymptomDataHook = Box<dyn Fn() -> Result<Vec<u8>, String> + Send + Sync>;
+
+pub(crate) type SymptomDataHooks = Arc<Mutex<Vec<(String, SymptomDataHook)>>>;
+
+// Symptom Data: Shared with the Web Server, collects the diagnostics from all the parts of the
+// XApp.
+pub(crate) struct SymptomData {
+    xapp_name: String,
+    config: Arc<ConfigStore>,
+    metrics: Option<Arc<Mutex<MetricsRegistry>>>,
+    health: Arc<Mutex<HealthMonitor>>,
+    alarm_client: Arc<Mutex<AlarmClient>>,
+    subscription_registry: Arc<Mutex<SubscriptionRegistry>>,
+    registered: Arc<AtomicBool>,
+    registration_request: Option<RegisterRequest>,
+    hooks: SymptomDataHooks,
+}
+
+impl SymptomData {
+    // Name of the archive file, as sent in the `Content-Disposition`.
+    pub(crate) fn file_name(&self) -> String {
+        format!("{}-{}-{}.tar", ARCHIVE_ROOT, self.xapp_name, now_secs())
+    }
+
+    // Collects the Symptom Data in a `tar` archive. Health checks and the hooks of the XApp may
+    // block, hence this should not be called on the async runtime.
+    pub(crate) fn collect(&self) -> Result<Vec<u8>, XAppError> {
+        let mut archive = Archive::new();
+
+        archive.append_json("config.json", &*self.config.current())?;
+
+        if let Some(ref metrics) = self.metrics {
+            let metrics = metrics
+                .lock()
+                .expect("Corrupted MetricsRegistry Mutex")
+                .encode();
+            archive.append("metrics.txt", metrics.as_bytes())?;
+        }
+
+        let mut logs = logging::recent_lines().join("\n");
+        logs.push('\n');
+        archive.append("logs.txt", logs.as_bytes())?;
+
+        let alarms = self
+            .alarm_client
+            .lock()
+            .expect("Corrupted AlarmClient Mutex")
+            .active_alarms();
+        archive.append_json("alarms.json", &alarms)?;
+
+        archive.append_json(
+            "registration.json",
+            &serde_json::json!({
+                "registered": self.registered.load(Ordering::SeqCst),
+                "request": self.registration_request,
+            }),
+        )?;
+
+        let subscriptions = self
+            .subscription_registry
+            .lock()
+            .expect("Corrupted SubscriptionRegistry Mutex")
+            .records();
+        archive.append_json("subscriptions.json", &subscriptions)?;
+
+        let health = self
+            .health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .clone();
+        let health = health.report();
+        archive.append_json("health.json", &health)?;
+
+        let mut errors = String::new();
+        for (name, hook) in self
+            .hooks
+            .lock()
+            .expect("Corrupted SymptomDataHooks Mutex")
+            .iter()
+        {
+            let result = std::panic::catch_unwind(AssertUnwindSafe(hook))
+                .unwrap_or_else(|_| Err("Symptom Data Hook panicked".to_string()));
+            match result {
+                Ok(data) => archive.append(&format!("app/{}", name), &data)?,
+                Err(e) => {
+                    log::warn!("Symptom Data Hook '{}' failed: {}", name, e);
+                    errors.push_str(&format!("app/{}: {}\n", name, e));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            archive.append("errors.txt", errors.as_bytes())?;
+        }
+
+        archive.finish()
+    }
+}
+
+// `tar` archive of the Symptom Data, built in memory.
+struct Archive {
+    builder: tar::Builder<Vec<u8>>,
+    mtime: u64,
+}
+
+impl Archive {
+    fn new() -> Self {
+        Self {
+            builder: tar::Builder::new(vec![]),
+            mtime: now_secs(),
+        }
+    }
+
+    fn append(&mut self, path: &str, data: &[u8]) -> Result<(), XAppError> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(self.mtime);
+        header.set_cksum();
+
+        self.builder
+            .append_data(&mut header, format!("{}/{}", ARCHIVE_ROOT, path), data)
+            .map_err(|e| {
+                XAppError(format!(
+                    "Error adding '{}' to the Symptom Data: {}",
+                    path, e
+                ))
+            })
+    }
+
+    fn append_json<T: serde::Serialize + ?Sized>(
+        &mut self,
+        path: &str,
+        value: &T,
+    ) -> Result<(), XAppError> {
+        let data = serde_json::to_vec_pretty(value)
+            .map_err(|e| XAppError(format!("serde_json: {}", e)))?;
+        self.append(path, &data)
+    }
+
+    fn finish(self) -> Result<Vec<u8>, XAppError> {
+        self.builder
+            .into_inner()
+            .map_err(|e| XAppError(format!("Error creating the Symptom Data archive: {}", e)))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+impl XApp {
+    /// Add a file to the Symptom Data of the XApp
+    ///
+    /// The hook is called every time the symptom data is collected, and the data returned by it is
+    /// added as `app/<name>` to the archive. The name should be a plain file name (without any
+    /// `/`), unique across the hooks.
+    pub fn add_symptomdata_hook<F>(&mut self, name: &str, hook: F) -> Result<(), XAppError>
+    where
+        F: Fn() -> Result<Vec<u8>, String> + Send + Sync + 'static,
+    {
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(XAppError(format!(
+                "Invalid Symptom Data file name: '{}'.",
+                name
+            )));
+        }
+
+        let mut hooks = self
+            .symptomdata_hooks
+            .lock()
+            .expect("Corrupted SymptomDataHooks Mutex");
+        if hooks.iter().any(|(existing, _)| existing == name) {
+            return Err(XAppError(format!(
+                "Symptom Data Hook '{}' already added.",
+                name
+            )));
+        }
+        hooks.push((name.to_string(), Box::new(hook)));
+
+        Ok(())
+    }
+
+    // Symptom Data of the XApp, for the Web Server.
+    pub(crate) fn symptom_data(&self) -> SymptomData {
+        SymptomData {
+            xapp_name: self.config.current().metadata.xapp_name.clone(),
+            config: Arc::clone(&self.config),
+            metrics: self.metrics.as_ref().map(Arc::clone),
+            health: Arc::clone(&self.health),
+            alarm_client: Arc::clone(&self.alarm_client),
+            subscription_registry: Arc::clone(&self.subscription_registry),
+            registered: Arc::clone(&self.app_is_registered),
+            registration_request: self.registration_request.clone(),
+            hooks: Arc::clone(&self.symptomdata_hooks),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::Read;
+
+    use crate::xapp::test_utils::test_xapp;
+
+    #[test]
+    fn test_collect_symptomdata() {
+        let mut xapp = test_xapp();
+
+        let result = xapp.add_symptomdata_hook("cells.json", || Ok(b"[\"cell-1\"]".to_vec()));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let result = xapp.add_symptomdata_hook("model.bin", || Err("Model not loaded".to_string()));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        for name in ["cells.json", "", "..", "a/b"] {
+            assert!(
+                xapp.add_symptomdata_hook(name, || Ok(vec![])).is_err(),
+                "{}",
+                name
+            );
+        }
+
+        let archive = xapp.symptom_data().collect();
+        assert!(archive.is_ok(), "{}", archive.err().unwrap());
+        let archive = archive.unwrap();
+
+        let mut files = BTreeMap::new();
+        let mut archive = tar::Archive::new(archive.as_slice());
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            let mut contents = String::new();
+            let _ = entry.read_to_string(&mut contents).unwrap();
+            let _ = files.insert(path, contents);
+        }
+
+        for file in [
+            "config.json",
+            "metrics.txt",
+            "logs.txt",
+            "alarms.json",
+            "registration.json",
+            "subscriptions.json",
+            "health.json",
+        ] {
+            assert!(
+                files.contains_key(&format!("symptomdata/{}", file)),
+                "{}",
+                file
+            );
+        }
+
+        let config: serde_json::Value =
+            serde_json::from_str(&files["symptomdata/config.json"]).unwrap();
+        assert_eq!(config["metadata"]["xappName"], "tests");
+        let registration: serde_json::Value =
+            serde_json::from_str(&files["symptomdata/registration.json"]).unwrap();
+        assert_eq!(registration["registered"], false);
+        assert_eq!(files["symptomdata/app/cells.json"], r#"["cell-1"]"#);
+        assert!(!files.contains_key("symptomdata/app/model.bin"));
+        assert_eq!(
+            files["symptomdata/errors.txt"],
+            "app/model.bin: Model not loaded\n"
+        );
+    }
+}
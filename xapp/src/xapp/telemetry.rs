@@ -0,0 +1,275 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Tracing of the XApp using OpenTelemetry
+//!
+//! With the `tracing` feature enabled, the framework creates following spans using the `tracing`
+//! crate -
+//!
+//! - `rmr_message`: For every received RMR Message processed by a handler registered using
+//!   `register_handler`.
+//! - `sdl`: For the SDL operations of the `RedisStorage`. When performed inside a handler, these
+//!   are the child spans of the `rmr_message` span.
+//! - `http_request`: For the HTTP requests to the App Manager, Subscription Manager and Alarm
+//!   Manager. The trace context is propagated to the service using the W3C `traceparent` header.
+//!
+//! `XApp::enable_tracing` exports the spans to an OpenTelemetry Collector over OTLP. Without the
+//! feature, none of the spans are created.
+//!
+//! ```ignore
+//!     xapp.enable_tracing("http://otel-collector:4318/v1/traces")?;
+//!     xapp.start();
+//! ```
+
+use reqwest::header::HeaderMap;
+
+#[cfg(feature = "tracing")]
+use reqwest::header::{HeaderName, HeaderValue};
+
+#[cfg(feature = "tracing")]
+use opentelemetry::trace::TracerProvider as _;
+#[cfg(feature = "tracing")]
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+#[cfg(feature = "tracing")]
+use opentelemetry_sdk::trace::SdkTracerProvider;
+#[cfg(feature = "tracing")]
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+#[cfg(feature = "tracing")]
+use tracing_subscriber::layer::SubscriberExt;
+
+#[cfg(feature = "tracing")]
+use super::XApp;
+#[cfg(feature = "tracing")]
+use crate::XAppError;
+
+/// A span of the framework, entered till the guard is dropped.
+///
+/// Without the `tracing` feature, this does nothing.
+pub(crate) struct SpanGuard {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+impl SpanGuard {
+    /// Record the HTTP status code of the response.
+    pub(crate) fn record_status(&self, _status: u16) {
+        #[cfg(feature = "tracing")]
+        {
+            let _ = self.span.record("http.status_code", _status);
+            if _status >= 400 {
+                let _ = self.span.record("otel.status_code", "ERROR");
+            }
+        }
+    }
+
+    /// Record that the operation in the span failed.
+    pub(crate) fn record_error(&self, _error: &str) {
+        #[cfg(feature = "tracing")]
+        {
+            let _ = self.span.record("otel.status_code", "ERROR");
+            let _ = self.span.record("error", _error);
+        }
+    }
+
+    /// Inject the trace context of the span in the headers of the HTTP request.
+    pub(crate) fn inject_context(&self, _headers: &mut HeaderMap) {
+        #[cfg(feature = "tracing")]
+        {
+            let context = self.span.context();
+            opentelemetry::global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(&context, &mut HeaderInjector(_headers))
+            });
+        }
+    }
+}
+
+/// Span for processing a received RMR Message.
+pub(crate) fn rmr_message_span(_message_type: i32, _meid: Option<&str>) -> SpanGuard {
+    SpanGuard {
+        #[cfg(feature = "tracing")]
+        span: tracing::info_span!(
+            "rmr_message",
+            message_type = _message_type,
+            meid = _meid.unwrap_or_default(),
+            otel.kind = "consumer",
+            otel.status_code = tracing::field::Empty,
+            error = tracing::field::Empty,
+        )
+        .entered(),
+    }
+}
+
+/// Span for a HTTP request to the RIC Platform service.
+pub(crate) fn http_request_span(_service: &str, _method: &str, _url: &str) -> SpanGuard {
+    SpanGuard {
+        #[cfg(feature = "tracing")]
+        span: tracing::info_span!(
+            "http_request",
+            service = _service,
+            http.method = _method,
+            http.url = _url,
+            http.status_code = tracing::field::Empty,
+            otel.kind = "client",
+            otel.status_code = tracing::field::Empty,
+            error = tracing::field::Empty,
+        )
+        .entered(),
+    }
+}
+
+// Sets the trace context headers in the `HeaderMap` of a request.
+#[cfg(feature = "tracing")]
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+#[cfg(feature = "tracing")]
+impl opentelemetry::propagation::Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            let _ = self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl XApp {
+    /// Export the spans of the XApp over OTLP
+    ///
+    /// The spans are exported in batches to the given OTLP/HTTP traces endpoint of the
+    /// OpenTelemetry Collector (eg. `http://otel-collector:4318/v1/traces`). The standard
+    /// `OTEL_EXPORTER_OTLP_*` environment variables take precedence over the `endpoint`.
+    ///
+    /// This installs the global `tracing` subscriber and hence fails if the application has
+    /// already installed one. Applications with their own subscriber should add a
+    /// `tracing_opentelemetry` layer to it instead. Remaining spans are exported in `join`.
+    pub fn enable_tracing(&mut self, endpoint: &str) -> Result<(), XAppError> {
+        use opentelemetry_otlp::WithExportConfig;
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| XAppError(format!("OTLP Exporter: {}", e)))?;
+
+        let service_name = self
+            .app_name
+            .clone()
+            .unwrap_or_else(|| self.config.current().metadata.xapp_name.clone());
+        let resource = opentelemetry_sdk::Resource::builder()
+            .with_service_name(service_name)
+            .build();
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource)
+            .build();
+
+        install_subscriber(&provider)?;
+        let _ = self.tracer_provider.replace(provider);
+
+        Ok(())
+    }
+
+    // Exports the remaining spans and shuts down the exporter.
+    pub(crate) fn shutdown_tracing(&mut self) {
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                log::warn!("Error: '{}' in shutting down the tracer provider.", e);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn tracing_subscriber(provider: &SdkTracerProvider) -> impl tracing::Subscriber + Send + Sync {
+    let tracer = provider.tracer("xapp");
+    tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+#[cfg(feature = "tracing")]
+fn install_subscriber(provider: &SdkTracerProvider) -> Result<(), XAppError> {
+    tracing::subscriber::set_global_default(tracing_subscriber(provider))
+        .map_err(|e| XAppError(format!("Tracing subscriber: {}", e)))?;
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use opentelemetry::trace::SpanKind;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use reqwest::blocking::Client as ReqwestClient;
+
+    use super::{rmr_message_span, tracing_subscriber};
+    use crate::xapp::metrics::instrumentation::{Instrumentation, APPMGR_SERVICE};
+    use crate::xapp::test_utils::http_stub;
+
+    #[test]
+    fn test_spans_exported() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+
+        let appmgr = http_stub(vec![503]);
+        let client = ReqwestClient::new();
+        tracing::subscriber::with_default(tracing_subscriber(&provider), || {
+            let span = rmr_message_span(12010, Some("gnb_001"));
+            let response = Instrumentation::default().send_request(
+                APPMGR_SERVICE,
+                &client,
+                client.get(format!("{}/ric/v1/health/alive", appmgr.url())),
+            );
+            assert!(response.is_ok());
+            drop(span);
+        });
+        assert!(provider.force_flush().is_ok());
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2, "{:?}", spans);
+
+        let http = spans.iter().find(|s| s.name == "http_request").unwrap();
+        let rmr = spans.iter().find(|s| s.name == "rmr_message").unwrap();
+        assert_eq!(rmr.span_kind, SpanKind::Consumer);
+        assert_eq!(http.span_kind, SpanKind::Client);
+        assert_eq!(http.parent_span_id, rmr.span_context.span_id());
+        assert_eq!(http.span_context.trace_id(), rmr.span_context.trace_id());
+        assert!(matches!(
+            http.status,
+            opentelemetry::trace::Status::Error { .. }
+        ));
+
+        for (span, key, value) in [
+            (rmr, "message_type", "12010"),
+            (rmr, "meid", "gnb_001"),
+            (http, "service", "appmgr"),
+            (http, "http.method", "GET"),
+            (http, "http.status_code", "503"),
+        ] {
+            assert!(
+                span.attributes
+                    .iter()
+                    .any(|kv| kv.key.as_str() == key && kv.value.as_str() == value),
+                "{}={} not found in {:?}",
+                key,
+                value,
+                span.attributes
+            );
+        }
+    }
+}
@@ -0,0 +1,200 @@
+// ==================================================================================
+//   Copyright (c) 2023 Abhijit Gadgil
+//
+//   Licensed under the Apache License, Version 2.0 (the "License");
+//   you may not use this file except in compliance with the License.
+//   You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+//   Unless required by applicable law or agreed to in writing, software
+//   distributed under the License is distributed on an "AS IS" BASIS,
+//   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//   See the License for the specific language governing permissions and
+//   limitations under the License.
+// ==================================================================================
+
+//! Helpers shared by the unit tests of the XApp framework.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+
+use sdl::{DataMap, KeySet, SdlError, SdlStorageApi, ValueType};
+use xapp_testkit::{FakeEndpoints, FakeServer};
+
+use crate::{PlatformEndpoints, XApp};
+
+// The fakes of the Platform services are used as the Platform Endpoints of the XApp.
+impl From<FakeEndpoints> for PlatformEndpoints {
+    fn from(endpoints: FakeEndpoints) -> Self {
+        Self {
+            appmgr: endpoints.appmgr,
+            submgr: endpoints.submgr,
+            alarmmgr: endpoints.alarmmgr,
+        }
+    }
+}
+
+// An in-memory SDL Storage for the tests.
+#[derive(Default)]
+pub(crate) struct MemoryStorage {
+    pub(crate) data: HashMap<String, Vec<u8>>,
+    pub(crate) groups: HashMap<String, Vec<Vec<u8>>>,
+}
+
+impl SdlStorageApi for MemoryStorage {
+    fn is_ready(&mut self, _namespace: &str) -> bool {
+        true
+    }
+
+    fn set(&mut self, _namespace: &str, data: &DataMap) -> Result<(), SdlError> {
+        self.data.extend(data.clone());
+        Ok(())
+    }
+
+    fn set_if_not_exists(&mut self, _: &str, key: &str, value: &[u8]) -> Result<(), SdlError> {
+        let _ = self
+            .data
+            .entry(key.to_string())
+            .or_insert_with(|| value.to_vec());
+        Ok(())
+    }
+
+    fn get(&mut self, _namespace: &str, keys: &KeySet) -> Result<DataMap, SdlError> {
+        Ok(keys
+            .iter()
+            .filter_map(|k| self.data.get(k).map(|v| (k.clone(), v.clone())))
+            .collect())
+    }
+
+    fn delete(&mut self, _namespace: &str, keys: &KeySet) -> Result<(), SdlError> {
+        self.data.retain(|k, _| !keys.contains(k));
+        Ok(())
+    }
+
+    fn delete_if(&mut self, _: &str, key: &str, value: &[u8]) -> Result<bool, SdlError> {
+        if self.data.get(key).map(|v| v.as_slice()) == Some(value) {
+            let _ = self.data.remove(key);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn list_keys(&mut self, _: &str, pattern: &str) -> Result<KeySet, SdlError> {
+        Ok(self
+            .data
+            .keys()
+            .filter(|k| glob_match(pattern.as_bytes(), k.as_bytes()))
+            .cloned()
+            .collect())
+    }
+
+    fn delete_all(&mut self, _: &str) -> Result<(), SdlError> {
+        self.data.clear();
+        self.groups.clear();
+        Ok(())
+    }
+
+    fn add_member(&mut self, _: &str, group: &str, value: &ValueType) -> Result<(), SdlError> {
+        let members = self.groups.entry(group.to_string()).or_default();
+        if !members.contains(value) {
+            members.push(value.clone());
+        }
+        Ok(())
+    }
+
+    fn delete_member(&mut self, _: &str, group: &str, value: &ValueType) -> Result<(), SdlError> {
+        if let Some(members) = self.groups.get_mut(group) {
+            members.retain(|m| m != value);
+        }
+        Ok(())
+    }
+
+    fn get_members(&mut self, _: &str, group: &str) -> Result<Vec<Vec<u8>>, SdlError> {
+        Ok(self.groups.get(group).cloned().unwrap_or_default())
+    }
+
+    fn del_group(&mut self, _: &str, group: &str) -> Result<(), SdlError> {
+        let _ = self.groups.remove(group);
+        Ok(())
+    }
+}
+
+// Matches the key with a Redis style glob pattern, supporting only `*` and `?`.
+fn glob_match(pattern: &[u8], key: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => key.is_empty(),
+        Some((b'*', rest)) => (0..=key.len()).any(|i| glob_match(rest, &key[i..])),
+        Some((b'?', rest)) => !key.is_empty() && glob_match(rest, &key[1..]),
+        Some((c, rest)) => key.first() == Some(c) && glob_match(rest, &key[1..]),
+    }
+}
+
+// An HTTP Server for the tests. Responds to every request with the next status from the
+// `statuses` (the last one is repeated). The requests are recorded by the server.
+pub(crate) fn http_stub(statuses: Vec<u16>) -> FakeServer {
+    let statuses = Arc::new(Mutex::new((statuses.into_iter(), 200)));
+
+    let router = axum::Router::new().fallback(move || {
+        let mut statuses = statuses.lock().unwrap();
+        let (ref mut next, ref mut status) = *statuses;
+        if let Some(next) = next.next() {
+            *status = next;
+        }
+        let status = StatusCode::from_u16(*status).unwrap();
+        async move { status }
+    });
+
+    FakeServer::start(router).expect("Start HTTP Stub")
+}
+
+// An XApp for the tests, with RMR listening on a free port. Only one XApp can exist at a time, so
+// the XApp should be dropped by the end of the test.
+pub(crate) fn test_xapp() -> XApp {
+    let rmr_port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Bind a free port")
+        .port();
+    let config = crate::xapp::tests::get_config_data(rmr_port);
+
+    let (app_tx, _) = std::sync::mpsc::channel();
+    let xapp = XApp::from_config(config, app_tx);
+    assert!(xapp.is_ok(), "{:?}", xapp.err().unwrap());
+    xapp.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl::{KeySet, SdlStorageApi};
+
+    use super::MemoryStorage;
+
+    #[test]
+    fn test_memory_storage() {
+        let mut sdl = MemoryStorage::default();
+        let ns = "tests";
+
+        assert!(sdl.set_if_not_exists(ns, "sub_1", b"one").is_ok());
+        assert!(sdl.set_if_not_exists(ns, "sub_1", b"uno").is_ok());
+        assert!(sdl.set_if_not_exists(ns, "alarm_1", b"two").is_ok());
+        assert_eq!(sdl.data["sub_1"], b"one".to_vec());
+
+        let keys = sdl.list_keys(ns, "sub_*").unwrap();
+        assert_eq!(keys, KeySet::from(["sub_1".to_string()]));
+        assert_eq!(sdl.list_keys(ns, "*").unwrap().len(), 2);
+
+        assert!(!sdl.delete_if(ns, "sub_1", b"uno").unwrap());
+        assert!(sdl.delete_if(ns, "sub_1", b"one").unwrap());
+        assert!(sdl.list_keys(ns, "sub_?").unwrap().is_empty());
+
+        assert!(sdl.add_member(ns, "group", &b"member".to_vec()).is_ok());
+        assert!(sdl.del_group(ns, "group").is_ok());
+        assert!(sdl.get_members(ns, "group").unwrap().is_empty());
+
+        assert!(sdl.delete_all(ns).is_ok());
+        assert!(sdl.data.is_empty());
+    }
+}
@@ -14,68 +14,648 @@
 //   limitations under the License.
 // ==================================================================================
 
-use std::sync::OnceLock;
+//! Web Server of the XApp
+//!
+//! The framework serves the health, config, metrics etc. routes under `/ric/v1/`. The XApps can
+//! add their own routes or nested routers, using `add_route` and `nest_router`, which are served
+//! by the same web server on the `http` port of the XApp.
+//!
+//! ```ignore
+//!     use xapp::axum::{routing::get, Json, Router};
+//!
+//!     xapp.add_route("/status", get(|| async { Json("Running") }))?;
+//!     xapp.nest_router("/debug", Router::new().route("/cells", get(list_cells)))?;
+//!     xapp.start();
+//! ```
 
-use tokio::sync::mpsc::Receiver as TokioSyncReceiver;
-use tokio::sync::RwLock;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
-use axum::{routing::get, Json, Router};
+use axum::{
+    extract::Query,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{get, post, MethodRouter},
+    Json, Router,
+};
 
-static METRICS_RECEIVER: OnceLock<RwLock<String>> = OnceLock::new();
+use registration_api::models::{ConfigValidationError, SubscriptionNotification};
+use ric_subscriptions::models::SubscriptionResponse;
 
-async fn metrics_receiver() -> String {
-    let value = METRICS_RECEIVER.get_or_init(|| RwLock::new(String::new()));
-    let value = value.read().await;
-    (*value).clone()
+use super::config::store::ConfigStore;
+use super::discovery::{deliver_xapp_event, XAppEventHandler, XAPP_EVENTS_PATH};
+use super::health::{HealthMonitor, HealthReport, Probe};
+use super::logging::{self, LogLevel};
+use super::message_log::{MessageFilter, MessageLog};
+use super::metrics::MetricsRegistry;
+use super::shutdown;
+use super::subscription::notification::PendingSubscriptions;
+use super::symptomdata::SymptomData;
+use super::XApp;
+use crate::XAppError;
+
+// All the routes of the framework are under this prefix.
+const FRAMEWORK_ROUTES_PREFIX: &str = "/ric/v1";
+
+// Routes added by the XApp, served along with the routes of the framework.
+#[derive(Default)]
+pub(crate) struct AppRoutes {
+    router: Router,
+    paths: HashSet<String>,
+}
+
+impl AppRoutes {
+    // Validates the path of a route or a nested router. The paths should be unique and not
+    // overlap with the routes of the framework.
+    fn validate_path(&self, path: &str, nested: bool) -> Result<(), XAppError> {
+        if !path.starts_with('/') {
+            return Err(XAppError(format!(
+                "Path '{}' should start with a '/'.",
+                path
+            )));
+        }
+
+        if nested && path.contains('*') {
+            return Err(XAppError(format!(
+                "Nested path '{}' may not contain wildcards.",
+                path
+            )));
+        }
+
+        validate_parameters(path)?;
+
+        let path = path.trim_end_matches('/');
+        let reserved = is_under(path, FRAMEWORK_ROUTES_PREFIX)
+            || (nested && is_under(FRAMEWORK_ROUTES_PREFIX, path));
+        if reserved {
+            return Err(XAppError(format!(
+                "Path '{}' overlaps with the routes of the framework.",
+                path
+            )));
+        }
+
+        if self.paths.contains(path) {
+            return Err(XAppError(format!("Path '{}' is already added.", path)));
+        }
+
+        if let Some(added) = self
+            .paths
+            .iter()
+            .find(|added| parameters_conflict(added, path))
+        {
+            return Err(XAppError(format!(
+                "Parameters of path '{}' conflict with the path '{}'.",
+                path, added
+            )));
+        }
+
+        Ok(())
+    }
+
+    // Axum panics on the routes it cannot insert, which is reported as an error instead. The
+    // router is left unchanged on error.
+    fn update(&mut self, path: &str, f: impl FnOnce(Router) -> Router) -> Result<(), XAppError> {
+        let router = self.router.clone();
+        let router =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(router))).map_err(|e| {
+                let reason = e
+                    .downcast_ref::<&str>()
+                    .map(|r| r.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                XAppError(format!("Path '{}' cannot be added: {}", path, reason))
+            })?;
+
+        self.router = router;
+        let _ = self.paths.insert(path.trim_end_matches('/').to_string());
+
+        Ok(())
+    }
+}
+
+// Parameters (`:name`) and wildcards (`*name`) in the path should be named and the wildcard can
+// only be at the end of the path.
+fn validate_parameters(path: &str) -> Result<(), XAppError> {
+    for (i, c) in path.char_indices().filter(|(_, c)| *c == ':' || *c == '*') {
+        let rest = &path[i + 1..];
+        if rest.is_empty() || rest.starts_with('/') {
+            return Err(XAppError(format!(
+                "Parameters in path '{}' should be named.",
+                path
+            )));
+        }
+        if c == '*' && rest.contains('/') {
+            return Err(XAppError(format!(
+                "Wildcard in path '{}' should be at the end.",
+                path
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Do the paths have differently named parameters at the same position (eg. `/a/:id` and
+// `/a/:name/b`)? Such routes cannot be added to the same router.
+fn parameters_conflict(added: &str, path: &str) -> bool {
+    let is_parameter = |segment: &str| segment.starts_with(':') || segment.starts_with('*');
+    for (a, b) in added.split('/').zip(path.split('/')) {
+        if a == b {
+            continue;
+        }
+        return is_parameter(a) && is_parameter(b);
+    }
+
+    false
+}
+
+// Is the `path` same as or under the `prefix`?
+fn is_under(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+impl XApp {
+    /// Add a route to the Web Server of the XApp
+    ///
+    /// The route is served on the `http` port of the XApp along with the routes of the
+    /// framework. The routes should be added before `start`. Paths under `/ric/v1` are reserved
+    /// for the framework. Any state required by the handlers should be provided by the
+    /// `method_router`, eg. using `with_state`.
+    pub fn add_route(&mut self, path: &str, method_router: MethodRouter) -> Result<(), XAppError> {
+        let app_routes = self.app_routes_mut()?;
+        app_routes.validate_path(path, false)?;
+
+        app_routes.update(path, |router| router.route(path, method_router))
+    }
+
+    /// Nest a router at the given path in the Web Server of the XApp
+    ///
+    /// All the routes of the `router` are served under the `path`. The `path` may not contain
+    /// wildcards and may not overlap with the routes of the framework. See `add_route` for
+    /// details.
+    pub fn nest_router(&mut self, path: &str, router: Router) -> Result<(), XAppError> {
+        let app_routes = self.app_routes_mut()?;
+        app_routes.validate_path(path, true)?;
+
+        app_routes.update(path, |app_router| app_router.nest(path, router))
+    }
+
+    fn app_routes_mut(&mut self) -> Result<&mut AppRoutes, XAppError> {
+        self.app_routes.as_mut().ok_or_else(|| {
+            XAppError("Routes should be added before starting the XApp.".to_string())
+        })
+    }
+
+    // State of the framework for the Web Server.
+    pub(crate) fn framework_state(&self) -> FrameworkState {
+        FrameworkState {
+            config: Arc::clone(&self.config),
+            health: Arc::clone(&self.health),
+            pending_subscriptions: Arc::clone(&self.pending_subscriptions),
+            metrics_registry: self.metrics.as_ref().map(Arc::clone),
+            symptom_data: Arc::new(self.symptom_data()),
+            message_log: Arc::clone(&self.message_log),
+            xapp_event_handler: Arc::clone(&self.xapp_event_handler),
+        }
+    }
+
+    // The routes are moved to the Web Server when the XApp is started.
+    pub(crate) fn take_app_routes(&mut self) -> Router {
+        self.app_routes
+            .take()
+            .map(|app_routes| app_routes.router)
+            .unwrap_or_default()
+    }
+}
+
+// Metrics are encoded from the registry for every scrape, in the format accepted by the scraper.
+async fn metrics(
+    metrics: Option<Arc<Mutex<MetricsRegistry>>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let metrics = match metrics {
+        Some(metrics) => metrics,
+        None => {
+            return (
+                StatusCode::NOT_FOUND,
+                [(header::CONTENT_TYPE, "text/plain")],
+                "Metrics are not enabled for the XApp.".to_string(),
+            )
+        }
+    };
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok());
+    let (content_type, body) = metrics.lock().unwrap().encode_for_accept(accept);
+
+    (StatusCode::OK, [(header::CONTENT_TYPE, content_type)], body)
+}
+
+async fn get_log_level() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "level": LogLevel::from_level_filter(log::max_level()) }))
+}
+
+// Log Level is given either as a `mdclog` numeric level or the name of the level.
+async fn set_log_level(request: serde_json::Value) -> (StatusCode, Json<serde_json::Value>) {
+    let level = serde_json::from_value::<LogLevel>(request["level"].clone())
+        .map_err(|e| crate::XAppError(format!("Invalid Log Level: {}", e)))
+        .and_then(|level| level.to_level_filter());
+
+    match level {
+        Ok(level) => {
+            log::info!("Setting Log Level to: {}", level);
+            logging::set_level(level);
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "level": LogLevel::from_level_filter(level) })),
+            )
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
+}
+
+// Config updates from the App Manager, the callback of the XApp may block, hence the update is
+// not run on the async runtime. Like `GET`, the configs are a list, which should contain only the
+// config of the XApp.
+async fn update_config(
+    config: Arc<ConfigStore>,
+    mut new_configs: Vec<crate::XAppConfig>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if new_configs.len() != 1 {
+        let errors = vec![ConfigValidationError::new(
+            "config".to_string(),
+            format!(
+                "Expected the config of the XApp only, got {} configs",
+                new_configs.len()
+            ),
+        )];
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!(errors)));
+    }
+    let new_config = new_configs.remove(0);
+
+    let result =
+        tokio::task::spawn_blocking(move || config.update(new_config).map(|_| config.current()))
+            .await
+            .expect("Config Update Task Failed");
+
+    match result {
+        Ok(config) => (StatusCode::OK, Json(serde_json::json!([config.as_ref()]))),
+        Err(errors) => {
+            log::warn!("Invalid XApp Config: {:?}", errors);
+            (StatusCode::BAD_REQUEST, Json(serde_json::json!(errors)))
+        }
+    }
+}
+
+// Symptom Data is collected from all the parts of the XApp (which may block) and is returned as a
+// `tar` archive.
+async fn symptomdata(symptom_data: Arc<SymptomData>) -> impl IntoResponse {
+    let result = tokio::task::spawn_blocking(move || {
+        symptom_data
+            .collect()
+            .map(|archive| (symptom_data.file_name(), archive))
+    })
+    .await
+    .expect("Symptom Data Task Failed");
+
+    match result {
+        Ok((file_name, archive)) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/x-tar".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", file_name),
+                ),
+            ],
+            archive,
+        ),
+        Err(e) => {
+            log::error!("Error collecting Symptom Data: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [
+                    (header::CONTENT_TYPE, "text/plain".to_string()),
+                    (header::CONTENT_DISPOSITION, "inline".to_string()),
+                ],
+                e.to_string().into_bytes(),
+            )
+        }
+    }
+}
+
+async fn recent_messages(
+    message_log: Arc<MessageLog>,
+    filter: MessageFilter,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !message_log.is_enabled() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Message Log is not enabled for the XApp." })),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!(message_log.records(&filter))),
+    )
+}
+
+// App Manager notifies the events about the XApps to this end point. The callback of the XApp may
+// block, hence it is not run on the async runtime.
+async fn xapp_event(handler: XAppEventHandler, event: SubscriptionNotification) -> StatusCode {
+    tokio::task::spawn_blocking(move || deliver_xapp_event(&handler, &event))
+        .await
+        .expect("XApp Event Task Failed");
+
+    StatusCode::OK
+}
+
+// Health checks may block (eg. SDL), hence they are not run on the async runtime nor with the
+// Health Monitor locked.
+async fn health_report(
+    health: Arc<Mutex<HealthMonitor>>,
+    probes: &'static [Probe],
+) -> HealthReport {
+    tokio::task::spawn_blocking(move || {
+        let health = health
+            .lock()
+            .expect("Corrupted HealthMonitor Mutex")
+            .clone();
+        health.report_for(probes)
+    })
+    .await
+    .expect("Health Checks Task Failed")
+}
+
+// Readiness and Liveness probes, the names of the failed checks are returned if any.
+async fn probe(health: Arc<Mutex<HealthMonitor>>, probe: Probe) -> (StatusCode, Json<String>) {
+    let probes: &'static [Probe] = match probe {
+        Probe::Readiness => &[Probe::Readiness],
+        Probe::Liveness => &[Probe::Liveness],
+    };
+    let report = health_report(health, probes).await;
+    let failed = report.failed(probe);
+    if failed.is_empty() {
+        (StatusCode::OK, Json("OK".to_string()))
+    } else {
+        let status = match probe {
+            Probe::Readiness => "NOT READY",
+            Probe::Liveness => "NOT ALIVE",
+        };
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(format!("{}: {}", status, failed.join(", "))),
+        )
+    }
+}
+
+// Subscription Manager notifies the results of the Subscriptions to this end point.
+async fn subscription_notification(
+    pending_subscriptions: Arc<Mutex<PendingSubscriptions>>,
+    notification: SubscriptionResponse,
+) -> StatusCode {
+    log::debug!("Received Subscription Notification: {:?}", notification);
+
+    let mut pending_subscriptions = pending_subscriptions
+        .lock()
+        .expect("Corrupted PendingSubscriptions Mutex");
+    pending_subscriptions.notify(notification);
+
+    StatusCode::OK
+}
+
+// State of the framework, shared with the handlers of the framework routes.
+pub(crate) struct FrameworkState {
+    config: Arc<ConfigStore>,
+    health: Arc<Mutex<HealthMonitor>>,
+    pending_subscriptions: Arc<Mutex<PendingSubscriptions>>,
+    metrics_registry: Option<Arc<Mutex<MetricsRegistry>>>,
+    symptom_data: Arc<SymptomData>,
+    message_log: Arc<MessageLog>,
+    xapp_event_handler: XAppEventHandler,
 }
 
 #[tokio::main]
 pub(crate) async fn run_ready_live_server(
-    config: crate::XAppConfig,
-    mut data_rx: TokioSyncReceiver<String>,
+    state: FrameworkState,
+    app_routes: Router,
+    shutdown: tokio::sync::watch::Receiver<bool>,
 ) -> Result<(), crate::XAppError> {
     log::info!("Starting Ready and Alive handlers!");
 
-    let port_num = crate::XApp::port_from_config(&config, "http")?;
+    let port_num = crate::XApp::port_from_config(&state.config.current(), "http")?;
 
-    let webapp = Router::new()
-        .route("/ric/v1/health/ready", get(|| async { Json("OK") }))
-        .route("/ric/v1/health/alive", get(|| async { Json("OK") }))
-        .route("/ric/v1/config", get(|| async { Json(vec![config]) }))
-        .route("/ric/v1/metrics", get(metrics_receiver));
+    let webapp = framework_routes(state).merge(app_routes);
 
     let bind_address = format!("0.0.0.0:{port_num}");
-    let server =
-        axum::Server::bind(&bind_address.parse().unwrap()).serve(webapp.into_make_service());
-
-    tokio::pin!(server);
-
-    loop {
-        tokio::select! {
-            // Tokio sync receiver has got a non-blocking `recv` method which returns a `Future`.
-            Some(v) = data_rx.recv() => {
-                let mut parts = v.split(':');
-                if let Some(val) = parts.next() {
-                    if val == "metrics" {
-                        if let Some(metrics) = parts.next() {
-                            log::debug!("Updating Metrics: {metrics:#?}");
-                            let value = METRICS_RECEIVER.get_or_init(|| RwLock::new(String::new()));
-                            let mut value = value.write().await;
-                            value.clear();
-                            value.push_str(metrics);
-                        }
-                    } else {
-                        log::debug!("Received Data: {v:?} does not match the expected format!");
-                    }
-                } else {
-                    log::debug!("Received Data: {v:?} does not match the expected format!");
-                    break;
-                }
-            }
-            _ = &mut server => break,
+    axum::Server::bind(&bind_address.parse().unwrap())
+        .serve(webapp.into_make_service())
+        .with_graceful_shutdown(shutdown::stopped(shutdown))
+        .await
+        .map_err(|e| crate::XAppError(format!("Web Server Error: {}", e)))
+}
+
+// Config of the XApp, `GET` and `POST` (or `PUT`) use the same list of configs.
+fn config_routes(config: Arc<ConfigStore>) -> Router {
+    let current_config = Arc::clone(&config);
+    let get_config = move || async move { Json(vec![current_config.current().as_ref().clone()]) };
+    let update =
+        move |Json(new_configs): Json<Vec<crate::XAppConfig>>| update_config(config, new_configs);
+
+    Router::new().route(
+        "/ric/v1/config",
+        get(get_config).post(update.clone()).put(update),
+    )
+}
+
+pub(crate) fn framework_routes(state: FrameworkState) -> Router {
+    let FrameworkState {
+        config,
+        health,
+        pending_subscriptions,
+        metrics_registry,
+        symptom_data,
+        message_log,
+        xapp_event_handler,
+    } = state;
+
+    let ready_health = Arc::clone(&health);
+    let alive_health = Arc::clone(&health);
+    Router::new()
+        .route(
+            "/ric/v1/health/ready",
+            get(move || probe(ready_health.clone(), Probe::Readiness)),
+        )
+        .route(
+            "/ric/v1/health/alive",
+            get(move || probe(alive_health.clone(), Probe::Liveness)),
+        )
+        .route(
+            "/ric/v1/health",
+            get(move || async move {
+                Json(health_report(health.clone(), &[Probe::Readiness, Probe::Liveness]).await)
+            }),
+        )
+        .merge(config_routes(config))
+        .route(
+            "/ric/v1/metrics",
+            get(move |headers: HeaderMap| metrics(metrics_registry.clone(), headers)),
+        )
+        .route(
+            "/ric/v1/symptomdata",
+            get(move || symptomdata(symptom_data.clone())),
+        )
+        .route(
+            "/ric/v1/debug/messages",
+            get(move |Query(filter): Query<MessageFilter>| {
+                recent_messages(message_log.clone(), filter)
+            }),
+        )
+        .route(
+            "/ric/v1/loglevel",
+            get(get_log_level).put(|Json(request): Json<serde_json::Value>| set_log_level(request)),
+        )
+        .route(
+            "/ric/v1/subscriptions",
+            post(move |Json(notification): Json<SubscriptionResponse>| {
+                subscription_notification(pending_subscriptions.clone(), notification)
+            }),
+        )
+        .route(
+            XAPP_EVENTS_PATH,
+            post(move |Json(event): Json<SubscriptionNotification>| {
+                xapp_event(xapp_event_handler.clone(), event)
+            }),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{routing::get, Json, Router};
+
+    use xapp_testkit::FakeServer;
+
+    use super::{config_routes, framework_routes};
+    use crate::xapp::config::store::ConfigStore;
+    use crate::xapp::test_utils::test_xapp;
 
+    #[test]
+    fn test_app_routes() {
+        let mut xapp = test_xapp();
+
+        let result = xapp.add_route("/status", get(|| async { Json("Running") }));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        let debug = Router::new().route("/cells", get(|| async { Json(vec!["cell-1"]) }));
+        let result = xapp.nest_router("/debug", debug);
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+
+        for path in [
+            "status",
+            "/status/",
+            "/ric/v1/health/ready",
+            "/ric/v1/custom",
+        ] {
+            let result = xapp.add_route(path, get(|| async { "" }));
+            assert!(result.is_err(), "{}", path);
+        }
+        let result = xapp.add_route("/cells/:id", get(|| async { "" }));
+        assert!(result.is_ok(), "{}", result.err().unwrap());
+        for path in [
+            "/x/:",
+            "/x/*",
+            "/x/*rest/y",
+            "/cells/:name",
+            "/cells/:name/neighbours",
+        ] {
+            let result = xapp.add_route(path, get(|| async { "" }));
+            assert!(result.is_err(), "{}", path);
+        }
+        for path in [
+            "/",
+            "/ric",
+            "/ric/v1",
+            "/debug",
+            "/files/*path",
+            "/n/:",
+            "/cells/:cell",
+        ] {
+            let result = xapp.nest_router(path, Router::new().route("/", get(|| async { "" })));
+            assert!(result.is_err(), "{}", path);
+        }
+        // Overlaps with `/cells/:id`, rejected by axum.
+        let cells = Router::new().route("/:id", get(|| async { "" }));
+        assert!(xapp.nest_router("/cells", cells).is_err());
+        // Not a framework route.
+        assert!(xapp.nest_router("/ricx", Router::new()).is_ok());
+
+        let webapp = framework_routes(xapp.framework_state()).merge(xapp.take_app_routes());
+        assert!(xapp.add_route("/later", get(|| async { "" })).is_err());
+
+        let server = FakeServer::start(webapp).unwrap();
+
+        for (path, expected) in [
+            ("/status", r#""Running""#),
+            ("/debug/cells", r#"["cell-1"]"#),
+            ("/cells/1", ""),
+            ("/ric/v1/health/alive", r#""OK""#),
+        ] {
+            let response = reqwest::blocking::get(format!("{}{}", server.url(), path));
+            assert!(response.is_ok(), "{:?}", response.err().unwrap());
+            let response = response.unwrap();
+            assert!(response.status().is_success(), "{}", path);
+            assert_eq!(response.text().unwrap(), expected);
         }
     }
 
-    Ok(())
+    #[test]
+    fn test_config_round_trip() {
+        let store = ConfigStore::new(crate::xapp::tests::get_config_data(4560));
+        let store = Arc::new(store.unwrap());
+        let server = FakeServer::start(config_routes(Arc::clone(&store))).unwrap();
+        let url = format!("{}/ric/v1/config", server.url());
+        let client = reqwest::blocking::Client::new();
+
+        let get_configs = || -> serde_json::Value {
+            let response = client.get(&url).send().unwrap();
+            assert!(response.status().is_success());
+            response.json().unwrap()
+        };
+
+        // The config from `GET` is updated and sent back.
+        let mut configs = get_configs();
+        assert_eq!(configs.as_array().map(Vec::len), Some(1));
+        configs[0]["config"]["alarms"] =
+            serde_json::json!([{ "id": 8004, "text": "E2 Node down" }]);
+        let response = client.post(&url).json(&configs).send().unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.json::<serde_json::Value>().unwrap(), configs);
+        assert_eq!(get_configs(), configs);
+        assert!(store.alarm_catalog().get(8004).is_some());
+
+        // Invalid Alarm Definitions are rejected and the current ones are kept.
+        let mut invalid = configs.clone();
+        invalid[0]["config"]["alarms"] = serde_json::json!([{ "id": 8005, "text": "" }]);
+        let response = client.post(&url).json(&invalid).send().unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let errors = response.json::<serde_json::Value>().unwrap();
+        assert_eq!(errors[0]["field"], "alarms");
+        assert!(store.alarm_catalog().get(8004).is_some());
+        assert!(store.alarm_catalog().get(8005).is_none());
+
+        // Only the config of the XApp is accepted.
+        let two_configs = serde_json::json!([configs[0], configs[0]]);
+        let response = client.post(&url).json(&two_configs).send().unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let response = client.put(&url).json(&configs[0]).send().unwrap();
+        assert!(response.status().is_client_error());
+        assert_eq!(get_configs(), configs);
+    }
 }
@@ -47,7 +47,7 @@ fn test_xapp_start_rmr_not_ready_stop() {
 
     xapp.stop();
 
-    xapp.join();
-
-    assert!(true);
+    let summary = xapp.join();
+    assert!(summary.timed_out.is_empty(), "{:?}", summary);
+    assert!(!xapp.is_running());
 }